@@ -1,18 +1,41 @@
+import sys
 import boto3
+from boto3.dynamodb.conditions import Key
 dynamo = boto3.resource('dynamodb')
 
-def truncateTable(tableName):
+# Tables with the competition number as partition key
+COMPETITION_TABLES = [
+    "athlete_store_v2",
+    "group_store_v2",
+    "time_group_store_v2",
+    "team_store_v2",
+    "waitlist_store",
+    "audit_store",
+    "official_store",
+    "note_store",
+]
+# Tables shared by all competitions
+GLOBAL_TABLES = ["authentication", "competition_store"]
+
+def truncateTable(tableName, competition=None):
+    """Delete all items of the table, only the items of the competition if given"""
     table = dynamo.Table(tableName)
-    
+
     #get the table keys
     tableKeyNames = [key.get("AttributeName") for key in table.key_schema]
 
     #Only retrieve the keys for each item in the table (minimize data transfer)
     projectionExpression = ", ".join('#' + key for key in tableKeyNames)
     expressionAttrNames = {'#'+key: key for key in tableKeyNames}
-    
+
+    def fetch(**kwargs):
+        if competition is None:
+            return table.scan(ProjectionExpression=projectionExpression, ExpressionAttributeNames=expressionAttrNames, **kwargs)
+        return table.query(KeyConditionExpression=Key("competition").eq(competition),
+                           ProjectionExpression=projectionExpression, ExpressionAttributeNames=expressionAttrNames, **kwargs)
+
     counter = 0
-    page = table.scan(ProjectionExpression=projectionExpression, ExpressionAttributeNames=expressionAttrNames)
+    page = fetch()
     with table.batch_writer() as batch:
         while page["Count"] > 0:
             counter += page["Count"]
@@ -21,19 +44,23 @@ def truncateTable(tableName):
                 batch.delete_item(Key=itemKeys)
             # Fetch the next page
             if 'LastEvaluatedKey' in page:
-                page = table.scan(
-                    ProjectionExpression=projectionExpression, ExpressionAttributeNames=expressionAttrNames,
-                    ExclusiveStartKey=page['LastEvaluatedKey'])
+                page = fetch(ExclusiveStartKey=page['LastEvaluatedKey'])
             else:
                 break
-    print(f"Deleted {counter}")
-    
-    
+    print(f"Deleted {counter} from {tableName}")
+
+
 if __name__ == "__main__":
-    answer = input("Delete all tables of DynamoDB (yes/no)")
+    # python clean_dynamodb_database.py [competition number]
+    competition = sys.argv[1] if len(sys.argv) > 1 else None
+    if competition is None:
+        answer = input("Delete all tables of DynamoDB (yes/no)")
+    else:
+        answer = input(f"Delete all data of competition {competition} (yes/no)")
     if answer == "yes":
-        truncateTable("athlete_store")
-        truncateTable("authentication")
-        truncateTable("group_store")
-        truncateTable("time_group_store")
-        truncateTable("team_store")
\ No newline at end of file
+        for tableName in COMPETITION_TABLES:
+            truncateTable(tableName, competition)
+        # Logins and competitions are shared, they are only deleted together with everything else
+        if competition is None:
+            for tableName in GLOBAL_TABLES:
+                truncateTable(tableName)
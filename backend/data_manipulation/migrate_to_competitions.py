@@ -0,0 +1,99 @@
+"""
+Copy the data of the single-competition tables into the tables keyed by competition (see deployment/MIGRATION.md)
+
+1. competition_store: the competition is created and set active if it does not exist yet
+2. athlete_store, group_store, time_group_store, team_store: every item is copied to the *_v2 table with the
+   competition attribute added. The old tables are only read.
+
+The authentication table is shared by all competitions and not migrated.
+
+Usage: python migrate_to_competitions.py <number> <name> <start date YYYY-MM-DD> <end date YYYY-MM-DD> [table suffix, e.g. _test]
+"""
+
+import sys
+from datetime import datetime, timezone
+import boto3
+from boto3.dynamodb.conditions import Key
+from botocore.exceptions import ClientError
+
+TABLES = ["athlete_store", "group_store", "time_group_store", "team_store"]
+
+dynamo = boto3.resource("dynamodb")
+
+
+def timestamp(date):
+	return int(datetime.strptime(date, "%Y-%m-%d").replace(tzinfo=timezone.utc).timestamp())
+
+
+def create_competition(table_name, number, name, start_date, end_date):
+	table = dynamo.Table(table_name)
+	try:
+		table.put_item(
+			Item={
+				"number": number,
+				"name": name,
+				"start_date": timestamp(start_date),
+				"end_date": timestamp(end_date),
+				"active": True,
+				"anonymized": False,
+			},
+			ConditionExpression="attribute_not_exists(#number)",
+			ExpressionAttributeNames={"#number": "number"},
+		)
+		print(f"Competition {number} created")
+	except ClientError as e:
+		if e.response["Error"]["Code"] != "ConditionalCheckFailedException":
+			raise
+		print(f"Competition {number} exists already")
+
+
+def scan(table):
+	page = table.scan()
+	yield from page["Items"]
+	while "LastEvaluatedKey" in page:
+		page = table.scan(ExclusiveStartKey=page["LastEvaluatedKey"])
+		yield from page["Items"]
+
+
+def count_competition(table, number):
+	counter = 0
+	page = table.query(KeyConditionExpression=Key("competition").eq(number), Select="COUNT")
+	counter += page["Count"]
+	while "LastEvaluatedKey" in page:
+		page = table.query(KeyConditionExpression=Key("competition").eq(number), Select="COUNT",
+						   ExclusiveStartKey=page["LastEvaluatedKey"])
+		counter += page["Count"]
+	return counter
+
+
+def copy_table(old_name, new_name, number):
+	"""Copy all items, running it again overwrites the copies with the same content"""
+	old_table = dynamo.Table(old_name)
+	new_table = dynamo.Table(new_name)
+	counter = 0
+	with new_table.batch_writer() as batch:
+		for item in scan(old_table):
+			item["competition"] = number
+			batch.put_item(Item=item)
+			counter += 1
+	copied = count_competition(new_table, number)
+	status = "OK" if copied >= counter else "MISSING ITEMS"
+	print(f"{old_name} -> {new_name}: {counter} read, {copied} stored for competition {number} [{status}]")
+	return copied >= counter
+
+
+if __name__ == "__main__":
+	if len(sys.argv) < 5:
+		print(__doc__)
+		sys.exit(1)
+	NUMBER, NAME, START_DATE, END_DATE = sys.argv[1:5]
+	SUFFIX = sys.argv[5] if len(sys.argv) > 5 else ""
+
+	create_competition("competition_store" + SUFFIX, NUMBER, NAME, START_DATE, END_DATE)
+	complete = True
+	for table_name in TABLES:
+		complete &= copy_table(table_name + SUFFIX, table_name + "_v2" + SUFFIX, NUMBER)
+	if not complete:
+		print("Migration incomplete, do not switch the backend to the new tables")
+		sys.exit(1)
+	print("Migration complete")
@@ -11,6 +11,10 @@ mod time_planner_routes;
 mod auth_routes;
 mod notes_routes;
 mod team_routes;
+mod competition_routes;
+mod privacy_routes;
+mod audit_routes;
+mod official_routes;
 
 #[actix_web::main]
 pub async fn start_server(db_handler: web::Data<Box<dyn Storage + Send + Sync>>) -> Result<()> {
@@ -25,6 +29,10 @@ pub async fn start_server(db_handler: web::Data<Box<dyn Storage + Send + Sync>>)
                 .configure(auth_routes::configure_routes)
                 .configure(notes_routes::configure_routes)
                 .configure(team_routes::configure_routes)
+                .configure(competition_routes::configure_routes)
+                .configure(privacy_routes::configure_routes)
+                .configure(audit_routes::configure_routes)
+                .configure(official_routes::configure_routes)
                 .route("/status", web::get().to(status))
                 //.route("/save_db", web::get().to(save_db)) // TODO: Remove in deployment
                 //.route("/load_db", web::get().to(load_db)), // TODO: Remove in deployment
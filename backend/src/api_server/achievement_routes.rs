@@ -1,6 +1,7 @@
 use actix_web::{get, web, HttpResponse, Responder, post, put, delete};
 use super::parse_json_body;
 use crate::certificate_generation::{Achievement, AchievementID, AthleteID};
+use crate::result_history::{change_result, replay_result, restore_result, result_events, ResultHistoryQuery, RestoreQuery, RoleQuery};
 use crate::Storage;
 
 pub fn configure_routes(cfg: &mut web::ServiceConfig) {
@@ -8,6 +9,24 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(post_achievement);
     cfg.service(update_achievement);
     cfg.service(delete_achievement);
+    cfg.service(get_result_history);
+    cfg.service(get_replayed_result);
+    cfg.service(restore_achievement);
+}
+
+/// Store the changed result together with its event in the result history
+async fn result_change_response(
+    data: &web::Data<Box<dyn Storage + Send + Sync>>,
+    achievement_id: &AchievementID,
+    role: Option<String>,
+    old: Option<Achievement>,
+    new: Option<Achievement>,
+    msg: &str,
+) -> HttpResponse {
+    match change_result(data.as_ref().as_ref(), achievement_id, role, old, new).await {
+        Ok(_) => HttpResponse::Ok().body(msg.to_string()),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error storing Achievement: {}", e))
+    }
 }
 
 #[get("/achievement")]
@@ -29,6 +48,7 @@ async fn post_achievement(
     data: web::Data<Box<dyn Storage + Send + Sync>>,
     body: web::Payload,
     query: web::Query<AthleteID>,
+    role: web::Query<RoleQuery>,
 ) -> impl Responder {
     let json_string = parse_json_body(body).await;
 
@@ -36,12 +56,11 @@ async fn post_achievement(
     let athlete_id = query.into_inner();
     match achievement {
         Ok(achievement) => {
-            match data.write_achievement(AchievementID::build(athlete_id, &achievement), achievement).await {
-                Ok(msg) => {
-                    HttpResponse::Ok().body(msg)
-                }
-                Err(e) => HttpResponse::InternalServerError().body(format!("Error inserting Achievement: {}", e))
+            let achievement_id = AchievementID::build(athlete_id, &achievement);
+            if data.get_achievement(&achievement_id).await.is_some() {
+                return HttpResponse::BadRequest().body("Error inserting Achievement: Achievement exists. Please update existing one!");
             }
+            result_change_response(&data, &achievement_id, role.into_inner().role, None, Some(achievement), "Achievement added").await
         }
         Err(e) => HttpResponse::BadRequest().body(format!("Error parsing Achievement JSON: {}", e))
     }
@@ -52,27 +71,67 @@ async fn update_achievement(
     data: web::Data<Box<dyn Storage + Send + Sync>>,
     body: web::Payload,
     achievement_id: web::Query<AchievementID>,
+    role: web::Query<RoleQuery>,
 ) -> impl Responder {
     let json_string = parse_json_body(body).await;
-
-    match data.update_achievement(achievement_id.into_inner(), json_string.as_str()).await {
-        Ok(msg) => {
-            HttpResponse::Ok().body(msg)
-        },
-        Err(e) => HttpResponse::BadRequest().body(format!("Error updating Achievement: {}", e))
+    let achievement_id = achievement_id.into_inner();
+    let old = match data.get_achievement(&achievement_id).await {
+        Some(old) => old,
+        None => return HttpResponse::BadRequest().body("Error updating Achievement: Key not found"),
+    };
+    let mut achievement = old.clone();
+    if let Err(e) = achievement.update_values(json_string.as_str()) {
+        return HttpResponse::BadRequest().body(format!("Error updating Achievement: {}", e));
     }
+    result_change_response(&data, &achievement_id, role.into_inner().role, Some(old), Some(achievement), "Achievement updated").await
 }
 
 #[delete("/achievement")]
 async fn delete_achievement(
     data: web::Data<Box<dyn Storage + Send + Sync>>,
     query: web::Query<AchievementID>,
+    role: web::Query<RoleQuery>,
 ) -> impl Responder {
     let achievement_id = query.into_inner();
-    match data.delete_achievement(&achievement_id).await {
-        Ok(msg) => {
-            HttpResponse::Ok().body(msg)
-        },
-        Err(e) => HttpResponse::BadRequest().body(format!("Error updating Achievement: {}", e))
+    let old = data.get_achievement(&achievement_id).await;
+    result_change_response(&data, &achievement_id, role.into_inner().role, old, None, "Achievement deleted").await
+}
+
+/// Recorded result changes, filtered by athlete (name, surname) and/or discipline, oldest first
+#[get("/achievement/history")]
+async fn get_result_history(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+    query: web::Query<ResultHistoryQuery>,
+) -> impl Responder {
+    match data.get_audit_records().await {
+        Ok(records) => {
+            let events = result_events(records, &query.into_inner());
+            HttpResponse::Ok().body(serde_json::to_string(&events).expect("Result events should be serializable"))
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error loading result history: {}", e))
+    }
+}
+
+/// Result of an athlete and discipline derived from its history, up to the event given as until
+#[get("/achievement/replay")]
+async fn get_replayed_result(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+    query: web::Query<ResultHistoryQuery>,
+) -> impl Responder {
+    match replay_result(data.as_ref().as_ref(), &query.into_inner()).await {
+        Ok(achievement) => HttpResponse::Ok().body(serde_json::to_string(&achievement).expect("Achievement should be serializable")),
+        Err(e) => HttpResponse::BadRequest().body(format!("Error replaying result: {}", e))
+    }
+}
+
+/// Restore a result to its value after an earlier change
+#[put("/achievement/restore")]
+async fn restore_achievement(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+    query: web::Query<RestoreQuery>,
+) -> impl Responder {
+    match restore_result(data.as_ref().as_ref(), &query.into_inner()).await {
+        Ok(achievement) => HttpResponse::Ok().body(serde_json::to_string(&achievement).expect("Achievement should be serializable")),
+        Err(e) => HttpResponse::BadRequest().body(format!("Error restoring result: {}", e))
     }
 }
@@ -3,17 +3,20 @@ use actix_web::{delete, get, post, put, web, HttpResponse, Responder};
 use serde_json::Value;
 use serde_json::json;
 use super::parse_json_body;
-use crate::certificate_generation::{Athlete, AthleteID, GroupID};
+use crate::certificate_generation::{is_same_person, Athlete, AthleteHistory, AthleteID, AthleteQuery, AthleteUpdate, GroupID};
 use crate::Storage;
+use crate::waitlist::{promote_from_waitlist, waitlist_if_full};
 
 pub fn configure_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(get_athletes);
+    cfg.service(search_athletes);
     cfg.service(get_athlete);
     cfg.service(post_athlete);
     cfg.service(update_athlete);
     cfg.service(delete_athlete);
     cfg.service(post_athlete_with_group);
     cfg.service(get_athlete_group);
+    cfg.service(get_athlete_history);
 }
 
 #[get("/athletes")]
@@ -30,6 +33,22 @@ async fn get_athletes(
     HttpResponse::Ok().body(serde_json::to_string(&group_athletes).expect("Athlete should be serializable"))
 }
 
+#[get("/athletes/search")]
+async fn search_athletes(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+    query: web::Query<AthleteQuery>,
+) -> impl Responder {
+    let mut group_athletes = data.get_athletes().await;
+
+    for (_, athletes) in group_athletes.iter_mut() {
+        for athlete in athletes.iter_mut() {
+            athlete.compute_total_points();
+        }
+    }
+    let result = query.into_inner().search(group_athletes);
+    HttpResponse::Ok().body(serde_json::to_string(&result).expect("Search result should be serializable"))
+}
+
 #[get("/athlete")]
 async fn get_athlete(
     data: web::Data<Box<dyn Storage + Send + Sync>>,
@@ -76,29 +95,61 @@ async fn update_athlete(
     let json_string = parse_json_body(body).await;
     let athlete: AthleteID = athlete_id.into_inner();
 
+    // An athlete registering again keeps the place only if the group is not full in the meantime
+    let registers_again = serde_json::from_str::<Value>(&json_string)
+        .is_ok_and(|value| value.get("deregistered").and_then(Value::as_bool) == Some(false));
+    if registers_again && data.get_athlete(&athlete).await.is_some_and(|athlete| athlete.is_deregistered()) {
+        match waitlist_if_full(data.as_ref().as_ref(), &athlete).await {
+            Ok(Some(position)) => return HttpResponse::Ok().body(format!("Group full. Athlete added to waitlist at position {}", position)),
+            Ok(None) => {}
+            Err(e) => return HttpResponse::InternalServerError().body(format!("Error updating Athlete: {}", e)),
+        }
+    }
+
     match data.update_athlete(athlete.clone(), json_string.as_str()).await {
-        Ok(msg) => {
-            if msg.contains("deregistered") || msg.contains("registered") {
-                let mut update_expression = String::from("");
-                if msg.contains("deregistered") {
-                    update_expression = format!("{}", json!({
-                        "delete_athlete_ids": [{"name": athlete.name(), "surname": athlete.surname()}]
-                    }));
-                }else{
-                    update_expression = format!("{}", json!({
-                        "athlete_ids": [{"name": athlete.name(), "surname": athlete.surname()}]
-                    }));                }
-                match data.get_athlete_group(&athlete).await {
-                    Some(group_id) => {
-                        match data.update_group(group_id, update_expression.as_str(), true).await{
-                            Ok(group_msg) => return HttpResponse::Ok().body(msg + " " +group_msg.as_str()),
-                            Err(e) => return HttpResponse::InternalServerError().body(format!("{} --> Error updating Athlete: {}", msg, e))
+        Ok(AthleteUpdate::Updated) => HttpResponse::Ok().body(AthleteUpdate::Updated.to_string()),
+        Ok(update) => {
+            let msg = update.to_string();
+            let update_expression = match update {
+                AthleteUpdate::Deregistered => json!({
+                    "delete_athlete_ids": [{"name": athlete.name(), "surname": athlete.surname()}]
+                }),
+                _ => json!({
+                    "athlete_ids": [{"name": athlete.name(), "surname": athlete.surname()}]
+                }),
+            }.to_string();
+            match data.get_athlete_group(&athlete).await {
+                Some(group_id) => {
+                    match data.update_group(group_id.clone(), update_expression.as_str(), true).await{
+                        Ok(group_msg) => {
+                            if update != AthleteUpdate::Deregistered {
+                                return HttpResponse::Ok().body(msg + " " + group_msg.as_str());
+                            }
+                            // The free place is given to the next athlete on the waitlist
+                            match promote_from_waitlist(data.as_ref().as_ref(), &group_id).await {
+                                Ok(Some(promotion)) => HttpResponse::Ok().body(format!(
+                                    "{} {} Promoted {} {} from waitlist to {}", msg, group_msg,
+                                    promotion.athlete_id.name(), promotion.athlete_id.surname(), promotion.group_name)),
+                                Ok(None) => HttpResponse::Ok().body(msg + " " + group_msg.as_str()),
+                                Err(e) => HttpResponse::InternalServerError().body(format!("{} --> Error promoting athlete from waitlist: {}", msg, e))
+                            }
+                        }
+                        Err(e) => HttpResponse::InternalServerError().body(format!("{} --> Error updating Athlete: {}", msg, e))
+                    }
+                }
+                None => {
+                    if update == AthleteUpdate::Deregistered {
+                        if let Some(athlete_info) = data.get_athlete(&athlete).await {
+                            if let Ok(mut waitlist) = data.get_waitlist(athlete_info.competition_type()).await {
+                                if waitlist.remove(&athlete).is_some() && data.store_waitlist(&waitlist).await.is_ok() {
+                                    return HttpResponse::Ok().body(msg + " Removed from waitlist");
+                                }
+                            }
                         }
                     }
-                    None => return HttpResponse::Ok().body(msg + " Not in any group"),
+                    HttpResponse::Ok().body(msg + " Not in any group")
                 }
             }
-            HttpResponse::Ok().body(msg)
         }
         Err(e) => HttpResponse::InternalServerError().body(format!("Error updating Athlete: {}", e))
     }
@@ -159,6 +210,35 @@ async fn get_athlete_group(
     }
 }
 
+#[get("/athlete_history")]
+async fn get_athlete_history(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+    query: web::Query<AthleteID>,
+) -> impl Responder {
+    let athlete_id = query.into_inner();
+    let current_athlete = data.get_athlete(&athlete_id).await;
+
+    match data.get_athlete_editions(std::slice::from_ref(&athlete_id)).await {
+        Ok(mut editions) => {
+            let editions: Vec<_> = editions
+                .remove(&athlete_id)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|(_, athlete)| match &current_athlete {
+                    Some(current_athlete) => is_same_person(current_athlete, athlete),
+                    None => true,
+                })
+                .collect();
+            if editions.is_empty() {
+                return HttpResponse::NotFound().body("Athlete not found in any competition");
+            }
+            let history = AthleteHistory::build(&athlete_id, editions);
+            HttpResponse::Ok().body(serde_json::to_string(&history).expect("History should be serializable"))
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error loading athlete history: {}", e))
+    }
+}
+
 async fn add_athlete_to_group(athlete_str: &str, data: web::Data<Box<dyn Storage + Send + Sync>>) -> Result<String, Box<dyn Error>> {
     let value: Value = serde_json::from_str(athlete_str)?;
     match value {
@@ -180,6 +260,16 @@ async fn add_athlete_to_group(athlete_str: &str, data: web::Data<Box<dyn Storage
                 _ => ""
             };
 
+            // Athletes are put on the waitlist of the competition type if the group is full
+            if let Some(group) = data.get_group(&group_id).await {
+                if group.is_full() {
+                    let mut waitlist = data.get_waitlist(&group.competition_type()).await?;
+                    let position = waitlist.add(AthleteID::new(name, surname), group_name);
+                    data.store_waitlist(&waitlist).await?;
+                    return Ok(format!("Group full. Athlete added to waitlist at position {}", position));
+                }
+            }
+
             let json_string = format!(r#"{{"athlete_ids": [{{"name": "{}", "surname": "{}"}}]}}"#, name, surname);
 
             match data.update_group(group_id, json_string.as_str(), false).await {
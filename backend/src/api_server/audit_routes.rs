@@ -0,0 +1,17 @@
+use crate::Storage;
+use actix_web::{get, web, HttpResponse, Responder};
+
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_audit_records);
+}
+
+/// All recorded changes of the active competition (e.g. group switches), oldest first
+#[get("/audit")]
+async fn get_audit_records(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+) -> impl Responder {
+    match data.get_audit_records().await {
+        Ok(records) => HttpResponse::Ok().body(serde_json::to_string(&records).expect("Audit records should be serializable")),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error loading audit log: {}", e))
+    }
+}
@@ -1,6 +1,8 @@
 use actix_web::{get, web, HttpResponse, Responder};
 use itertools::Itertools;
-use crate::certificate_generation::{merge_pdfs, AgeGroupID, AgeGroupIDs, AthleteID, GroupID, PDF, PDFMessage};
+use log::warn;
+use crate::certificate_generation::{is_same_person, merge_pdfs, AgeGroupID, AgeGroupIDs, Athlete, AthleteHistory, AthleteID, GroupID, PDF, PDFMessage};
+use crate::competitions::Competition;
 use crate::time_planner::{TimeGroupID};
 use crate::Storage;
 
@@ -18,12 +20,18 @@ async fn get_certificate(
     data: web::Data<Box<dyn Storage + Send + Sync>>,
     query: web::Query<AthleteID>,
 ) -> impl Responder {
+    let competition = match data.get_active_competition().await {
+        Some(competition) => competition,
+        None => return HttpResponse::BadRequest().body("No active competition"),
+    };
     let achievement_id = query.into_inner();
     let athlete = data.get_athlete(&achievement_id).await;
 
     match athlete {
         Some(athlete) => {
-            let certificate = PDF::new_certificate(&athlete);
+            let mut athletes = vec![athlete];
+            mark_personal_bests(&data, &mut athletes, &competition).await;
+            let certificate = PDF::new_certificate(&athletes[0], &competition);
             let pdf_message = certificate.to_http_message();
             match pdf_message {
                 Ok(pdf) => HttpResponse::Ok()
@@ -42,12 +50,17 @@ async fn get_certificates(
     data: web::Data<Box<dyn Storage + Send + Sync>>,
     query: web::Query<GroupID>,
 ) -> impl Responder {
+    let competition = match data.get_active_competition().await {
+        Some(competition) => competition,
+        None => return HttpResponse::BadRequest().body("No active competition"),
+    };
     let group_id = query.into_inner();
     let group = data.get_group(&group_id).await;
 
     match group {
-        Some(group) => {
-            let certificates = PDF::new_group_certificates(&group);
+        Some(mut group) => {
+            mark_personal_bests(&data, group.mut_athletes(), &competition).await;
+            let certificates = PDF::new_group_certificates(&group, &competition);
             let pdf_message = certificates.to_http_message();
             match pdf_message {
                 Ok(pdf_message) => HttpResponse::Ok()
@@ -66,6 +79,10 @@ async fn get_group_results(
     data: web::Data<Box<dyn Storage + Send + Sync>>,
     query: web::Query<GroupID>,
 ) -> impl Responder {
+    let competition = match data.get_active_competition().await {
+        Some(competition) => competition,
+        None => return HttpResponse::BadRequest().body("No active competition"),
+    };
     let group_id = query.into_inner();
     let group = data.get_group(&group_id).await;
 
@@ -85,8 +102,9 @@ async fn get_group_results(
         .collect());
     
     match group {
-        Some(group) => {
-            let certificate = PDF::new_group_result(&group, included_disciplines);
+        Some(mut group) => {
+            mark_personal_bests(&data, group.mut_athletes(), &competition).await;
+            let certificate = PDF::new_group_result(&group, included_disciplines, &competition);
             let pdf_message = certificate.to_http_message();
             match pdf_message {
                 Ok(pdf_message) => HttpResponse::Ok()
@@ -104,12 +122,17 @@ async fn get_age_group_results(
     data: web::Data<Box<dyn Storage + Send + Sync>>,
     query: web::Query<AgeGroupID>,
 ) -> impl Responder {
+    let competition = match data.get_active_competition().await {
+        Some(competition) => competition,
+        None => return HttpResponse::BadRequest().body("No active competition"),
+    };
     let group_id = query.into_inner();
     let age_group = data.get_age_group(&group_id).await;
 
     match age_group {
-        Some(age_group) => {
-            let certificate = match PDF::build_from_age_group_result(&age_group){
+        Some(mut age_group) => {
+            mark_personal_bests(&data, age_group.mut_athletes(), &competition).await;
+            let certificate = match PDF::build_from_age_group_result(&age_group, &competition){
                 Ok(pdf) => pdf,
                 Err(e) => return HttpResponse::InternalServerError().body(format!("Error generating PDF: {}", e))
             };
@@ -132,20 +155,25 @@ async fn get_all_age_group_results(
     query: web::Query<AgeGroupIDs>,
 ) -> impl Responder {
 
+    let competition = match data.get_active_competition().await {
+        Some(competition) => competition,
+        None => return HttpResponse::BadRequest().body("No active competition"),
+    };
     let mut pdfs: Vec<PDF> = Vec::new();
     let age_group_ids= query.into_inner().convert();
     
     match age_group_ids {
         Ok(age_group_ids) => {
             for age_group_id in age_group_ids {
-                let age_group = match data.get_age_group(&age_group_id).await {
+                let mut age_group = match data.get_age_group(&age_group_id).await {
                     Some(age_group) => age_group,
                     None =>  {
                         println!("Age group not found");
                         continue;
                     }
                 };
-                let certificate = match PDF::build_from_age_group_result(&age_group){
+                mark_personal_bests(&data, age_group.mut_athletes(), &competition).await;
+                let certificate = match PDF::build_from_age_group_result(&age_group, &competition){
                     Ok(pdf) => pdf,
                     Err(e) =>{
                         println!("Not able to generate pdf: {e}");
@@ -176,11 +204,15 @@ async fn get_all_age_group_results(
 async fn get_team_results(
     data: web::Data<Box<dyn Storage + Send + Sync>>,
 ) -> impl Responder {
+    let competition = match data.get_active_competition().await {
+        Some(competition) => competition,
+        None => return HttpResponse::BadRequest().body("No active competition"),
+    };
     let teams = data.get_teams().await;
 
     match teams {
         Ok(teams) => {
-            let results = PDF::new_team_result(&teams);
+            let results = PDF::new_team_result(&teams, &competition);
             let pdf_message = results.to_http_message();
             match pdf_message {
                 Ok(pdf_message) => HttpResponse::Ok()
@@ -192,4 +224,32 @@ async fn get_team_results(
         },
         Err(e) => HttpResponse::InternalServerError().body(format!("Error retrieving teams {}", e)),
     }
-}
\ No newline at end of file
+}
+
+/// Mark the results which are new personal bests in the given competition (shown on the PDFs)
+async fn mark_personal_bests(
+    data: &web::Data<Box<dyn Storage + Send + Sync>>,
+    athletes: &mut [Athlete],
+    competition: &Competition,
+) {
+    let athlete_ids: Vec<AthleteID> = athletes.iter().map(AthleteID::from_athlete).collect();
+    let mut editions = match data.get_athlete_editions(&athlete_ids).await {
+        Ok(editions) => editions,
+        Err(e) => {
+            warn!("Not able to load athlete history, personal bests are not marked: {e}");
+            return;
+        }
+    };
+
+    for athlete in athletes.iter_mut() {
+        let athlete_id = AthleteID::from_athlete(athlete);
+        let athlete_editions = editions
+            .remove(&athlete_id)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(_, other)| is_same_person(athlete, other))
+            .collect();
+        let history = AthleteHistory::build(&athlete_id, athlete_editions);
+        athlete.set_personal_bests(history.new_personal_bests(&competition.number));
+    }
+}
@@ -0,0 +1,109 @@
+use crate::Storage;
+use actix_web::{get, web, HttpResponse, Responder, post, put};
+use actix_web::web::Query;
+use crate::api_server::parse_json_body;
+use crate::competitions::{Competition, CompetitionID};
+
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_competitions);
+    cfg.service(get_competition);
+    cfg.service(post_competition);
+    cfg.service(update_competition);
+    cfg.service(get_active_competition);
+    cfg.service(set_active_competition);
+}
+
+#[get("/competitions")]
+async fn get_competitions(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+) -> impl Responder {
+    match data.get_competitions().await {
+        Ok(mut competitions) => {
+            competitions.sort_by_key(|competition| competition.start_date);
+            HttpResponse::Ok().body(serde_json::to_string(&competitions).expect("Competitions should be serializable"))
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error getting competitions: {}", e))
+    }
+}
+
+#[get("/competition")]
+async fn get_competition(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+    query: Query<CompetitionID>,
+) -> impl Responder {
+    match data.get_competition(&query.into_inner()).await {
+        Some(competition) => {
+            HttpResponse::Ok().body(serde_json::to_string(&competition).expect("Competition should be serializable"))
+        }
+        None => HttpResponse::NotFound().body("Competition not found")
+    }
+}
+
+#[post("/competition")]
+async fn post_competition(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+    body: web::Payload,
+) -> impl Responder {
+    let json_string = parse_json_body(body).await;
+
+    match Competition::from_json(&json_string) {
+        Ok(mut competition) => {
+            let competition_id = CompetitionID { number: competition.number.clone() };
+            if data.get_competition(&competition_id).await.is_some() {
+                return HttpResponse::BadRequest().body("Competition exists. Please update existing one!");
+            }
+            // Only set_active_competition changes which competition is active
+            competition.active = false;
+            competition.anonymized = false;
+            match data.store_competition(competition).await {
+                Ok(msg) => HttpResponse::Ok().body(msg),
+                Err(e) => HttpResponse::InternalServerError().body(format!("Error saving competition: {}", e))
+            }
+        }
+        Err(e) => HttpResponse::BadRequest().body(format!("Invalid competition data: {}", e))
+    }
+}
+
+#[put("/competition")]
+async fn update_competition(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+    body: web::Payload,
+    query: Query<CompetitionID>,
+) -> impl Responder {
+    let json_string = parse_json_body(body).await;
+
+    let mut competition = match data.get_competition(&query.into_inner()).await {
+        Some(competition) => competition,
+        None => return HttpResponse::NotFound().body("Competition not found")
+    };
+    if let Err(e) = competition.update_values(&json_string) {
+        return HttpResponse::BadRequest().body(format!("Invalid competition data: {}", e));
+    }
+    match data.store_competition(competition).await {
+        Ok(_) => HttpResponse::Ok().body("Competition updated"),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error saving competition: {}", e))
+    }
+}
+
+#[get("/active_competition")]
+async fn get_active_competition(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+) -> impl Responder {
+    match data.get_active_competition().await {
+        Some(competition) => {
+            HttpResponse::Ok().body(serde_json::to_string(&competition).expect("Competition should be serializable"))
+        }
+        None => HttpResponse::NotFound().body("No active competition")
+    }
+}
+
+#[put("/active_competition")]
+async fn set_active_competition(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+    query: Query<CompetitionID>,
+) -> impl Responder {
+    match data.set_active_competition(&query.into_inner()).await {
+        Ok(msg) => HttpResponse::Ok().body(msg),
+        Err(e) => HttpResponse::BadRequest().body(format!("Error changing active competition: {}", e))
+    }
+}
@@ -2,14 +2,21 @@ use crate::Storage;
 use actix_web::{get, web, HttpResponse, Responder, post, put};
 use actix_web::web::Query;
 use crate::api_server::parse_json_body;
-use crate::certificate_generation::{AgeGroupID, GroupID, GroupStore, SwitchGroupID};
+use serde::Deserialize;
+use crate::certificate_generation::{age_classes, AgeGroupID, AllocationRequest, CompetitionType, GroupAllocation, GroupID, GroupStats, GroupStore, SwitchGroupID};
+use crate::time_planner::TimeGroupID;
 
 pub fn configure_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(get_group);
     cfg.service(post_group);
     cfg.service(update_group);
     cfg.service(get_age_group);
+    cfg.service(get_age_classes);
+    cfg.service(get_group_stats);
     cfg.service(switch_group);
+    cfg.service(propose_group_allocation);
+    cfg.service(commit_group_allocation);
+    cfg.service(get_waitlist);
 }
 
 #[get("/group")]
@@ -108,4 +115,98 @@ async fn get_age_group(
         }
         None => HttpResponse::NotFound().body("Not found")
     }
-}
\ No newline at end of file
+}
+
+/// Result statistics per discipline for the given group or for all groups if no name is given
+#[get("/group_stats")]
+async fn get_group_stats(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+    query: Query<GroupID>,
+) -> impl Responder {
+    let group_names: Vec<String> = match query.into_inner().name {
+        Some(name) => vec![name],
+        None => {
+            let mut names: Vec<String> = data.get_athletes().await.into_keys().collect();
+            names.sort();
+            names
+        }
+    };
+
+    let mut stats = vec![];
+    for group_name in group_names {
+        match data.get_group(&GroupID::new(&group_name)).await {
+            Some(group) => {
+                let time_group = data.get_time_group(&TimeGroupID::new(group_name)).await;
+                stats.push(GroupStats::build(&group, time_group.as_ref()))
+            }
+            None => return HttpResponse::NotFound().body(format!("Group {} not found", group_name))
+        }
+    }
+    HttpResponse::Ok().body(serde_json::to_string(&stats).expect("Group statistics should be serializable"))
+}
+
+/// Configured age class definitions
+#[get("/age_classes")]
+async fn get_age_classes() -> impl Responder {
+    HttpResponse::Ok().body(serde_json::to_string(age_classes()).expect("Age classes should be serializable"))
+}
+
+/// Proposal for the allocation of all registered decathlon athletes to groups. Nothing is stored.
+#[post("/group_allocation")]
+async fn propose_group_allocation(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+    body: web::Payload,
+) -> impl Responder {
+    let json_string = parse_json_body(body).await;
+
+    let request: AllocationRequest = match serde_json::from_str(&json_string) {
+        Ok(request) => request,
+        Err(e) => return HttpResponse::BadRequest().body(format!("Invalid allocation request: {}", e))
+    };
+    match data.get_all_athletes().await {
+        Ok(athletes) => {
+            let allocation = GroupAllocation::propose(&athletes, &request);
+            HttpResponse::Ok().body(serde_json::to_string(&allocation).expect("Allocation should be serializable"))
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error loading athletes: {}", e))
+    }
+}
+
+/// Store a reviewed group allocation
+#[put("/group_allocation")]
+async fn commit_group_allocation(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+    body: web::Payload,
+) -> impl Responder {
+    let json_string = parse_json_body(body).await;
+
+    let allocation: GroupAllocation = match serde_json::from_str(&json_string) {
+        Ok(allocation) => allocation,
+        Err(e) => return HttpResponse::BadRequest().body(format!("Invalid group allocation: {}", e))
+    };
+    let allocation = match allocation.validate(data.as_ref().as_ref()).await {
+        Ok(allocation) => allocation,
+        Err(e) => return HttpResponse::BadRequest().body(format!("Invalid group allocation: {}", e))
+    };
+    match allocation.commit(data.as_ref().as_ref()).await {
+        Ok(msg) => HttpResponse::Ok().body(msg),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error storing group allocation: {}", e))
+    }
+}
+
+#[derive(Deserialize)]
+struct WaitlistQuery {
+    competition_type: CompetitionType,
+}
+
+/// Waitlist and promotions of a competition type (for the registration desk)
+#[get("/waitlist")]
+async fn get_waitlist(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+    query: Query<WaitlistQuery>,
+) -> impl Responder {
+    match data.get_waitlist(&query.competition_type).await {
+        Ok(waitlist) => HttpResponse::Ok().body(serde_json::to_string(&waitlist).expect("Waitlist should be serializable")),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error loading waitlist: {}", e))
+    }
+}
@@ -1,12 +1,16 @@
 use crate::Storage;
-use actix_web::{get, web, HttpResponse, Responder, post};
+use actix_web::{get, web, HttpResponse, Responder, post, put};
 use actix_web::web::Query;
 use crate::api_server::parse_json_body;
-use crate::notes::{NoteID};
+use crate::notes::{add_note, discipline_notes, edit_note, NoteCategory, NoteEntryID, NoteID, NoteInput};
+use crate::result_history::RoleQuery;
 
 pub fn configure_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(get_note);
     cfg.service(post_note);
+    cfg.service(get_note_entries);
+    cfg.service(post_note_entry);
+    cfg.service(put_note_entry);
 }
 
 #[get("/notes")]
@@ -29,20 +33,71 @@ async fn get_note(
     }    
 }
 
+/// Add a general note with the plain text of the body, kept for clients of the former free-text notes
 #[post("/notes")]
 async fn post_note(
     data: web::Data<Box<dyn Storage + Send + Sync>>,
     body: web::Payload,
     query: Query<NoteID>,
+    role: Query<RoleQuery>,
 ) -> impl Responder {
-    let json_string = parse_json_body(body).await;
-    let note_id: NoteID = query.into_inner();
+    let text = parse_json_body(body).await;
+    if text.trim().is_empty() {
+        return HttpResponse::BadRequest().body("Invalid note: Text of the note not given");
+    }
+    let input = NoteInput { text, category: NoteCategory::General, athlete_id: None };
+    match add_note(data.as_ref().as_ref(), &query.into_inner(), input, role.into_inner().role).await {
+        Ok(entry) => HttpResponse::Ok().body(serde_json::to_string(&entry).expect("Note should be serializable")),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error saving note: {}", e))
+    }
+}
 
-    match data.save_note(note_id, json_string).await {
-        Ok(msg) => {
-            HttpResponse::Ok().body(msg)
-        }
+/// All notes of the group for the discipline, oldest first
+#[get("/note_entries")]
+async fn get_note_entries(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+    query: Query<NoteID>,
+) -> impl Responder {
+    match discipline_notes(data.as_ref().as_ref(), &query.into_inner()).await {
+        Ok(notes) => HttpResponse::Ok().body(serde_json::to_string(&notes).expect("Notes should be serializable")),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error retrieving notes: {}", e))
+    }
+}
+
+#[post("/note_entry")]
+async fn post_note_entry(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+    body: web::Payload,
+    query: Query<NoteID>,
+    role: Query<RoleQuery>,
+) -> impl Responder {
+    let json_string = parse_json_body(body).await;
+    let input = match NoteInput::from_json(&json_string) {
+        Ok(input) => input,
+        Err(e) => return HttpResponse::BadRequest().body(format!("Invalid note: {}", e))
+    };
+    match add_note(data.as_ref().as_ref(), &query.into_inner(), input, role.into_inner().role).await {
+        Ok(entry) => HttpResponse::Ok().body(serde_json::to_string(&entry).expect("Note should be serializable")),
         Err(e) => HttpResponse::InternalServerError().body(format!("Error saving note: {}", e))
     }
 }
 
+/// Edit a note, the previous version is kept in the history of the note
+#[put("/note_entry")]
+async fn put_note_entry(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+    body: web::Payload,
+    query: Query<NoteID>,
+    entry_id: Query<NoteEntryID>,
+    role: Query<RoleQuery>,
+) -> impl Responder {
+    let json_string = parse_json_body(body).await;
+    let input = match NoteInput::from_json(&json_string) {
+        Ok(input) => input,
+        Err(e) => return HttpResponse::BadRequest().body(format!("Invalid note: {}", e))
+    };
+    match edit_note(data.as_ref().as_ref(), &query.into_inner(), &entry_id.into_inner(), input, role.into_inner().role).await {
+        Ok(entry) => HttpResponse::Ok().body(serde_json::to_string(&entry).expect("Note should be serializable")),
+        Err(e) => HttpResponse::BadRequest().body(format!("Error editing note: {}", e))
+    }
+}
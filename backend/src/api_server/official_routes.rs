@@ -0,0 +1,100 @@
+use crate::Storage;
+use actix_web::{delete, get, post, web, HttpResponse, Responder};
+use actix_web::web::Query;
+use crate::api_server::parse_json_body;
+use crate::certificate_generation::PDF;
+use crate::officials::{build_roster, Official, OfficialID};
+
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_officials);
+    cfg.service(post_official);
+    cfg.service(delete_official);
+    cfg.service(get_official_assignments);
+    cfg.service(get_roster);
+    cfg.service(get_roster_pdf);
+}
+
+#[get("/officials")]
+async fn get_officials(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+) -> impl Responder {
+    match data.get_officials().await {
+        Ok(officials) => HttpResponse::Ok().body(serde_json::to_string(&officials).expect("Officials should be serializable")),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error getting officials: {}", e))
+    }
+}
+
+/// Store a new official or replace the official (and the assignments) with the same name
+#[post("/official")]
+async fn post_official(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+    body: web::Payload,
+) -> impl Responder {
+    let json_string = parse_json_body(body).await;
+    match Official::from_json(&json_string) {
+        Ok(official) => match data.store_official(&official).await {
+            Ok(msg) => HttpResponse::Ok().body(msg),
+            Err(e) => HttpResponse::InternalServerError().body(format!("Error saving official: {}", e))
+        },
+        Err(e) => HttpResponse::BadRequest().body(format!("Invalid official data: {}", e))
+    }
+}
+
+#[delete("/official")]
+async fn delete_official(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+    query: Query<OfficialID>,
+) -> impl Responder {
+    match data.delete_official(&query.into_inner()).await {
+        Ok(_) => HttpResponse::Ok().body("Official deleted"),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error deleting official: {}", e))
+    }
+}
+
+/// Disciplines the official is assigned to, ordered by start time
+#[get("/official/assignments")]
+async fn get_official_assignments(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+    query: Query<OfficialID>,
+) -> impl Responder {
+    let official_id = query.into_inner();
+    if data.get_official(&official_id).await.is_none() {
+        return HttpResponse::NotFound().body(format!("Official {} not found", official_id.name));
+    }
+    match build_roster(data.as_ref().as_ref()).await {
+        Ok(roster) => HttpResponse::Ok()
+            .body(serde_json::to_string(&roster.assignments_of(&official_id)).expect("Assignments should be serializable")),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error building roster: {}", e))
+    }
+}
+
+/// Officials per scheduled discipline and warnings for stations without a judge
+#[get("/official_roster")]
+async fn get_roster(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+) -> impl Responder {
+    match build_roster(data.as_ref().as_ref()).await {
+        Ok(roster) => HttpResponse::Ok().body(serde_json::to_string(&roster).expect("Roster should be serializable")),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error building roster: {}", e))
+    }
+}
+
+#[get("/official_roster/pdf")]
+async fn get_roster_pdf(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+) -> impl Responder {
+    let roster = match build_roster(data.as_ref().as_ref()).await {
+        Ok(roster) => roster,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error building roster: {}", e))
+    };
+    let competition = match data.get_active_competition().await {
+        Some(competition) => competition,
+        None => return HttpResponse::BadRequest().body("No active competition"),
+    };
+    match PDF::new_official_roster(&roster, &competition).to_http_message() {
+        Ok(pdf_message) => HttpResponse::Ok()
+            .content_type("application/pdf")
+            .body(pdf_message),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error generating PDF: {}", e))
+    }
+}
@@ -0,0 +1,49 @@
+use crate::Storage;
+use actix_web::{get, web, HttpResponse, Responder, post};
+use actix_web::web::Query;
+use chrono::Duration;
+use serde::Deserialize;
+use crate::certificate_generation::AthleteID;
+use crate::privacy::{anonymize_expired_competitions, export_athlete, retention_period};
+
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_athlete_export);
+    cfg.service(anonymize);
+}
+
+#[derive(Deserialize)]
+struct AnonymizeQuery {
+    retention_days: Option<i64>,
+}
+
+#[get("/athlete_export")]
+async fn get_athlete_export(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+    query: Query<AthleteID>,
+) -> impl Responder {
+    let athlete_id = query.into_inner();
+
+    match export_athlete(data.as_ref().as_ref(), &athlete_id).await {
+        Ok(export) if export.records.is_empty() => HttpResponse::NotFound().body("Athlete not found in any competition"),
+        Ok(export) => HttpResponse::Ok().body(serde_json::to_string(&export).expect("Export should be serializable")),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error exporting athlete data: {}", e))
+    }
+}
+
+/// Anonymize all competitions which ended more than the retention period (RETENTION_DAYS or query parameter) ago
+#[post("/anonymize")]
+async fn anonymize(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+    query: Query<AnonymizeQuery>,
+) -> impl Responder {
+    let retention_period = match query.retention_days {
+        Some(days) if days < 0 => return HttpResponse::BadRequest().body("Retention period must not be negative"),
+        Some(days) => Duration::days(days),
+        None => retention_period(),
+    };
+
+    match anonymize_expired_competitions(data.as_ref().as_ref(), retention_period).await {
+        Ok(competitions) => HttpResponse::Ok().body(serde_json::to_string(&competitions).expect("Competition numbers should be serializable")),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error anonymizing athlete data: {}", e))
+    }
+}
@@ -2,9 +2,12 @@ use crate::Storage;
 use actix_web::{get, web, HttpResponse, Responder, post, put};
 use actix_web::web::Query;
 use serde_json::Value;
+use chrono::Utc;
 use crate::api_server::parse_json_body;
-use crate::certificate_generation::{GroupID, PDF};
-use crate::time_planner::{DisciplineID, DisciplineUpdateId, StartingOrder, TimeGroupID};
+use crate::certificate_generation::{AthleteID, GroupID, PDF};
+use crate::notes::{discipline_notes, NoteID};
+use crate::result_history::RoleQuery;
+use crate::time_planner::{build_time_groups, DEFAULT_TIME_ZONE, missing_results, DisciplineState, StateChange, next_attempt, record_attempt, Attempt, estimate_start_times, estimate_time_group, ical_feed, LocationQuery, SeedingInfo, SeedingStrategy, StartingOrderQuery, generate_time_table_for_storage, validate_time_table, SchemaError, TimeTable, UploadSummary, station_occupancy, ScheduleRequest, DisciplineID, DisciplineUpdateId, StartingOrder, TimeGroupID};
 
 pub fn configure_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(get_discipline);
@@ -16,8 +19,29 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(get_track_starting_order);
     cfg.service(change_starting_order);
     cfg.service(upload_time_table);
+    cfg.service(upload_time_table_csv);
+    cfg.service(get_time_table);
+    cfg.service(get_time_table_csv);
+    cfg.service(get_time_table_validation);
+    cfg.service(get_station_occupancy);
+    cfg.service(generate_time_table);
     cfg.service(change_discipline_state);
     cfg.service(reset_athlete_order);
+    cfg.service(change_seeding);
+    cfg.service(get_time_group_calendar);
+    cfg.service(get_location_calendar);
+    cfg.service(get_athlete_calendar);
+    cfg.service(post_attempt);
+    cfg.service(get_next_attempt);
+}
+
+fn calendar_response(calendar: Result<String, Box<dyn std::error::Error>>) -> HttpResponse {
+    match calendar {
+        Ok(calendar) => HttpResponse::Ok()
+            .content_type("text/calendar; charset=utf-8")
+            .body(calendar),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error generating calendar: {}", e))
+    }
 }
 
 #[get("/discipline")]
@@ -29,7 +53,8 @@ async fn get_discipline(
     let time_group_id = TimeGroupID::new(discipline_id.group_name());
     let time_group = data.get_time_group(&time_group_id).await;
     match time_group {
-        Some(time_group) => {
+        Some(mut time_group) => {
+            time_group.localize();
             match discipline_id.discipline_name() {
                 Some(discipline_name) => {
                     match time_group.get_discipline(&discipline_name) {
@@ -57,18 +82,21 @@ async fn get_current_discipline(
     query: Query<TimeGroupID>,
 ) -> impl Responder {
     let group_id = query.into_inner();
-    let group = data.get_time_group(&group_id).await;
-    match group {
-        Some(mut group) => {
-            let discipline = group.get_current_discipline().clone();
-            match data.store_time_group(group).await {
-                Ok(_) => HttpResponse::Ok()
-                    .body(serde_json::to_string(&discipline)
-                        .expect("Discipline should be serializable")),
-                Err(e) => HttpResponse::BadRequest().body(format!("Error storing updated group: {e}"))
-            }
+    let group = match data.get_time_group(&group_id).await {
+        Some(group) => group,
+        None => return HttpResponse::NotFound().body("Group Not Found"),
+    };
+    // The current discipline is polled by the groups, only the delay of the group itself is estimated here and
+    // the other time groups are not loaded (see /disciplines for the estimates of all groups)
+    let mut groups = [group];
+    estimate_start_times(&mut groups, Utc::now());
+    match groups[0].get_current_discipline() {
+        Some(discipline) => {
+            HttpResponse::Ok()
+                .body(serde_json::to_string(&discipline)
+                    .expect("Discipline should be serializable"))
         }
-        None => HttpResponse::NotFound().body("Group Not Found")
+        None => HttpResponse::NotFound().body("Group has no disciplines")
     }
 }
 
@@ -80,16 +108,21 @@ async fn get_next_discipline(
     let group_id = query.into_inner();
     let group = data.get_time_group(&group_id).await;
     match group {
-        Some(group) => {
-            let discipline = group.get_next_discipline();
-            HttpResponse::Ok()
-                .body(serde_json::to_string(&discipline)
-                    .expect("Discipline should be serializable"))
+        Some(mut group) => {
+            group.localize();
+            match group.get_next_discipline() {
+                Some(discipline) => HttpResponse::Ok()
+                    .body(serde_json::to_string(&discipline)
+                        .expect("Discipline should be serializable")),
+                None => HttpResponse::NotFound().body("No next discipline")
+            }
         }
         None => HttpResponse::NotFound().body("Group Not Found")
     }
 }
 
+/// Change the state of a discipline ({"name": ..., "state": ..., "force": false}). Finishing needs results of all
+/// athletes and going back needs the override.
 #[put("/discipline_state")]
 async fn change_discipline_state(
     data: web::Data<Box<dyn Storage + Send + Sync>>,
@@ -98,18 +131,28 @@ async fn change_discipline_state(
 ) -> impl Responder {
     let group_id = query.into_inner();
     let group = data.get_time_group(&group_id).await;
-    let json_string = parse_json_body(body).await;
+    let change: StateChange = match serde_json::from_str(parse_json_body(body).await.as_str()) {
+        Ok(change) => change,
+        Err(e) => return HttpResponse::BadRequest().body(format!("Could not parse new state information: {e}")),
+    };
 
     match group {
         Some(mut group) => {
-            match group.change_discipline_state(json_string.as_str()) {
+            let missing = match (&change.state, change.force) {
+                (DisciplineState::Finished, false) => match missing_results(data.as_ref().as_ref(), &group, &change.name).await {
+                    Ok(missing) => missing,
+                    Err(e) => return HttpResponse::NotFound().body(format!("Error updating state: {e}")),
+                },
+                _ => vec![],
+            };
+            match group.change_discipline_state(&change, &missing) {
                 Ok(msg) => {
                     match data.store_time_group(group).await {
                         Ok(_) => HttpResponse::Ok().body(msg),
                         Err(e) => HttpResponse::BadRequest().body(format!("Error storing updated group: {e}"))
                     }
                 }
-                Err(e) => HttpResponse::BadRequest().body(format!("Error updating state: {e}"))
+                Err(e) => HttpResponse::Conflict().body(format!("Error updating state: {e}"))
             }
         }
         None => HttpResponse::NotFound().body("Group Not Found")
@@ -125,6 +168,10 @@ async fn get_disciplines(
     let group = data.get_time_group(&group_id).await;
     match group {
         Some(group) => {
+            let mut group = estimate_time_group(data.as_ref().as_ref(), group.name())
+                .await
+                .unwrap_or(group);
+            group.localize();
             let disciplines = group.get_disciplines().clone();
             HttpResponse::Ok()
                 .body(serde_json::to_string(&disciplines)
@@ -143,7 +190,7 @@ async fn get_discipline_protocol(
     let time_group_id = TimeGroupID::new(discipline_id.group_name());
     let time_group = data.get_time_group(&time_group_id).await;
     match time_group {
-        Some(mut time_group) => {
+        Some(time_group) => {
             let discipline = match discipline_id.discipline_name() {
                 Some(discipline_name) => {
                     match time_group.get_discipline(&discipline_name) {
@@ -151,15 +198,25 @@ async fn get_discipline_protocol(
                         None => return HttpResponse::NotFound().body(format!("Unable to find discipline {} in group", discipline_name))
                     }
                 }
-                None => {
-                    time_group.get_current_discipline()
+                None => match time_group.get_current_discipline() {
+                    Some(discipline) => discipline,
+                    None => return HttpResponse::NotFound().body("Group has no disciplines")
                 }
             };
             let group = match data.get_group(&GroupID::new(&discipline_id.group_name())).await {
                 Some(group) => group,
                 None => return HttpResponse::NotFound().body(format!("Unable to find group {}", discipline_id.group_name()))
             };
-            let certificate = PDF::new_discipline_protocol(&group, &discipline);
+            let competition = match data.get_active_competition().await {
+                Some(competition) => competition,
+                None => return HttpResponse::BadRequest().body("No active competition"),
+            };
+            let note_id = NoteID { discipline: discipline.name().to_string(), group_name: group.name().to_string() };
+            let notes = match discipline_notes(data.as_ref().as_ref(), &note_id).await {
+                Ok(notes) => notes,
+                Err(e) => return HttpResponse::InternalServerError().body(format!("Error loading notes: {}", e))
+            };
+            let certificate = PDF::new_discipline_protocol(&group, &discipline, &competition, &notes);
             let pdf_message = certificate.to_http_message();
             match pdf_message {
                 Ok(pdf_message) => HttpResponse::Ok()
@@ -173,18 +230,25 @@ async fn get_discipline_protocol(
 }
 
 
+/// Default starting order of the group, or the (rotated) starting order of the given discipline
 #[get("/starting_order")]
 async fn get_starting_order(
     data: web::Data<Box<dyn Storage + Send + Sync>>,
-    query: Query<TimeGroupID>,
+    query: Query<StartingOrderQuery>,
 ) -> impl Responder {
-    let group_id = query.into_inner();
-    let group = data.get_time_group(&group_id).await;
+    let query = query.into_inner();
+    let group = data.get_time_group(&TimeGroupID { name: query.name }).await;
     match group {
         Some(group) => {
-            let discipline = group.get_default_starting_order();
+            let starting_order = match query.discipline_name {
+                Some(discipline_name) => match group.get_discipline(&discipline_name) {
+                    Some(discipline) => discipline.starting_order().clone(),
+                    None => return HttpResponse::NotFound().body(format!("Unable to find discipline {} in group", discipline_name)),
+                },
+                None => group.get_default_starting_order(),
+            };
             HttpResponse::Ok()
-                .body(serde_json::to_string(&discipline)
+                .body(serde_json::to_string(&starting_order)
                     .expect("Discipline should be serializable"))
         }
         None => HttpResponse::NotFound().body("Group Not Found")
@@ -241,6 +305,31 @@ async fn change_starting_order(
     }
 }
 
+/// Check the time table against the schema, validate and store it. Schema violations are rejected with their paths,
+/// time tables with conflicts with the validation report. Otherwise the stored time groups are merged with the upload
+/// and the summary contains the remaining warnings and the changes.
+async fn store_time_table(data: &web::Data<Box<dyn Storage + Send + Sync>>, time_table: Result<TimeTable, Vec<SchemaError>>) -> HttpResponse {
+    let time_table = match time_table {
+        Ok(time_table) => time_table.to_value(),
+        Err(errors) => return HttpResponse::BadRequest().body(serde_json::to_string(&errors).expect("Schema errors should be serializable"))
+    };
+    let time_zone = data.get_active_competition().await
+        .map_or(DEFAULT_TIME_ZONE, |competition| competition.config.time_zone);
+    let time_groups = match build_time_groups(&time_table, time_zone) {
+        Ok(time_groups) => time_groups,
+        Err(e) => return HttpResponse::BadRequest().body(format!("Invalid time table: {}", e))
+    };
+    let report = validate_time_table(data.as_ref().as_ref(), &time_groups).await;
+    if !report.is_valid() {
+        return HttpResponse::BadRequest().body(serde_json::to_string(&report).expect("Report should be serializable"));
+    }
+    match data.store_time_plan(time_table).await {
+        Ok(changes) => HttpResponse::Ok()
+            .body(serde_json::to_string(&UploadSummary { report, changes }).expect("Summary should be serializable")),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error storing time table: {}", e))
+    }
+}
+
 #[post("/time_table")]
 async fn upload_time_table(
     data: web::Data<Box<dyn Storage + Send + Sync>>,
@@ -249,18 +338,98 @@ async fn upload_time_table(
     let json_string = parse_json_body(body).await;
     let time_table: serde_json::error::Result<Value> = serde_json::from_str(json_string.as_str());
     match time_table {
-        Ok(time_table) => {
-            match data.store_time_plan(time_table).await {
-                Ok(msg) => HttpResponse::Ok().body(msg),
-                Err(e) => HttpResponse::InternalServerError().body(format!("Error storing time table: {}", e))
-            }
-        }
+        Ok(time_table) => store_time_table(&data, TimeTable::from_value(&time_table)).await,
         Err(e) => {
             HttpResponse::BadRequest().body(format!("Could not parse time table: {}", e))
         }
     }
 }
 
+/// Upload of the CSV export, see `TimeTable::to_csv`
+#[post("/time_table/csv")]
+async fn upload_time_table_csv(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+    body: web::Payload,
+) -> impl Responder {
+    let csv = parse_json_body(body).await;
+    store_time_table(&data, TimeTable::from_csv(&csv)).await
+}
+
+/// Current time table in the upload format, including all changes made after the upload
+#[get("/time_table")]
+async fn get_time_table(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+) -> impl Responder {
+    match data.get_time_groups().await {
+        Ok(time_groups) => HttpResponse::Ok()
+            .body(serde_json::to_string(&TimeTable::from_time_groups(&time_groups)).expect("Time table should be serializable")),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error loading time groups: {}", e))
+    }
+}
+
+#[get("/time_table/csv")]
+async fn get_time_table_csv(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+) -> impl Responder {
+    let csv = match data.get_time_groups().await {
+        Ok(time_groups) => TimeTable::from_time_groups(&time_groups).to_csv(),
+        Err(e) => Err(e),
+    };
+    match csv {
+        Ok(csv) => HttpResponse::Ok()
+            .content_type("text/csv; charset=utf-8")
+            .body(csv),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error exporting time table: {}", e))
+    }
+}
+
+/// Generate a conflict free time table which can be uploaded to /time_table. Nothing is stored.
+#[post("/time_table_generation")]
+async fn generate_time_table(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+    body: web::Payload,
+) -> impl Responder {
+    let json_string = parse_json_body(body).await;
+    let request: ScheduleRequest = match serde_json::from_str(json_string.as_str()) {
+        Ok(request) => request,
+        Err(e) => return HttpResponse::BadRequest().body(format!("Could not parse schedule request: {}", e))
+    };
+
+    match generate_time_table_for_storage(data.as_ref().as_ref(), request).await {
+        Ok(time_table) => HttpResponse::Ok().body(serde_json::to_string(&time_table).expect("Time table should be serializable")),
+        Err(e) => HttpResponse::BadRequest().body(format!("Could not generate time table: {}", e))
+    }
+}
+
+/// Validate the stored time table
+#[get("/time_table_validation")]
+async fn get_time_table_validation(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+) -> impl Responder {
+    match data.get_time_groups().await {
+        Ok(time_groups) => {
+            let report = validate_time_table(data.as_ref().as_ref(), &time_groups).await;
+            HttpResponse::Ok().body(serde_json::to_string(&report).expect("Report should be serializable"))
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error loading time table: {}", e))
+    }
+}
+
+/// Groups which are currently at each station of the venue, locations outside the venue are listed at the end
+#[get("/station_occupancy")]
+async fn get_station_occupancy(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+) -> impl Responder {
+    let stations = data.get_active_competition().await
+        .map(|competition| competition.config.stations)
+        .unwrap_or_default();
+    match data.get_time_groups().await {
+        Ok(time_groups) => HttpResponse::Ok()
+            .body(serde_json::to_string(&station_occupancy(&time_groups, &stations)).expect("Occupancy should be serializable")),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error loading time groups: {}", e))
+    }
+}
+
 
 #[put("/reset_athlete_order")]
 async fn reset_athlete_order(
@@ -277,7 +446,12 @@ async fn reset_athlete_order(
 
     match group {
         Some(mut group) => {
-            match group.reshuffle_athlete_order(group.name().to_string(), true, athlete_states) {
+            // Pick up a changed number of lanes of the venue
+            if let Some(competition) = data.get_active_competition().await {
+                group.set_lanes(competition.config.lanes);
+            }
+            let info = SeedingInfo::load(data.as_ref().as_ref(), group.name()).await;
+            match group.reshuffle_athlete_order(group.name().to_string(), true, athlete_states, &info) {
                 Ok(msg) => {
                     match data.store_time_group(group).await {
                         Ok(_) => HttpResponse::Ok().body(msg),
@@ -289,4 +463,127 @@ async fn reset_athlete_order(
         }
         None => HttpResponse::NotFound().body("Group Not Found")
     }
-}
\ No newline at end of file
+}
+#[get("/ical/time_group")]
+async fn get_time_group_calendar(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+    query: Query<TimeGroupID>,
+) -> impl Responder {
+    let group_name = match query.into_inner().name {
+        Some(name) => name,
+        None => return HttpResponse::BadRequest().body("No group name given"),
+    };
+    let calendar = ical_feed(data.as_ref().as_ref(), &group_name,
+                             |time_group, _| time_group.name() == group_name).await;
+    calendar_response(calendar)
+}
+
+#[get("/ical/location")]
+async fn get_location_calendar(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+    query: Query<LocationQuery>,
+) -> impl Responder {
+    let location = query.into_inner().location;
+    let calendar = ical_feed(data.as_ref().as_ref(), &location,
+                             |_, discipline| discipline.location() == location).await;
+    calendar_response(calendar)
+}
+
+#[get("/ical/athlete")]
+async fn get_athlete_calendar(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+    query: Query<AthleteID>,
+) -> impl Responder {
+    let athlete_id = query.into_inner();
+    let group_name = data.get_athletes().await
+        .into_iter()
+        .find(|(_, athletes)| athletes.iter().any(|athlete| AthleteID::from_athlete(athlete) == athlete_id))
+        .map(|(group_name, _)| group_name);
+    let group_name = match group_name {
+        Some(group_name) => group_name,
+        None => return HttpResponse::NotFound().body("Athlete not found in any group"),
+    };
+    let calendar_name = format!("{} {}", athlete_id.name(), athlete_id.surname());
+    let calendar = ical_feed(data.as_ref().as_ref(), &calendar_name,
+                             |time_group, _| time_group.name() == group_name).await;
+    calendar_response(calendar)
+}
+
+/// Change the seeding strategy of a track discipline (e.g. {"Random": {}}) and regenerate its runs
+#[put("/seeding")]
+async fn change_seeding(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+    query: Query<DisciplineID>,
+    body: web::Payload,
+) -> impl Responder {
+    let discipline_id = query.into_inner();
+    let discipline_name = match discipline_id.discipline_name() {
+        Some(discipline_name) => discipline_name,
+        None => return HttpResponse::BadRequest().body("No discipline name given"),
+    };
+    let seeding: SeedingStrategy = match serde_json::from_str(parse_json_body(body).await.as_str()) {
+        Ok(seeding) => seeding,
+        Err(e) => return HttpResponse::BadRequest().body(format!("Could not parse seeding strategy: {}", e)),
+    };
+    let mut group = match data.get_time_group(&TimeGroupID::new(discipline_id.group_name())).await {
+        Some(group) => group,
+        None => return HttpResponse::NotFound().body("Group Not Found"),
+    };
+
+    let info = SeedingInfo::load(data.as_ref().as_ref(), group.name()).await;
+    let time_zone = group.time_zone();
+    let discipline = match group.change_seeding(&discipline_name, seeding, &info) {
+        Ok(discipline) => {
+            let mut discipline = discipline.clone();
+            discipline.localize(time_zone);
+            discipline
+        }
+        Err(e) => return HttpResponse::BadRequest().body(format!("Error changing seeding: {}", e)),
+    };
+    match data.store_time_group(group).await {
+        Ok(_) => HttpResponse::Ok().body(serde_json::to_string(&discipline).expect("Discipline should be serializable")),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error storing updated group: {e}"))
+    }
+}
+
+/// Record a single attempt of a field discipline, returns the updated achievement and who is up next
+#[post("/attempt")]
+async fn post_attempt(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+    query: Query<DisciplineID>,
+    role: Query<RoleQuery>,
+    body: web::Payload,
+) -> impl Responder {
+    let discipline_id = query.into_inner();
+    let discipline_name = match discipline_id.discipline_name() {
+        Some(discipline_name) => discipline_name,
+        None => return HttpResponse::BadRequest().body("No discipline name given"),
+    };
+    let attempt: Attempt = match serde_json::from_str(parse_json_body(body).await.as_str()) {
+        Ok(attempt) => attempt,
+        Err(e) => return HttpResponse::BadRequest().body(format!("Could not parse attempt: {}", e)),
+    };
+
+    match record_attempt(data.as_ref().as_ref(), &discipline_id.group_name(), &discipline_name, attempt, role.into_inner().role).await {
+        Ok(update) => HttpResponse::Ok().body(serde_json::to_string(&update).expect("Attempt update should be serializable")),
+        Err(e) => HttpResponse::BadRequest().body(format!("Error recording attempt: {}", e))
+    }
+}
+
+/// Athlete who is up next in a field discipline, null if all attempts are done
+#[get("/next_attempt")]
+async fn get_next_attempt(
+    data: web::Data<Box<dyn Storage + Send + Sync>>,
+    query: Query<DisciplineID>,
+) -> impl Responder {
+    let discipline_id = query.into_inner();
+    let discipline_name = match discipline_id.discipline_name() {
+        Some(discipline_name) => discipline_name,
+        None => return HttpResponse::BadRequest().body("No discipline name given"),
+    };
+
+    match next_attempt(data.as_ref().as_ref(), &discipline_id.group_name(), &discipline_name).await {
+        Ok(next) => HttpResponse::Ok().body(serde_json::to_string(&next).expect("Next attempt should be serializable")),
+        Err(e) => HttpResponse::NotFound().body(format!("Error finding next attempt: {}", e))
+    }
+}
@@ -0,0 +1,83 @@
+use async_trait::async_trait;
+use chrono::{DateTime, SecondsFormat, Utc};
+use chrono::serde::ts_seconds;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+
+use crate::certificate_generation::{Achievement, AchievementID, AthleteID};
+
+/// Changes which are recorded in the audit log
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum AuditAction {
+    GroupSwitch {
+        athlete_ids: Vec<AthleteID>,
+        from: String,
+        to: String,
+    },
+    /// Result of an athlete was entered, changed, restored or deleted (None if there is no result)
+    ResultChange {
+        athlete_id: AthleteID,
+        discipline: String,
+        /// Role of the user who entered the change, if known
+        role: Option<String>,
+        old: Option<Box<Achievement>>,
+        new: Option<Box<Achievement>>,
+    },
+}
+
+impl AuditAction {
+    /// Athletes the change concerns
+    pub fn athlete_ids(&self) -> Vec<&AthleteID> {
+        match self {
+            AuditAction::GroupSwitch { athlete_ids, .. } => athlete_ids.iter().collect(),
+            AuditAction::ResultChange { athlete_id, .. } => vec![athlete_id],
+        }
+    }
+
+    /// Replace the athlete ids, e.g. by pseudonyms
+    pub fn rename_athletes(&mut self, rename: impl Fn(&AthleteID) -> AthleteID) {
+        match self {
+            AuditAction::GroupSwitch { athlete_ids, .. } => athlete_ids.iter_mut().for_each(|athlete_id| *athlete_id = rename(athlete_id)),
+            AuditAction::ResultChange { athlete_id, .. } => *athlete_id = rename(athlete_id),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AuditRecord {
+    /// Sortable unique id (RFC 3339 time stamp with microseconds)
+    pub id: String,
+    #[serde(with = "ts_seconds")]
+    pub timestamp: DateTime<Utc>,
+    pub action: AuditAction,
+}
+
+impl AuditRecord {
+    pub fn new(action: AuditAction) -> Self {
+        let timestamp = Utc::now();
+        AuditRecord {
+            id: timestamp.to_rfc3339_opts(SecondsFormat::Micros, true),
+            timestamp,
+            action,
+        }
+    }
+
+    /// Record as exported for the athlete, other athletes switched together are left out
+    pub fn for_athlete(mut self, athlete_id: &AthleteID) -> Self {
+        if let AuditAction::GroupSwitch { athlete_ids, .. } = &mut self.action {
+            athlete_ids.retain(|switched| switched == athlete_id);
+        }
+        self
+    }
+}
+
+#[async_trait]
+pub trait AuditStorage {
+    async fn get_audit_records(&self) -> Result<Vec<AuditRecord>, Box<dyn Error>>;
+    /// Records of the active competition which concern the athlete
+    async fn get_athlete_audit_records(&self, athlete_id: &AthleteID) -> Result<Vec<AuditRecord>, Box<dyn Error>>;
+    async fn store_audit_record(&self, record: &AuditRecord) -> Result<String, Box<dyn Error>>;
+    /// Write the result of the athlete (None deletes it) and the audit record of the change in one transaction.
+    /// A new result (no previous result in the record) fails if the athlete already has a result for the discipline.
+    async fn store_result_change(&self, achievement_id: &AchievementID, new: Option<&Achievement>, record: &AuditRecord) -> Result<String, Box<dyn Error>>;
+}
@@ -1,6 +1,11 @@
 pub(crate) mod achievements;
+mod age_classes;
 mod age_group_utils;
+mod athlete_history;
+mod athlete_search;
 mod athletes;
+mod group_allocation;
+mod group_stats;
 mod groups;
 mod pdf;
 
@@ -12,9 +17,14 @@ use std::error::Error;
 use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 use serde_json::{Map, Value};
+pub use age_classes::age_classes;
 pub use age_group_utils::AgeGroupSelector;
-pub use athletes::{Athlete, AthleteID};
-pub use groups::{AgeGroup, AgeGroupID, Group, GroupID, GroupStore, SwitchGroupID, AgeGroupIDs};
+pub use athletes::{Athlete, AthleteID, AthleteUpdate};
+pub use athlete_search::AthleteQuery;
+pub use athlete_history::{AthleteHistory, is_same_person};
+pub use group_allocation::{AllocationRequest, GroupAllocation};
+pub use group_stats::GroupStats;
+pub use groups::{AgeGroup, AgeGroupID, Group, GroupID, GroupStore, GroupSwitch, SwitchGroupID, AgeGroupIDs};
 pub use achievements::{Achievement, AchievementID};
 pub use pdf::{PDF, merge_pdfs, PDFMessage};
 use async_trait::async_trait;
@@ -23,10 +33,13 @@ use async_trait::async_trait;
 pub trait AchievementStorage {
     async fn get_athlete(&self, athlete_id: &AthleteID) -> Option<Athlete>;
     async fn get_athletes(&self) -> HashMap<String, Vec<Athlete>>;
+    /// All athletes of the competition, including athletes without a group
+    async fn get_all_athletes(&self) -> Result<Vec<Athlete>, Box<dyn Error>>;
     async fn write_athlete(&self, athlete_id: AthleteID, athlete: Athlete) -> Result<String, Box<dyn Error>>;
-    async fn update_athlete(&self, athlete_id: AthleteID, json_string: &str) -> Result<String, Box<dyn Error>>;
+    async fn update_athlete(&self, athlete_id: AthleteID, json_string: &str) -> Result<AthleteUpdate, Box<dyn Error>>;
     async fn delete_athlete(&self, athlete_id: AthleteID) -> Result<String, Box<dyn Error>>;
     async fn get_group(&self, group_id: &GroupID) -> Option<Group>;
+    async fn get_group_store(&self, group_id: &GroupID) -> Option<GroupStore>;
     async fn write_group_store(&self, group_id: GroupID, group_store: GroupStore) -> Result<String, Box<dyn Error>>;
     async fn write_group(&self, group_id: GroupID, group: Group) -> Result<String, Box<dyn Error>>;
     async fn update_group(&self, group_id: GroupID, json_string: &str, only_time_group: bool) -> Result<String, Box<dyn Error>>;
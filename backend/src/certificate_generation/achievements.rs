@@ -70,6 +70,28 @@ impl Achievement {
         }
     }
 
+    /// Numeric final result (seconds, meters or centimeters). None if there is no valid result.
+    pub fn result_value(&self) -> Option<f32> {
+        let value = match self {
+            Achievement::Distance(r) => r.final_result().to_f32(),
+            Achievement::Height(r) => r.final_result() as f32,
+            Achievement::Time(r) => r.final_result().to_f32(),
+        };
+        if value > 0. { Some(value) } else { None }
+    }
+
+    /// Compare two results of the same discipline (lower times and higher distances/heights are better)
+    pub fn is_better_than(&self, other: &Achievement) -> bool {
+        match (self.result_value(), other.result_value()) {
+            (Some(value), Some(other_value)) => match self {
+                Achievement::Time(_) => value < other_value,
+                _ => value > other_value,
+            },
+            (Some(_), None) => true,
+            _ => false,
+        }
+    }
+
     pub fn from_json(json_string: &str) -> Result<Self, serde_json::error::Error> {
         let processed_content = preprocess_json(json_string);
         let mut achievement: Achievement = serde_json::from_str(processed_content.as_str())?;
@@ -135,6 +157,9 @@ impl HeightResult {
     pub fn start_height(&self) -> i32 {
         self.start_height
     }
+    pub fn height_increase(&self) -> i32 {
+        self.height_increase
+    }
     pub fn tries(&self) -> String {
         self.tries.clone()
     }
@@ -0,0 +1,154 @@
+use std::sync::OnceLock;
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+
+use super::CompetitionType;
+
+/// Definition of one age class. The name is appended to the gender of the athlete (e.g. "M" + "40" = "M40",
+/// "W" + "-U10" = "W-U10").
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgeClass {
+    pub name: String,
+    /// Youngest age in this class (inclusive)
+    pub min_age: i32,
+    /// Oldest age in this class (exclusive), no limit if not given
+    #[serde(default)]
+    pub max_age: Option<i32>,
+    /// Competition type of the athletes in this class, used to select the athletes of an age group
+    pub competition_type: CompetitionType,
+    #[serde(default = "default_show_points")]
+    pub show_points: bool,
+    /// Certificate layout of another competition type, by default the layout of the athlete's competition type
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub layout: Option<CompetitionType>,
+}
+
+fn default_show_points() -> bool {
+    true
+}
+
+impl AgeClass {
+    pub fn contains(&self, age: i32) -> bool {
+        self.min_age <= age && self.max_age.is_none_or(|max_age| age < max_age)
+    }
+
+    /// Birth years of the athletes in this class as (exclusive start, inclusive end)
+    pub fn birth_years(&self, current_year: i32) -> (i32, i32) {
+        let start_year = match self.max_age {
+            Some(max_age) => current_year - max_age,
+            None => 0,
+        };
+        (start_year, current_year - self.min_age)
+    }
+
+    pub fn layout(&self, competition_type: &CompetitionType) -> CompetitionType {
+        self.layout.clone().unwrap_or(competition_type.clone())
+    }
+}
+
+/// Age classes which are used to classify the athletes of the given competition types
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgeClassScheme {
+    pub competition_types: Vec<CompetitionType>,
+    pub classes: Vec<AgeClass>,
+}
+
+fn age_class(name: &str, min_age: i32, max_age: Option<i32>, competition_type: CompetitionType, show_points: bool) -> AgeClass {
+    AgeClass {
+        name: name.to_string(),
+        min_age,
+        max_age,
+        competition_type,
+        show_points,
+        layout: None,
+    }
+}
+
+pub fn default_age_classes() -> Vec<AgeClassScheme> {
+    vec![
+        AgeClassScheme {
+            competition_types: vec![CompetitionType::Decathlon],
+            classes: vec![
+                age_class("", 0, Some(40), CompetitionType::Decathlon, true),
+                age_class("40", 40, Some(50), CompetitionType::Decathlon, true),
+                age_class("50", 50, Some(60), CompetitionType::Decathlon, true),
+                age_class("60", 60, Some(70), CompetitionType::Decathlon, true),
+                age_class("70", 70, None, CompetitionType::Decathlon, true),
+            ],
+        },
+        AgeClassScheme {
+            competition_types: vec![CompetitionType::Triathlon, CompetitionType::Pentathlon, CompetitionType::Heptathlon],
+            classes: vec![
+                age_class("-U4", 0, Some(4), CompetitionType::Triathlon, false),
+                age_class("-U6", 4, Some(6), CompetitionType::Triathlon, false),
+                age_class("-U8", 6, Some(8), CompetitionType::Triathlon, false),
+                age_class("-U10", 8, Some(10), CompetitionType::Triathlon, true),
+                age_class("-U12", 10, Some(12), CompetitionType::Triathlon, true),
+                age_class("-U14", 12, Some(14), CompetitionType::Pentathlon, true),
+                age_class("-U16", 14, Some(16), CompetitionType::Heptathlon, true),
+                age_class("", 16, None, CompetitionType::Heptathlon, true),
+            ],
+        },
+    ]
+}
+
+/// Age class definitions, loaded once from the JSON file given in AGE_CLASSES (default definitions otherwise)
+pub fn age_classes() -> &'static [AgeClassScheme] {
+    static AGE_CLASSES: OnceLock<Vec<AgeClassScheme>> = OnceLock::new();
+    AGE_CLASSES.get_or_init(|| {
+        let path = match std::env::var("AGE_CLASSES") {
+            Ok(path) => path,
+            Err(_) => return default_age_classes(),
+        };
+        let schemes = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| serde_json::from_str(&content).map_err(|e| e.to_string()));
+        match schemes {
+            Ok(schemes) => {
+                info!("Age classes loaded from {}", path);
+                schemes
+            }
+            Err(e) => {
+                error!("Could not load age classes from {}: {}. Using default age classes", path, e);
+                default_age_classes()
+            }
+        }
+    })
+}
+
+/// Age class of an athlete with the given competition type and age
+pub fn classify(competition_type: &CompetitionType, age: i32) -> Option<&'static AgeClass> {
+    age_classes()
+        .iter()
+        .filter(|scheme| scheme.competition_types.contains(competition_type))
+        .flat_map(|scheme| scheme.classes.iter())
+        .find(|age_class| age_class.contains(age))
+}
+
+/// First age class with the given name
+pub fn find_age_class(name: &str) -> Option<&'static AgeClass> {
+    age_classes()
+        .iter()
+        .flat_map(|scheme| scheme.classes.iter())
+        .find(|age_class| age_class.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::certificate_generation::CompetitionType;
+    use super::{classify, find_age_class};
+
+    #[test]
+    fn classify_and_select() {
+        assert_eq!(classify(&CompetitionType::Decathlon, 39).expect("Age class should exist").name, "");
+        assert_eq!(classify(&CompetitionType::Decathlon, 75).expect("Age class should exist").name, "70");
+        assert_eq!(classify(&CompetitionType::Pentathlon, 9).expect("Age class should exist").name, "-U10");
+        assert!(!classify(&CompetitionType::Triathlon, 5).expect("Age class should exist").show_points);
+
+        let age_class = find_age_class("-U12").expect("Age class should exist");
+        assert_eq!(age_class.competition_type, CompetitionType::Triathlon);
+        assert_eq!(age_class.birth_years(2024), (2012, 2014));
+        assert_eq!(find_age_class("70").expect("Age class should exist").birth_years(2024), (0, 1954));
+    }
+}
@@ -1,21 +1,7 @@
-use super::{Athlete, CompetitionType};
+use super::{age_classes, Athlete, CompetitionType};
 use chrono::prelude::{Datelike, Utc};
 use log::error;
 
-fn parse_age_group(age_identifier: &str) -> Result<i32, ()> {
-    // Check if the string is not empty and has more than one character
-    if let Some(rest) = age_identifier.get(1..) {
-        // Parse the remaining characters to an integer
-        if let Ok(parsed) = rest.parse::<i32>() {
-            Ok(parsed)
-        } else {
-            Err(())
-        }
-    } else {
-        Err(())
-    }
-}
-
 pub struct AgeGroupSelector {
     pub gender: Option<String>,
     pub start_year: i32,
@@ -24,71 +10,42 @@ pub struct AgeGroupSelector {
 }
 
 impl AgeGroupSelector {
+    /// Selector for an age identifier like "M40", "W" or "U12" (all genders), based on the configured age classes
     pub fn build(age_identifier: &str) -> Result<Self, ()> {
-        let current_year = Utc::now().year();
-
-        if age_identifier.contains("U") {
-            let max_age = parse_age_group(age_identifier)?;
-            let start_year = current_year - max_age + 1;
-            let end_year = start_year + 1;
-            let competition_type: CompetitionType;
-
-            if max_age <= 12 {
-                competition_type = CompetitionType::Triathlon
-            }else if max_age <= 14 {
-                competition_type = CompetitionType::Pentathlon
-            }else {
-                competition_type = CompetitionType::Heptathlon
-            }
-
-            Ok(AgeGroupSelector {
-                gender: None,
-                start_year,
-                end_year,
-                competition_type: competition_type
-            })
-        } else if age_identifier.contains("M") || age_identifier.contains("W") {
-            let gender = age_identifier
-                .chars()
-                .next()
-                .expect("Should at least contains one char since if condition ensures it");
-
-            let start_year;
-            let end_year;
-
-            let min_age = parse_age_group(age_identifier);
-            match min_age {
-                Ok(min_age) => {
-                    end_year = current_year - min_age;
-                    if min_age == 70 {
-                        start_year = 0; // All athletes older than 70
-                    }else{
-                        start_year = end_year - 10;
-                    }
-                }
-                Err(_) => {
-                    end_year = current_year;
-                    start_year = current_year - 40;
-                }
-            };
-
-            Ok(AgeGroupSelector {
-                gender: Some(gender.to_string()),
-                start_year,
-                end_year,
-                competition_type: CompetitionType::Decathlon
-            })
-        } else if age_identifier == "Staffel" {
-            Ok(AgeGroupSelector {
+        if age_identifier == "Staffel" {
+            return Ok(AgeGroupSelector {
                 gender: Some("S".to_string()),
                 start_year:0,
                 end_year: 200,
                 competition_type: CompetitionType::Decathlon
-            })
+            });
+        }
+
+        let (gender, class_name) = if age_identifier.starts_with('U') {
+            (None, format!("-{}", age_identifier))
+        } else if age_identifier.starts_with('M') || age_identifier.starts_with('W') {
+            let (gender, class_name) = age_identifier.split_at(1);
+            (Some(gender.to_string()), class_name.to_string())
         } else {
             error!("Neither 'M', 'W' or 'U' in age_identifier string");
-            Err(())
-        }
+            return Err(());
+        };
+
+        let age_class = match age_classes::find_age_class(&class_name) {
+            Some(age_class) => age_class,
+            None => {
+                error!("No age class defined for {}", age_identifier);
+                return Err(());
+            }
+        };
+        let (start_year, end_year) = age_class.birth_years(Utc::now().year());
+
+        Ok(AgeGroupSelector {
+            gender,
+            start_year,
+            end_year,
+            competition_type: age_class.competition_type.clone()
+        })
     }
 }
 
@@ -0,0 +1,156 @@
+use std::collections::{HashMap, HashSet};
+use chrono::{DateTime, Utc};
+use chrono::serde::ts_seconds;
+use serde::Serialize;
+use super::{Achievement, Athlete, AthleteID, CompetitionType};
+use crate::competitions::Competition;
+
+/// Results of one athlete in a single competition edition
+#[derive(Clone, Debug, Serialize)]
+pub struct EditionResult {
+    pub competition_number: String,
+    pub competition_name: String,
+    #[serde(with = "ts_seconds")]
+    pub start_date: DateTime<Utc>,
+    pub competition_type: CompetitionType,
+    pub total_points: u32,
+    pub achievements: HashMap<String, Achievement>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct PersonalBest {
+    pub competition_number: String,
+    pub achievement: Achievement,
+}
+
+/// All results of an athlete over all competition editions.
+/// Athletes are linked across editions by their athlete id (and birth date if available).
+#[derive(Clone, Debug, Serialize)]
+pub struct AthleteHistory {
+    pub name: String,
+    pub surname: String,
+    pub editions: Vec<EditionResult>,
+    pub personal_bests: HashMap<String, PersonalBest>,
+}
+
+impl AthleteHistory {
+    pub fn build(athlete_id: &AthleteID, editions: Vec<(Competition, Athlete)>) -> Self {
+        let mut edition_results: Vec<EditionResult> = editions
+            .into_iter()
+            .map(|(competition, athlete)| EditionResult {
+                competition_number: competition.number,
+                competition_name: competition.name,
+                start_date: competition.start_date,
+                competition_type: athlete.competition_type().clone(),
+                total_points: athlete.total_point(),
+                achievements: athlete.achievements().clone(),
+            })
+            .collect();
+        edition_results.sort_by_key(|edition| edition.start_date);
+
+        // The first edition with the best result holds the personal best (ties do not count as new PB)
+        let mut personal_bests: HashMap<String, PersonalBest> = HashMap::new();
+        for edition in &edition_results {
+            for (discipline_name, achievement) in &edition.achievements {
+                if achievement.result_value().is_none() {
+                    continue;
+                }
+                let is_new_best = match personal_bests.get(discipline_name) {
+                    Some(personal_best) => achievement.is_better_than(&personal_best.achievement),
+                    None => true,
+                };
+                if is_new_best {
+                    personal_bests.insert(discipline_name.clone(), PersonalBest {
+                        competition_number: edition.competition_number.clone(),
+                        achievement: achievement.clone(),
+                    });
+                }
+            }
+        }
+
+        AthleteHistory {
+            name: athlete_id.name(),
+            surname: athlete_id.surname(),
+            editions: edition_results,
+            personal_bests,
+        }
+    }
+
+    /// Disciplines in which the athlete improved on all earlier editions in the given competition.
+    /// Results of a first participation in a discipline are not marked as personal best.
+    pub fn new_personal_bests(&self, competition_number: &str) -> HashSet<String> {
+        let Some(position) = self.editions
+            .iter()
+            .position(|edition| edition.competition_number == competition_number) else {
+            return HashSet::new();
+        };
+        let earlier_editions = &self.editions[..position];
+
+        self.editions[position].achievements
+            .iter()
+            .filter(|(_, achievement)| achievement.result_value().is_some())
+            .filter(|(discipline_name, achievement)| {
+                let earlier_results: Vec<&Achievement> = earlier_editions
+                    .iter()
+                    .filter_map(|edition| edition.achievements.get(discipline_name.as_str()))
+                    .filter(|earlier| earlier.result_value().is_some())
+                    .collect();
+                !earlier_results.is_empty()
+                    && earlier_results.iter().all(|earlier| achievement.is_better_than(earlier))
+            })
+            .map(|(discipline_name, _)| discipline_name.clone())
+            .collect()
+    }
+}
+
+/// Two athlete entries belong to the same person if the birth dates do not contradict each other
+pub fn is_same_person(athlete: &Athlete, other: &Athlete) -> bool {
+    match (athlete.birth_date(), other.birth_date()) {
+        (Some(birth_date), Some(other_birth_date)) => birth_date.date_naive() == other_birth_date.date_naive(),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::certificate_generation::{Achievement, Athlete, AthleteID, CompetitionType};
+    use crate::competitions::Competition;
+    use super::AthleteHistory;
+
+    fn get_competition(number: &str, start_date: i64) -> Competition {
+        Competition::from_json(format!(r#"{{"number": "{number}", "name": "Favoritner Jedermann Zehnkampf",
+            "start_date": {start_date}, "end_date": {start_date}}}"#).as_str())
+            .expect("Competition JSON should be valid")
+    }
+
+    fn get_athlete(long_jump: &str, run: &str) -> Athlete {
+        let mut athlete = Athlete::new("Max", "Mustermann", None, "M", HashMap::new(),
+                                       CompetitionType::Decathlon, Some(1), None, None);
+        let long_jump = Achievement::from_json(format!(r#"{{"Distance": {{"name": "Weitsprung",
+            "first_try": {long_jump}, "unit": "m"}}}}"#).as_str()).expect("Achievement should be valid");
+        let run = Achievement::from_json(format!(r#"{{"Time": {{"name": "100 Meter Lauf",
+            "final_result": {run}, "unit": "s"}}}}"#).as_str()).expect("Achievement should be valid");
+        athlete.add_achievement(long_jump).expect("Achievement should be new");
+        athlete.add_achievement(run).expect("Achievement should be new");
+        athlete
+    }
+
+    #[test]
+    fn personal_bests() {
+        let history = AthleteHistory::build(&AthleteID::new("Max", "Mustermann"), vec![
+            (get_competition("30", 1758931200), get_athlete("5.10", "12.50")),
+            (get_competition("28", 1695427200), get_athlete("5.20", "13.10")),
+            (get_competition("29", 1727049600), get_athlete("4.90", "12.80")),
+        ]);
+
+        assert_eq!(history.editions.iter().map(|e| e.competition_number.as_str()).collect::<Vec<_>>(),
+                   vec!["28", "29", "30"]);
+        assert_eq!(history.personal_bests["Weitsprung"].competition_number, "28");
+        assert_eq!(history.personal_bests["100 Meter Lauf"].competition_number, "30");
+
+        assert!(history.new_personal_bests("28").is_empty());
+        assert_eq!(history.new_personal_bests("29").into_iter().collect::<Vec<_>>(), vec!["100 Meter Lauf"]);
+        assert_eq!(history.new_personal_bests("30").into_iter().collect::<Vec<_>>(), vec!["100 Meter Lauf"]);
+    }
+}
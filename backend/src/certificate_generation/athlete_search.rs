@@ -0,0 +1,384 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use super::{Athlete, CompetitionType};
+
+const DEFAULT_PAGE_SIZE: usize = 50;
+const MAX_PAGE_SIZE: usize = 500;
+
+/// Registration state of an athlete as seen by the registration desk
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum AthleteStatus {
+    Registered, // checked in and got a starting number
+    Pending, // signed up but not yet checked in
+    Deregistered,
+}
+
+impl AthleteStatus {
+    pub fn of(athlete: &Athlete) -> Self {
+        if athlete.is_deregistered() {
+            AthleteStatus::Deregistered
+        } else if athlete.starting_number().is_some() {
+            AthleteStatus::Registered
+        } else {
+            AthleteStatus::Pending
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub enum SortField {
+    Relevance,
+    Name,
+    Surname,
+    StartingNumber,
+    TotalPoints,
+    AgeGroup,
+    Group,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+/// Query for the athlete search. All given filters have to match.
+/// `q` is matched fuzzy and diacritic insensitive against name and surname, or against the
+/// starting number if it is numeric.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct AthleteQuery {
+    pub q: Option<String>,
+    pub starting_number: Option<u16>,
+    pub group: Option<String>,
+    pub age_group: Option<String>,
+    pub competition_type: Option<CompetitionType>,
+    pub status: Option<AthleteStatus>,
+    pub paid: Option<bool>,
+    pub sort_by: Option<SortField>,
+    pub order: Option<SortOrder>,
+    pub page: Option<usize>,
+    pub page_size: Option<usize>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct AthleteSearchHit {
+    pub group_name: String,
+    pub status: AthleteStatus,
+    pub athlete: Athlete,
+    #[serde(skip)]
+    score: u32,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct AthleteSearchResult {
+    pub total: usize,
+    pub page: usize,
+    pub page_size: usize,
+    pub athletes: Vec<AthleteSearchHit>,
+}
+
+impl AthleteQuery {
+    /// Filter, sort and paginate the athletes of all groups (as returned by `get_athletes`)
+    pub fn search(&self, group_athletes: HashMap<String, Vec<Athlete>>) -> AthleteSearchResult {
+        let query = self.q.as_deref().map(str::trim).filter(|q| !q.is_empty());
+        let query_number = query.and_then(|q| q.parse::<u16>().ok());
+        let query_tokens = query.map(tokenize).unwrap_or_default();
+
+        let mut hits: Vec<AthleteSearchHit> = vec![];
+        for (group_name, athletes) in group_athletes {
+            for athlete in athletes {
+                let score = match (query, query_number) {
+                    (None, _) => 0,
+                    (Some(_), Some(number)) => {
+                        if *athlete.starting_number() == Some(number) { 0 } else { continue }
+                    }
+                    (Some(_), None) => match name_score(&query_tokens, &athlete) {
+                        Some(score) => score,
+                        None => continue,
+                    },
+                };
+                if !self.matches_filters(&group_name, &athlete) {
+                    continue;
+                }
+                hits.push(AthleteSearchHit {
+                    group_name: group_name.clone(),
+                    status: AthleteStatus::of(&athlete),
+                    athlete,
+                    score,
+                });
+            }
+        }
+
+        let sort_field = self.sort_by.clone().unwrap_or(if query.is_some() {
+            SortField::Relevance
+        } else {
+            SortField::Group
+        });
+        hits.sort_by(|a, b| {
+            let ordering = compare_hits(a, b, &sort_field);
+            let ordering = match self.order {
+                Some(SortOrder::Desc) => ordering.reverse(),
+                _ => ordering,
+            };
+            ordering.then_with(|| a.athlete.athlete_id().cmp(&b.athlete.athlete_id()))
+        });
+
+        let page_size = self.page_size.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+        let page = self.page.unwrap_or(1).max(1);
+        let total = hits.len();
+        let athletes = hits
+            .into_iter()
+            .skip(page.saturating_sub(1).saturating_mul(page_size))
+            .take(page_size)
+            .collect();
+
+        AthleteSearchResult {
+            total,
+            page,
+            page_size,
+            athletes,
+        }
+    }
+
+    fn matches_filters(&self, group_name: &str, athlete: &Athlete) -> bool {
+        if let Some(starting_number) = self.starting_number {
+            if *athlete.starting_number() != Some(starting_number) {
+                return false;
+            }
+        }
+        if let Some(group) = &self.group {
+            if !group.trim().eq_ignore_ascii_case(group_name) {
+                return false;
+            }
+        }
+        if let Some(age_group) = &self.age_group {
+            if !age_group.trim().eq_ignore_ascii_case(&athlete.age_group()) {
+                return false;
+            }
+        }
+        if let Some(competition_type) = &self.competition_type {
+            if athlete.competition_type() != competition_type {
+                return false;
+            }
+        }
+        if let Some(status) = &self.status {
+            if AthleteStatus::of(athlete) != *status {
+                return false;
+            }
+        }
+        if let Some(paid) = self.paid {
+            if athlete.paid().unwrap_or(false) != paid {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+fn compare_hits(a: &AthleteSearchHit, b: &AthleteSearchHit, sort_field: &SortField) -> Ordering {
+    match sort_field {
+        SortField::Relevance => a.score.cmp(&b.score),
+        SortField::Name => fold(a.athlete.name()).cmp(&fold(b.athlete.name())),
+        SortField::Surname => fold(a.athlete.surname()).cmp(&fold(b.athlete.surname())),
+        // Athletes without starting number are listed last
+        SortField::StartingNumber => match (a.athlete.starting_number(), b.athlete.starting_number()) {
+            (Some(a), Some(b)) => a.cmp(b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        },
+        SortField::TotalPoints => a.athlete.total_point().cmp(&b.athlete.total_point()),
+        SortField::AgeGroup => a.athlete.age_group().cmp(&b.athlete.age_group()),
+        SortField::Group => a.group_name.cmp(&b.group_name)
+            .then_with(|| fold(a.athlete.surname()).cmp(&fold(b.athlete.surname()))),
+    }
+}
+
+/// Score how well all query tokens match the name of the athlete (lower is better).
+/// Returns None if at least one query token does not match any part of the name.
+fn name_score(query_tokens: &[String], athlete: &Athlete) -> Option<u32> {
+    let name_tokens: Vec<String> = tokenize(athlete.name())
+        .into_iter()
+        .chain(tokenize(athlete.surname()))
+        .collect();
+
+    let mut total_score = 0;
+    for query_token in query_tokens {
+        let best_score = name_tokens
+            .iter()
+            .filter_map(|name_token| token_score(query_token, name_token))
+            .min()?;
+        total_score += best_score;
+    }
+    Some(total_score)
+}
+
+fn token_score(query_token: &str, name_token: &str) -> Option<u32> {
+    if query_token == name_token {
+        Some(0)
+    } else if name_token.starts_with(query_token) {
+        Some(1)
+    } else if name_token.contains(query_token) {
+        Some(2)
+    } else {
+        let max_distance = match query_token.chars().count() {
+            0..=3 => 0,
+            4..=6 => 1,
+            _ => 2,
+        };
+        let distance = levenshtein(query_token, name_token);
+        if distance <= max_distance {
+            Some(2 + distance as u32)
+        } else {
+            None
+        }
+    }
+}
+
+fn tokenize(text: &str) -> Vec<String> {
+    fold(text)
+        .split(|c: char| c.is_whitespace() || c == '-')
+        .filter(|token| !token.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Lowercase the text and replace diacritics by their ascii representation (e.g. "ü" -> "ue")
+fn fold(text: &str) -> String {
+    let mut folded = String::with_capacity(text.len());
+    for c in text.to_lowercase().chars() {
+        match c {
+            'ä' | 'æ' => folded.push_str("ae"),
+            'ö' | 'ø' | 'œ' => folded.push_str("oe"),
+            'ü' => folded.push_str("ue"),
+            'ß' => folded.push_str("ss"),
+            'à' | 'á' | 'â' | 'ã' | 'å' | 'ā' | 'ă' | 'ą' => folded.push('a'),
+            'ç' | 'ć' | 'č' => folded.push('c'),
+            'ď' | 'đ' => folded.push('d'),
+            'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ė' | 'ę' | 'ě' => folded.push('e'),
+            'ì' | 'í' | 'î' | 'ï' | 'ī' | 'į' => folded.push('i'),
+            'ł' | 'ľ' | 'ĺ' => folded.push('l'),
+            'ñ' | 'ń' | 'ň' => folded.push('n'),
+            'ò' | 'ó' | 'ô' | 'õ' | 'ō' | 'ő' => folded.push('o'),
+            'ř' | 'ŕ' => folded.push('r'),
+            'ś' | 'š' | 'ş' | 'ș' => folded.push('s'),
+            'ť' | 'ţ' | 'ț' => folded.push('t'),
+            'ù' | 'ú' | 'û' | 'ū' | 'ů' | 'ű' | 'ų' => folded.push('u'),
+            'ý' | 'ÿ' => folded.push('y'),
+            'ź' | 'ż' | 'ž' => folded.push('z'),
+            c => folded.push(c),
+        }
+    }
+    folded
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b_chars: Vec<char> = b.chars().collect();
+    let mut previous_row: Vec<usize> = (0..=b_chars.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current_row = vec![i + 1];
+        for (j, b_char) in b_chars.iter().enumerate() {
+            let substitution_cost = if a_char == *b_char { 0 } else { 1 };
+            let value = (previous_row[j] + substitution_cost)
+                .min(previous_row[j + 1] + 1)
+                .min(current_row[j] + 1);
+            current_row.push(value);
+        }
+        previous_row = current_row;
+    }
+
+    previous_row[b_chars.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::certificate_generation::{Athlete, CompetitionType};
+    use super::{fold, AthleteQuery, AthleteStatus, SortField, SortOrder};
+
+    fn get_group_athletes() -> HashMap<String, Vec<Athlete>> {
+        let deregistered = Athlete::from_json(r#"{"name": "Anna", "surname": "Schmid", "gender": "W",
+            "achievements": {}, "competition_type": "Decathlon", "starting_number": 3, "deregistered": true}"#)
+            .expect("Athlete JSON should be valid");
+
+        HashMap::from([
+            ("Gruppe 1".to_string(), vec![
+                Athlete::new("Thomas", "Müller", None, "M", HashMap::new(),
+                             CompetitionType::Decathlon, Some(1), None, None),
+                Athlete::new("José", "García", None, "M", HashMap::new(),
+                             CompetitionType::Decathlon, Some(2), None, None),
+                deregistered,
+            ]),
+            ("U12".to_string(), vec![
+                Athlete::new("Lena", "Mueller", None, "W", HashMap::new(),
+                             CompetitionType::Triathlon, None, None, None),
+            ]),
+        ])
+    }
+
+    fn names(query: &AthleteQuery) -> Vec<String> {
+        query.search(get_group_athletes())
+            .athletes
+            .iter()
+            .map(|hit| hit.athlete.athlete_id())
+            .collect()
+    }
+
+    #[test]
+    fn fold_diacritics() {
+        assert_eq!(fold("Müller"), "mueller");
+        assert_eq!(fold("García Straße"), "garcia strasse");
+    }
+
+    #[test]
+    fn fuzzy_name_search() {
+        let query = AthleteQuery { q: Some("Mueller".to_string()), ..Default::default() };
+        assert_eq!(names(&query).len(), 2);
+
+        let query = AthleteQuery { q: Some("muller thomas".to_string()), ..Default::default() };
+        assert_eq!(names(&query), vec!["Thomas_Müller"]);
+
+        let query = AthleteQuery { q: Some("Jose".to_string()), ..Default::default() };
+        assert_eq!(names(&query), vec!["José_García"]);
+    }
+
+    #[test]
+    fn starting_number_lookup() {
+        let query = AthleteQuery { q: Some("2".to_string()), ..Default::default() };
+        assert_eq!(names(&query), vec!["José_García"]);
+    }
+
+    #[test]
+    fn filter_and_paginate() {
+        let query = AthleteQuery {
+            status: Some(AthleteStatus::Registered),
+            sort_by: Some(SortField::StartingNumber),
+            order: Some(SortOrder::Desc),
+            ..Default::default()
+        };
+        assert_eq!(names(&query), vec!["José_García", "Thomas_Müller"]);
+
+        let query = AthleteQuery {
+            competition_type: Some(CompetitionType::Triathlon),
+            ..Default::default()
+        };
+        assert_eq!(names(&query), vec!["Lena_Mueller"]);
+
+        let query = AthleteQuery {
+            sort_by: Some(SortField::Name),
+            page: Some(2),
+            page_size: Some(3),
+            ..Default::default()
+        };
+        let result = query.search(get_group_athletes());
+        assert_eq!(result.total, 4);
+        assert_eq!(result.athletes.len(), 1);
+        assert_eq!(result.athletes[0].athlete.name(), "Thomas");
+
+        // Pages beyond the last one are empty
+        let query = AthleteQuery { page: Some(usize::MAX), ..Default::default() };
+        assert!(query.search(get_group_athletes()).athletes.is_empty());
+    }
+}
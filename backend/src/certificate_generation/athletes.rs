@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use super::achievements::Achievement;
 use chrono::{DateTime, Datelike, LocalResult, TimeZone, Utc};
 use chrono::serde::ts_seconds_option;
@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 use std::error::Error;
 use serde_json::Value;
 use super::{CompetitionType, preprocess_json};
+use super::age_classes::{self, AgeClass};
 
 /// Athlete struct that contains all information for an athlete as well as all their achievements
 #[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
@@ -27,7 +28,13 @@ pub struct Athlete {
     t_shirt: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     paid: Option<bool>,
-    deregistered: bool
+    deregistered: bool,
+    /// Best times in milliseconds declared at the registration, by discipline (used to seed the runs)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    declared_best_times: HashMap<String, u32>,
+    /// Disciplines in which the athlete achieved a new personal best (only set for PDF generation)
+    #[serde(skip)]
+    personal_bests: HashSet<String>,
 }
 
 impl Athlete {
@@ -53,7 +60,9 @@ impl Athlete {
             total_points,
             t_shirt,
             paid: None,
-            deregistered: false
+            deregistered: false,
+            declared_best_times: HashMap::new(),
+            personal_bests: HashSet::new(),
         }
     }
 
@@ -92,45 +101,42 @@ impl Athlete {
     pub fn is_active(&self) -> bool {
         !self.deregistered && self.starting_number.is_some()
     }
+    pub fn is_deregistered(&self) -> bool {
+        self.deregistered
+    }
+    pub fn paid(&self) -> Option<bool> {
+        self.paid
+    }
     pub fn competition_type(&self) -> &CompetitionType {
         &self.competition_type
     }
+    pub fn declared_best_times(&self) -> &HashMap<String, u32> {
+        &self.declared_best_times
+    }
+
+    /// Age class of the athlete according to the configured age classes
+    pub fn age_class(&self) -> Option<&'static AgeClass> {
+        let birth_date = self.birth_date()?;
+        let years = Utc::now().year() - birth_date.year();
+        age_classes::classify(&self.competition_type, years)
+    }
 
     pub fn age_group(&self) -> String {
-        match self.competition_type {
-            CompetitionType::Decathlon => {
-                let mut age_group = self.gender.clone();
-                if let Some(birth_date) = self.birth_date() {
-                    let years = Utc::now().year() - birth_date.year();
-                    //let years = Utc::now().years_since(birth_date).unwrap_or(0);
-                    match years {
-                        age if age < 40 => age_group += "",
-                        age if age < 50 => age_group += "40",
-                        age if age < 60 => age_group += "50",
-                        age if age < 70 => age_group += "60",
-                        _ => age_group += "70"
-                    }
-                }
-                age_group
-            },
-            _ => {
-                let mut age_group = self.gender.clone();
-                if let Some(birth_date) = self.birth_date() {
-                    let years = Utc::now().year() - birth_date.year();
-                    //let years = Utc::now().years_since(birth_date).unwrap_or(0);
-                    match years {
-                        age if age < 4 => age_group += "-U4",
-                        age if age < 6 => age_group += "-U6",
-                        age if age < 8 => age_group += "-U8",
-                        age if age < 10 => age_group += "-U10",
-                        age if age < 12 => age_group += "-U12",
-                        age if age < 14 => age_group += "-U14",
-                        age if age < 16 => age_group += "-U16",
-                        _ => age_group += ""
-                    }
-                }
-                age_group
-            }
+        match self.age_class() {
+            Some(age_class) => format!("{}{}", self.gender, age_class.name),
+            None => self.gender.clone(),
+        }
+    }
+
+    /// Whether points are printed on the certificate (not for the youngest age classes)
+    pub fn show_points(&self) -> bool {
+        self.age_class().is_none_or(|age_class| age_class.show_points)
+    }
+
+    pub fn certificate_layout(&self) -> CompetitionType {
+        match self.age_class() {
+            Some(age_class) => age_class.layout(&self.competition_type),
+            None => self.competition_type.clone(),
         }
     }
 
@@ -201,10 +207,41 @@ impl Athlete {
                 None => self.starting_number = None
             }
         }
+        if let Some(deregistered) = json_value.get("deregistered") {
+            self.deregistered = Value::as_bool(deregistered)
+                .ok_or("Invalid format for deregistered. Expected bool")?;
+        }
+        if let Some(declared_best_times) = json_value.get("declared_best_times") {
+            self.declared_best_times = serde_json::from_value(declared_best_times.clone())
+                .map_err(|e| format!("Invalid format for declared_best_times. Expected milliseconds by discipline: {e}"))?;
+        }
 
         Ok(())
     }
 
+    /// Replace all personal data by the given pseudonym.
+    /// Gender, birth year, competition type and results are kept, so rankings and age groups do not change.
+    pub fn anonymize(&mut self, name: &str, surname: &str) {
+        self.name = name.to_string();
+        self.surname = surname.to_string();
+        self.birth_date = self.birth_date
+            .and_then(|birth_date| Utc.with_ymd_and_hms(birth_date.year(), 1, 1, 0, 0, 0).single());
+        self.t_shirt = None;
+        self.paid = None;
+    }
+
+    pub fn achievements(&self) -> &HashMap<String, Achievement> {
+        &self.achievements
+    }
+
+    pub fn set_personal_bests(&mut self, disciplines: HashSet<String>) {
+        self.personal_bests = disciplines;
+    }
+
+    pub fn is_personal_best(&self, discipline_name: &str) -> bool {
+        self.personal_bests.contains(discipline_name)
+    }
+
     pub fn get_achievement(&self, query_name: &str) -> Option<&Achievement> {
         self.achievements.get(&query_name.to_string())
     }
@@ -275,6 +312,34 @@ impl AthleteID {
 
 }
 
+/// Outcome of an athlete update, registration changes have to be reflected in the group and the waitlist
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AthleteUpdate {
+    Updated,
+    Registered,
+    Deregistered,
+}
+
+impl AthleteUpdate {
+    pub fn from_registration(was_deregistered: bool, is_deregistered: bool) -> Self {
+        match (was_deregistered, is_deregistered) {
+            (false, true) => AthleteUpdate::Deregistered,
+            (true, false) => AthleteUpdate::Registered,
+            _ => AthleteUpdate::Updated,
+        }
+    }
+}
+
+impl std::fmt::Display for AthleteUpdate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AthleteUpdate::Updated => write!(f, "Athlete updated"),
+            AthleteUpdate::Registered => write!(f, "Athlete registered"),
+            AthleteUpdate::Deregistered => write!(f, "Athlete deregistered"),
+        }
+    }
+}
+
 
 #[cfg(test)]
 mod tests {
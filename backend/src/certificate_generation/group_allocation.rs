@@ -0,0 +1,335 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::error::Error;
+use serde::{Deserialize, Serialize};
+
+use super::{Athlete, AthleteID, CompetitionType, GroupID, GroupStore};
+use crate::time_planner::{self, TimeGroupID};
+use crate::Storage;
+
+/// Prefix of all decathlon groups ("Gruppe 1", "Gruppe 2", ...)
+const GROUP_PREFIX: &str = "Gruppe";
+
+/// Parameters for the automatic group allocation of all registered decathlon athletes
+#[derive(Clone, Debug, Deserialize)]
+pub struct AllocationRequest {
+    /// Maximum number of athletes per group, one entry per group ("Gruppe 1", "Gruppe 2", ...)
+    pub group_sizes: Vec<usize>,
+    /// Athletes which have to start in the same group (e.g. friends, family)
+    #[serde(default)]
+    pub keep_together: Vec<Vec<AthleteID>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AllocatedGroup {
+    pub name: String,
+    #[serde(default)]
+    pub capacity: usize,
+    pub athlete_ids: Vec<AthleteID>,
+    /// Number of athletes per age class, only informative for the review
+    #[serde(default)]
+    pub age_groups: BTreeMap<String, usize>,
+}
+
+/// Proposal of the group allocation. Nothing is stored until the proposal is committed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GroupAllocation {
+    pub groups: Vec<AllocatedGroup>,
+    /// Athletes which did not fit into any group
+    #[serde(default)]
+    pub unassigned: Vec<AthleteID>,
+}
+
+impl GroupAllocation {
+    /// Distribute all registered decathlon athletes over the groups.
+    /// Athletes which have to stay together are placed as one unit, the age classes are spread
+    /// evenly relative to the group capacity.
+    pub fn propose(athletes: &[Athlete], request: &AllocationRequest) -> Self {
+        let mut athletes: Vec<&Athlete> = athletes
+            .iter()
+            .filter(|athlete| !athlete.is_deregistered() && *athlete.competition_type() == CompetitionType::Decathlon)
+            .collect();
+        athletes.sort_by_key(|athlete| athlete.athlete_id());
+
+        let mut groups: Vec<AllocatedGroup> = request.group_sizes
+            .iter()
+            .enumerate()
+            .map(|(index, capacity)| AllocatedGroup {
+                name: format!("{} {}", GROUP_PREFIX, index + 1),
+                capacity: *capacity,
+                athlete_ids: vec![],
+                age_groups: BTreeMap::new(),
+            })
+            .collect();
+
+        let mut unassigned = vec![];
+        for unit in units(&athletes, &request.keep_together) {
+            let best_group = groups
+                .iter()
+                .enumerate()
+                .filter(|(_, group)| group.athlete_ids.len() + unit.len() <= group.capacity)
+                .map(|(index, group)| (index, placement_cost(group, &unit)))
+                .min_by(|(_, cost), (_, other_cost)| cost.total_cmp(other_cost))
+                .map(|(index, _)| index);
+
+            match best_group {
+                Some(index) => {
+                    let group = &mut groups[index];
+                    for athlete in unit {
+                        group.athlete_ids.push(AthleteID::from_athlete(athlete));
+                        *group.age_groups.entry(athlete.age_group()).or_default() += 1;
+                    }
+                }
+                None => unassigned.extend(unit.into_iter().map(AthleteID::from_athlete)),
+            }
+        }
+
+        GroupAllocation { groups, unassigned }
+    }
+
+    /// Check a (reviewed or edited) allocation with the same rules as the proposal: every athlete is a registered
+    /// decathlon athlete, allocated at most once, and no group exceeds its capacity. If the allocation does not
+    /// give a capacity, the capacity of the stored group applies.
+    fn check(&self, athletes: &HashMap<AthleteID, Athlete>, stored_capacities: &HashMap<String, usize>) -> Result<(), Box<dyn Error>> {
+        let mut allocated = HashSet::new();
+        for group in &self.groups {
+            if !group.name.starts_with(GROUP_PREFIX) {
+                return Err(Box::from(format!("Invalid group name {}", group.name)));
+            }
+            let capacity = Some(group.capacity)
+                .filter(|capacity| *capacity > 0)
+                .or_else(|| stored_capacities.get(&group.name).copied());
+            if let Some(capacity) = capacity.filter(|capacity| group.athlete_ids.len() > *capacity) {
+                return Err(Box::from(format!("{} athletes exceed the capacity {} of {}", group.athlete_ids.len(), capacity, group.name)));
+            }
+            for athlete_id in &group.athlete_ids {
+                let athlete = athletes.get(athlete_id).ok_or(format!("Athlete with ID {:?} not found", athlete_id))?;
+                if *athlete.competition_type() != CompetitionType::Decathlon {
+                    return Err(Box::from(format!("Athlete with ID {:?} is not a decathlon athlete", athlete_id)));
+                }
+                if athlete.is_deregistered() {
+                    return Err(Box::from(format!("Athlete with ID {:?} is deregistered", athlete_id)));
+                }
+                if !allocated.insert(athlete_id) {
+                    return Err(Box::from(format!("Athlete with ID {:?} allocated twice", athlete_id)));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn load_athletes(storage: &(dyn Storage + Send + Sync)) -> Result<HashMap<AthleteID, Athlete>, Box<dyn Error>> {
+        Ok(storage
+            .get_all_athletes()
+            .await?
+            .into_iter()
+            .map(|athlete| (AthleteID::from_athlete(&athlete), athlete))
+            .collect())
+    }
+
+    /// Check the allocation against the stored athletes and groups before it is committed. The validated allocation
+    /// keeps the loaded athletes and groups, so the commit does not load them again.
+    pub async fn validate(self, storage: &(dyn Storage + Send + Sync)) -> Result<ValidatedAllocation, Box<dyn Error>> {
+        let athletes = Self::load_athletes(storage).await?;
+
+        // Decathlon groups which are not part of the allocation are emptied
+        let obsolete_groups: Vec<String> = storage.get_athletes().await
+            .into_keys()
+            .filter(|name| name.starts_with(GROUP_PREFIX) && !self.groups.iter().any(|group| group.name == *name))
+            .collect();
+        let mut group_stores = HashMap::new();
+        for group_name in self.groups.iter().map(|group| &group.name).chain(&obsolete_groups) {
+            if let Some(group_store) = storage.get_group_store(&GroupID::new(group_name)).await {
+                group_stores.insert(group_name.clone(), group_store);
+            }
+        }
+        let stored_capacities = group_stores
+            .iter()
+            .filter_map(|(group_name, group_store)| group_store.capacity.map(|capacity| (group_name.clone(), capacity)))
+            .collect();
+        self.check(&athletes, &stored_capacities)?;
+
+        Ok(ValidatedAllocation { allocation: self, athletes, group_stores, obsolete_groups })
+    }
+}
+
+/// Allocation which passed the check, together with the athletes and groups it was checked against
+#[derive(Debug)]
+pub struct ValidatedAllocation {
+    allocation: GroupAllocation,
+    athletes: HashMap<AthleteID, Athlete>,
+    group_stores: HashMap<String, GroupStore>,
+    obsolete_groups: Vec<String>,
+}
+
+impl ValidatedAllocation {
+    /// Store the allocation as group stores, then rebuild the starting orders of the time groups.
+    /// Groups are only written if they were not changed since the validation. Committing the same allocation again
+    /// leads to the same result, so a commit which failed part way is completed by validating and committing again.
+    pub async fn commit(self, storage: &(dyn Storage + Send + Sync)) -> Result<String, Box<dyn Error>> {
+        let mut group_members: Vec<(String, Vec<AthleteID>)> = self.allocation.groups
+            .iter()
+            .map(|group| (group.name.clone(), group.athlete_ids.clone()))
+            .collect();
+        group_members.extend(self.obsolete_groups.iter().map(|name| (name.clone(), vec![])));
+
+        for (group_name, athlete_ids) in &group_members {
+            let mut group_store = match self.group_stores.get(group_name) {
+                Some(group_store) => group_store.clone(),
+                None => GroupStore {
+                    name: group_name.clone(),
+                    athlete_ids: HashSet::new(),
+                    competition_type: CompetitionType::Decathlon,
+                    notes: HashMap::new(),
+                    capacity: None,
+                    version: 0,
+                }
+            };
+            group_store.athlete_ids = athlete_ids.iter().cloned().collect();
+            if let Some(group) = self.allocation.groups.iter().find(|group| group.name == *group_name && group.capacity > 0) {
+                group_store.capacity = Some(group.capacity);
+            }
+            storage.write_group_store(GroupID::new(group_name), group_store).await?;
+        }
+
+        // Time groups last, their starting orders follow the stored groups
+        for (group_name, athlete_ids) in group_members {
+            if let Some(mut time_group) = storage.get_time_group(&TimeGroupID::new(group_name)).await {
+                let time_athletes = athlete_ids
+                    .iter()
+                    .filter_map(|athlete_id| self.athletes.get(athlete_id))
+                    .map(|athlete| time_planner::Athlete::new(
+                        athlete.name().to_string(),
+                        athlete.surname().to_string(),
+                        Some(athlete.age_group())))
+                    .collect();
+                time_group.replace_athletes(time_athletes)?;
+                storage.store_time_group(time_group).await?;
+            }
+        }
+
+        Ok(String::from("Group allocation stored"))
+    }
+}
+
+/// Split the athletes into units which have to be placed in the same group.
+/// Larger units are placed first, since they are the hardest to fit.
+fn units<'a>(athletes: &[&'a Athlete], keep_together: &[Vec<AthleteID>]) -> Vec<Vec<&'a Athlete>> {
+    let index_of: HashMap<AthleteID, usize> = athletes
+        .iter()
+        .enumerate()
+        .map(|(index, athlete)| (AthleteID::from_athlete(athlete), index))
+        .collect();
+
+    // Union find over all requested pairs
+    let mut parent: Vec<usize> = (0..athletes.len()).collect();
+    fn root(parent: &mut [usize], index: usize) -> usize {
+        let mut index = index;
+        while parent[index] != index {
+            parent[index] = parent[parent[index]];
+            index = parent[index];
+        }
+        index
+    }
+    for members in keep_together {
+        let indices: Vec<usize> = members.iter().filter_map(|id| index_of.get(id).copied()).collect();
+        for pair in indices.windows(2) {
+            let (first, second) = (root(&mut parent, pair[0]), root(&mut parent, pair[1]));
+            parent[second] = first;
+        }
+    }
+
+    let mut units: BTreeMap<usize, Vec<&Athlete>> = BTreeMap::new();
+    for (index, athlete) in athletes.iter().enumerate() {
+        units.entry(root(&mut parent, index)).or_default().push(athlete);
+    }
+    let mut units: Vec<Vec<&Athlete>> = units.into_values().collect();
+    units.sort_by_key(|unit| std::cmp::Reverse(unit.len()));
+    units
+}
+
+/// The lower the cost, the better the unit fits into the group.
+/// Counts how crowded the age classes of the unit and the group itself are relative to the capacity.
+fn placement_cost(group: &AllocatedGroup, unit: &[&Athlete]) -> f64 {
+    let capacity = group.capacity.max(1) as f64;
+    let age_class_cost: f64 = unit
+        .iter()
+        .map(|athlete| (*group.age_groups.get(&athlete.age_group()).unwrap_or(&0) + 1) as f64 / capacity)
+        .sum();
+    age_class_cost + (group.athlete_ids.len() + unit.len()) as f64 / capacity
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use chrono::{TimeZone, Utc};
+    use crate::certificate_generation::{Athlete, AthleteID, CompetitionType};
+    use super::{AllocatedGroup, AllocationRequest, GroupAllocation};
+
+    fn get_athlete(name: &str, birth_year: i32) -> Athlete {
+        Athlete::new(name, "Muster", Utc.with_ymd_and_hms(birth_year, 3, 1, 0, 0, 0).single(), "M",
+                     HashMap::new(), CompetitionType::Decathlon, None, None, None)
+    }
+
+    fn get_athletes() -> Vec<Athlete> {
+        vec![
+            get_athlete("A", 1998), get_athlete("B", 1998), get_athlete("C", 1998), get_athlete("D", 1998),
+            get_athlete("E", 1982), get_athlete("F", 1982), get_athlete("G", 1982), get_athlete("H", 1982),
+        ]
+    }
+
+    #[test]
+    fn balance_age_groups() {
+        let request = AllocationRequest { group_sizes: vec![4, 4], keep_together: vec![] };
+        let allocation = GroupAllocation::propose(&get_athletes(), &request);
+
+        assert!(allocation.unassigned.is_empty());
+        for group in &allocation.groups {
+            assert_eq!(group.athlete_ids.len(), 4);
+            assert!(group.age_groups.values().all(|count| *count == 2));
+        }
+    }
+
+    #[test]
+    fn keep_pairs_and_capacity() {
+        let request = AllocationRequest {
+            group_sizes: vec![3, 3],
+            keep_together: vec![vec![AthleteID::new("A", "Muster"), AthleteID::new("B", "Muster"), AthleteID::new("C", "Muster")]],
+        };
+        let allocation = GroupAllocation::propose(&get_athletes(), &request);
+
+        assert_eq!(allocation.unassigned.len(), 2);
+        let group = allocation.groups
+            .iter()
+            .find(|group| group.athlete_ids.contains(&AthleteID::new("A", "Muster")))
+            .expect("Athlete should be allocated");
+        assert!(group.athlete_ids.contains(&AthleteID::new("B", "Muster")));
+        assert!(group.athlete_ids.contains(&AthleteID::new("C", "Muster")));
+        assert!(allocation.groups.iter().all(|group| group.athlete_ids.len() <= 3));
+    }
+
+    #[test]
+    fn reject_invalid_allocation() {
+        let athletes: HashMap<AthleteID, Athlete> = get_athletes()
+            .into_iter()
+            .chain([Athlete::new("Y", "Muster", None, "W", HashMap::new(), CompetitionType::Triathlon, None, None, None)])
+            .map(|athlete| (AthleteID::from_athlete(&athlete), athlete))
+            .collect();
+        let allocation = |capacity: usize, names: &[&str]| GroupAllocation {
+            groups: vec![AllocatedGroup {
+                name: String::from("Gruppe 1"),
+                capacity,
+                athlete_ids: names.iter().map(|name| AthleteID::new(name, "Muster")).collect(),
+                age_groups: Default::default(),
+            }],
+            unassigned: vec![],
+        };
+        let stored_capacities = HashMap::from([(String::from("Gruppe 1"), 2)]);
+
+        assert!(allocation(3, &["A", "B", "C"]).check(&athletes, &stored_capacities).is_ok());
+        assert!(allocation(2, &["A", "B", "C"]).check(&athletes, &stored_capacities).is_err());
+        assert!(allocation(0, &["A", "B", "C"]).check(&athletes, &stored_capacities).is_err());
+        assert!(allocation(0, &["A", "B"]).check(&athletes, &stored_capacities).is_ok());
+        assert!(allocation(3, &["A", "Y"]).check(&athletes, &stored_capacities).is_err());
+        assert!(allocation(3, &["A", "A"]).check(&athletes, &stored_capacities).is_err());
+    }
+}
@@ -0,0 +1,169 @@
+use serde::{Deserialize, Serialize};
+
+use super::{competition_order, Achievement, CompetitionType, Group};
+use crate::time_planner::TimeGroup;
+
+/// Statistics of one discipline within a group. Times are in seconds, distances in meters and heights
+/// in centimeters.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DisciplineStats {
+    pub discipline: String,
+    /// Number of valid results entered
+    pub results: usize,
+    pub mean: Option<f32>,
+    pub median: Option<f32>,
+    pub best: Option<f32>,
+    pub mean_points: Option<f32>,
+    /// Discipline is finished according to the time plan. Without a time plan a discipline counts as
+    /// finished as soon as a result is entered.
+    pub finished: bool,
+    /// Athletes without any result for this discipline, only counted for finished disciplines
+    pub dns: usize,
+    /// Athletes with a result entry but without a valid result
+    pub dnf: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GroupStats {
+    pub group_name: String,
+    pub competition_type: CompetitionType,
+    /// Number of athletes which are not deregistered
+    pub athletes: usize,
+    pub disciplines: Vec<DisciplineStats>,
+}
+
+impl GroupStats {
+    pub fn build(group: &Group, time_group: Option<&TimeGroup>) -> Self {
+        let athletes: Vec<_> = group.athletes().iter().filter(|athlete| !athlete.is_deregistered()).collect();
+        let competition_type = group.competition_type();
+
+        let disciplines = competition_order(&competition_type)
+            .into_iter()
+            .map(|discipline| {
+                let mut values = vec![];
+                let mut points = vec![];
+                let mut best: Option<&Achievement> = None;
+                let (mut dns, mut dnf) = (0, 0);
+
+                for athlete in &athletes {
+                    let achievement = match athlete.get_achievement(discipline) {
+                        Some(achievement) => achievement,
+                        None => {
+                            dns += 1;
+                            continue;
+                        }
+                    };
+                    let value = match achievement.result_value() {
+                        Some(value) => value,
+                        None => {
+                            dnf += 1;
+                            continue;
+                        }
+                    };
+                    values.push(value);
+                    points.push(achievement.points(athlete) as f32);
+                    if best.is_none_or(|best| achievement.is_better_than(best)) {
+                        best = Some(achievement);
+                    }
+                }
+
+                let finished = match time_group {
+                    Some(time_group) => time_group.get_discipline(discipline).is_some_and(|discipline| discipline.is_finished()),
+                    None => dns < athletes.len(),
+                };
+                if !finished {
+                    dns = 0;
+                }
+
+                DisciplineStats {
+                    discipline: discipline.to_string(),
+                    results: values.len(),
+                    mean: mean(&values),
+                    median: median(&mut values),
+                    best: best.and_then(Achievement::result_value).map(round),
+                    mean_points: mean(&points),
+                    finished,
+                    dns,
+                    dnf,
+                }
+            })
+            .collect();
+
+        GroupStats {
+            group_name: group.name().to_string(),
+            competition_type,
+            athletes: athletes.len(),
+            disciplines,
+        }
+    }
+}
+
+fn round(value: f32) -> f32 {
+    (value * 100.).round() / 100.
+}
+
+fn mean(values: &[f32]) -> Option<f32> {
+    if values.is_empty() {
+        return None;
+    }
+    Some(round(values.iter().sum::<f32>() / values.len() as f32))
+}
+
+fn median(values: &mut [f32]) -> Option<f32> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(|value, other| value.total_cmp(other));
+    let middle = values.len() / 2;
+    if values.len() % 2 == 0 {
+        Some(round((values[middle - 1] + values[middle]) / 2.))
+    } else {
+        Some(round(values[middle]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::certificate_generation::{Achievement, Athlete, CompetitionType, Group};
+    use super::GroupStats;
+
+    fn get_athlete(name: &str, run: Option<&str>) -> Athlete {
+        let mut athlete = Athlete::new(name, "Muster", None, "M", HashMap::new(),
+                                       CompetitionType::Decathlon, Some(1), None, None);
+        if let Some(run) = run {
+            let run = Achievement::from_json(format!(r#"{{"Time": {{"name": "100 Meter Lauf",
+                "final_result": {run}, "unit": "s"}}}}"#).as_str()).expect("Achievement should be valid");
+            athlete.add_achievement(run).expect("Achievement should be new");
+        }
+        athlete
+    }
+
+    #[test]
+    fn discipline_stats() {
+        let group = Group::new("Gruppe 1", vec![
+            get_athlete("A", Some("12.50")),
+            get_athlete("B", Some("13.10")),
+            get_athlete("C", Some("11.90")),
+            get_athlete("D", Some("-1.0")),
+            get_athlete("E", None),
+        ], CompetitionType::Decathlon);
+
+        let stats = GroupStats::build(&group, None);
+        assert_eq!(stats.athletes, 5);
+        let run = stats.disciplines.iter().find(|stats| stats.discipline == "100 Meter Lauf").expect("Discipline should exist");
+        assert_eq!(run.results, 3);
+        assert_eq!(run.best, Some(11.9));
+        assert_eq!(run.median, Some(12.5));
+        assert_eq!(run.mean, Some(12.5));
+        assert!(run.mean_points.is_some());
+        assert!(run.finished);
+        assert_eq!((run.dns, run.dnf), (1, 1));
+
+        // Disciplines which were not held yet have no DNS
+        let long_jump = stats.disciplines.iter().find(|stats| stats.discipline == "Weitsprung").expect("Discipline should exist");
+        assert!(!long_jump.finished);
+        assert_eq!((long_jump.results, long_jump.dns), (0, 0));
+        assert_eq!(long_jump.mean, None);
+    }
+}
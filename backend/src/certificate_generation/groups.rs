@@ -1,8 +1,12 @@
 use serde::{ Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::error::Error;
+use serde_json::Value;
 
 use super::{Athlete, AthleteID, CompetitionType};
+use crate::audit::{AuditAction, AuditRecord};
+use crate::time_planner::{self, TimeGroup, TimeGroupID};
+use crate::Storage;
 
 /// Group representing the group and links to the athletes in the group
 /// Mainly used for separate storage of Groups and Athletes
@@ -11,7 +15,13 @@ pub struct GroupStore {
     pub name: String,
     pub athlete_ids: HashSet<AthleteID>,
     pub competition_type: CompetitionType,
-    pub notes: HashMap<String, String>
+    pub notes: HashMap<String, String>,
+    /// Maximum number of registered athletes in the group
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capacity: Option<usize>,
+    /// Incremented with every write, used for conditional writes
+    #[serde(default)]
+    pub version: u64,
 }
 
 impl GroupStore {
@@ -19,6 +29,18 @@ impl GroupStore {
         let group_store: GroupStore = serde_json::from_str(json_string)?;
         Ok(group_store)
     }
+
+    /// Stored form of the group, the version is the one the group was loaded with
+    pub fn from_group(group: &Group, version: u64) -> Self {
+        GroupStore {
+            name: group.name().to_string(),
+            athlete_ids: group.athlete_ids(),
+            competition_type: group.competition_type(),
+            notes: group.notes().clone(),
+            capacity: group.capacity(),
+            version,
+        }
+    }
 }
 
 /// Group representing all athletes that compete in the same group
@@ -29,6 +51,8 @@ pub struct Group {
     athletes: Vec<Athlete>,
     competition_type: CompetitionType,
     notes: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    capacity: Option<usize>,
 }
 
 impl Group {
@@ -38,6 +62,7 @@ impl Group {
             athletes,
             competition_type,
             notes: HashMap::new(),
+            capacity: None,
         }
     }
 
@@ -47,6 +72,7 @@ impl Group {
             athletes: age_group.athletes.clone(),
             competition_type,
             notes: HashMap::new(),
+            capacity: None,
         }
     }
 
@@ -59,6 +85,38 @@ impl Group {
     pub fn name(&self) -> &str {
         self.name.as_str()
     }
+    pub fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+    pub fn set_capacity(&mut self, capacity: Option<usize>) {
+        self.capacity = capacity
+    }
+
+    /// Number of athletes in the group which are not deregistered
+    pub fn registered_athletes(&self) -> usize {
+        self.athletes
+            .iter()
+            .filter(|athlete| !athlete.is_deregistered())
+            .map(AthleteID::from_athlete)
+            .collect::<HashSet<AthleteID>>()
+            .len()
+    }
+
+    pub fn is_full(&self) -> bool {
+        match self.capacity {
+            Some(capacity) => self.registered_athletes() >= capacity,
+            None => false,
+        }
+    }
+
+    pub fn check_capacity(&self) -> Result<(), Box<dyn Error>> {
+        match self.capacity {
+            Some(capacity) if self.registered_athletes() > capacity => {
+                Err(Box::from(format!("Group {} is full (capacity {})", self.name, capacity)))
+            }
+            _ => Ok(()),
+        }
+    }
 
     pub fn set_name(&mut self, name: String) {
         self.name = name
@@ -130,6 +188,104 @@ impl SwitchGroupID {
     }
 }
 
+/// New state of both groups (and their time groups) after moving athletes from one group to another.
+/// The storage has to write all parts together, so an athlete is never in two groups.
+#[derive(Clone, Debug)]
+pub struct GroupSwitch {
+    pub from: GroupStore,
+    pub to: GroupStore,
+    pub from_time_group: Option<TimeGroup>,
+    pub to_time_group: Option<TimeGroup>,
+    /// Athletes which are moved
+    pub athlete_ids: Vec<AthleteID>,
+    pub audit_record: AuditRecord,
+}
+
+impl GroupSwitch {
+    pub fn build(
+        mut from: GroupStore,
+        mut to: GroupStore,
+        mut from_time_group: Option<TimeGroup>,
+        mut to_time_group: Option<TimeGroup>,
+        athletes: &[Athlete],
+    ) -> Result<Self, Box<dyn Error>> {
+        if from.name == to.name {
+            return Err(Box::from("Athletes are already in this group"));
+        }
+        if athletes.is_empty() {
+            return Err(Box::from("No athletes to switch"));
+        }
+
+        for athlete in athletes {
+            let athlete_id = AthleteID::from_athlete(athlete);
+            if !from.athlete_ids.remove(&athlete_id) {
+                return Err(Box::from(format!("Athlete {} not in group {}", athlete.full_name(), from.name)));
+            }
+            to.athlete_ids.insert(athlete_id);
+        }
+
+        // Deregistered athletes are not part of the starting orders
+        let time_athletes: Vec<time_planner::Athlete> = athletes
+            .iter()
+            .filter(|athlete| !athlete.is_deregistered())
+            .map(|athlete| time_planner::Athlete::new(
+                athlete.name().to_string(),
+                athlete.surname().to_string(),
+                Some(athlete.age_group())))
+            .collect();
+        if let Some(time_group) = &mut from_time_group {
+            for athlete in &time_athletes {
+                time_group.delete_athlete(athlete.clone())?;
+            }
+        }
+        if let Some(time_group) = &mut to_time_group {
+            time_group.insert_athletes(&time_athletes);
+        }
+
+        let athlete_ids: Vec<AthleteID> = athletes.iter().map(AthleteID::from_athlete).collect();
+        let audit_record = AuditRecord::new(AuditAction::GroupSwitch {
+            athlete_ids: athlete_ids.clone(),
+            from: from.name.clone(),
+            to: to.name.clone(),
+        });
+
+        Ok(GroupSwitch { from, to, from_time_group, to_time_group, athlete_ids, audit_record })
+    }
+
+    /// Load both groups and their time groups and compute the switch of the athletes given in the json
+    /// string (e.g. {"athlete_ids": [{"name": "Max", "surname": "Mustermann"}]})
+    pub async fn load(
+        storage: &(dyn Storage + Send + Sync),
+        group_info: SwitchGroupID,
+        json_string: &str,
+    ) -> Result<Self, Box<dyn Error>> {
+        let from_group_id = GroupID::new(group_info.from.ok_or("From group not given")?.as_str());
+        let to_group_id = GroupID::new(group_info.to.ok_or("To group not given")?.as_str());
+
+        let json_value: Value = serde_json::from_str(json_string)?;
+        let athlete_ids: Vec<AthleteID> = serde_json::from_value(
+            json_value.get("athlete_ids").cloned().ok_or("Athlete IDs not given")?
+        )?;
+        let mut athletes = vec![];
+        for athlete_id in &athlete_ids {
+            let athlete = storage.get_athlete(athlete_id).await
+                .ok_or(format!("Athlete with ID {:?} not found", athlete_id))?;
+            athletes.push(athlete);
+        }
+
+        let from = storage.get_group_store(&from_group_id).await.ok_or("From group not found")?;
+        let to = storage.get_group_store(&to_group_id).await.ok_or("To group not found")?;
+        let mut to_group = storage.get_group(&to_group_id).await.ok_or("To group not found")?;
+        athletes.iter().for_each(|athlete| to_group.add_athlete(athlete.clone()));
+        to_group.check_capacity()?;
+
+        let from_time_group = storage.get_time_group(&TimeGroupID::new(from.name.clone())).await;
+        let to_time_group = storage.get_time_group(&TimeGroupID::new(to.name.clone())).await;
+
+        GroupSwitch::build(from, to, from_time_group, to_time_group, &athletes)
+    }
+}
+
 /// Age group representing all athletes that compete with each other for the total overall score
 /// Used to generate the final results for the competition
 #[derive(Clone, Debug, PartialEq, Eq, Serialize)]
@@ -184,4 +340,39 @@ impl AgeGroupIDs{
 
         return Ok(ids);
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+    use crate::certificate_generation::{Athlete, AthleteID, CompetitionType};
+    use super::{GroupStore, GroupSwitch};
+
+    fn get_group_store(name: &str, athletes: &[Athlete]) -> GroupStore {
+        GroupStore {
+            name: name.to_string(),
+            athlete_ids: athletes.iter().map(AthleteID::from_athlete).collect(),
+            competition_type: CompetitionType::Decathlon,
+            notes: HashMap::new(),
+            capacity: None,
+            version: 0,
+        }
+    }
+
+    #[test]
+    fn switch_group() {
+        let athlete = Athlete::new("Max", "Mustermann", None, "M", HashMap::new(),
+                                   CompetitionType::Decathlon, Some(1), None, None);
+        let other = Athlete::new("Erika", "Musterfrau", None, "W", HashMap::new(),
+                                 CompetitionType::Decathlon, Some(2), None, None);
+        let from = get_group_store("Gruppe 1", &[athlete.clone(), other.clone()]);
+        let to = get_group_store("Gruppe 2", &[]);
+
+        let switch = GroupSwitch::build(from.clone(), to.clone(), None, None, &[athlete.clone()])
+            .expect("Switch should be valid");
+        assert_eq!(switch.from.athlete_ids, HashSet::from([AthleteID::from_athlete(&other)]));
+        assert_eq!(switch.to.athlete_ids, HashSet::from([AthleteID::from_athlete(&athlete)]));
+
+        assert!(GroupSwitch::build(to, from.clone(), None, None, &[athlete.clone()]).is_err());
+        assert!(GroupSwitch::build(from.clone(), from, None, None, &[athlete]).is_err());
+    }
+}
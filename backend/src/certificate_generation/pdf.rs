@@ -3,6 +3,7 @@ mod group_results;
 mod certificates;
 mod discipline_protocol;
 mod team_results;
+mod official_roster;
 
 use std::collections::HashSet;
 use std::error::Error;
@@ -25,13 +26,15 @@ use crate::certificate_generation::{CompetitionType, Athlete, Group, AgeGroup};
 use crate::certificate_generation::pdf::certificates::{get_certificate, all_group_certificates};
 use crate::certificate_generation::pdf::discipline_protocol::get_discipline_protocol;
 use crate::certificate_generation::pdf::group_results::new_group_result;
+use crate::certificate_generation::pdf::official_roster::get_official_roster;
 use crate::teams::Team;
+use crate::competitions::Competition;
 use crate::time_planner::Discipline;
+use crate::officials::Roster;
+use crate::notes::NoteEntry;
 
 //const FONT_DIR: &'static str = "assets/fonts";
 //const DEFAULT_FONT: &'static str = "times_new_roman";
-const COMPETITION_NUMBER: &'static str = "30";
-const DATE: &'static str = "27. / 28. September 2025";
 
 pub struct PDFMessage {
     body: Vec<u8>,
@@ -69,8 +72,8 @@ pub struct PDF {
 }
 
 impl PDF {
-    pub fn new_certificate(athlete: &Athlete) -> Self {
-        let doc = get_certificate(athlete);
+    pub fn new_certificate(athlete: &Athlete, competition: &Competition) -> Self {
+        let doc = get_certificate(athlete, competition);
 
         PDF { content: doc }
     }
@@ -81,27 +84,32 @@ impl PDF {
         PDF { content: doc }
     }
 
-    pub fn new_group_result(group: &Group, disciplines: Option<Vec<String>>) -> Self {
-        let doc = new_group_result(group, disciplines);
+    pub fn new_group_result(group: &Group, disciplines: Option<Vec<String>>, competition: &Competition) -> Self {
+        let doc = new_group_result(group, disciplines, competition);
         PDF { content: doc }
     }
 
-    pub fn new_team_result(teams: &Vec<Team>) -> Self {
-        let doc = new_team_result(teams);
+    pub fn new_team_result(teams: &Vec<Team>, competition: &Competition) -> Self {
+        let doc = new_team_result(teams, competition);
         PDF { content: doc }
     } 
 
-    pub fn new_group_certificates(group: &Group) -> Self {
-        let doc = all_group_certificates(group);
+    pub fn new_group_certificates(group: &Group, competition: &Competition) -> Self {
+        let doc = all_group_certificates(group, competition);
         PDF { content: doc }
     }
 
-    pub fn new_discipline_protocol(group: &Group, discipline: &Discipline) -> Self {
-        let doc = get_discipline_protocol(group, discipline);
+    pub fn new_discipline_protocol(group: &Group, discipline: &Discipline, competition: &Competition, notes: &[NoteEntry]) -> Self {
+        let doc = get_discipline_protocol(group, discipline, competition, notes);
         PDF { content: doc }
     }
 
-    pub fn build_from_age_group_result(age_group: &AgeGroup) -> Result<Self, Box<dyn Error>> {
+    pub fn new_official_roster(roster: &Roster, competition: &Competition) -> Self {
+        let doc = get_official_roster(roster, competition);
+        PDF { content: doc }
+    }
+
+    pub fn build_from_age_group_result(age_group: &AgeGroup, competition: &Competition) -> Result<Self, Box<dyn Error>> {
         let mut athlete_competition_types: HashSet<&CompetitionType> = HashSet::new();
         let mut competition_type = CompetitionType::Decathlon; // decathlon as default
         for athlete in age_group.athletes() {
@@ -112,7 +120,7 @@ impl PDF {
             return Err(Box::from("Not all athletes in same competition"));
         }
         let group = Group::from_age_group(age_group, competition_type);
-        let doc = new_group_result(&group, None);
+        let doc = new_group_result(&group, None, competition);
         Ok(PDF { content: doc })
     }
 
@@ -324,8 +332,18 @@ mod tests {
     use crate::certificate_generation::{Achievement, Athlete, CompetitionType, Group};
     use crate::certificate_generation::achievements::{DistanceResult, TimeResult};
     use crate::teams::Team;
+    use crate::competitions::Competition;
+    use crate::officials::{OfficialRole, Roster, RosterEntry, RosterOfficial, StaffingWarning};
+    use crate::notes::{NoteEntry, NoteID, NoteInput};
+    use crate::time_planner::{build_time_groups, DEFAULT_TIME_ZONE};
     use super::PDF;
 
+    fn get_competition() -> Competition {
+        Competition::from_json(r#"{"number": "30", "name": "Favoritner Jedermann Zehnkampf",
+            "start_date": 1758931200, "end_date": 1759017600}"#)
+            .expect("Competition JSON should be valid")
+    }
+
     fn get_athlete() -> Athlete {
         let birthday = NaiveDateTime::parse_from_str("1997.03.22 0:0:0", "%Y.%m.%d %H:%M:%S").unwrap();
         let mut achievements = HashMap::new();
@@ -369,14 +387,14 @@ mod tests {
         athletes.push(get_athlete());
         athletes.push(get_athlete());
         let group = Group::new("Gruppe 1", athletes, CompetitionType::Decathlon);
-        let pdf = PDF::new_group_result(&group, None);
+        let pdf = PDF::new_group_result(&group, None, &get_competition());
         let pdf_write_result = pdf._write_pdf("tests/output/write_group_result.pdf");
         match pdf_write_result {
             Ok(_) => {}
             Err(err) => panic!("Error while writing PDF: {err}"),
         }
 
-        let pdf = PDF::new_group_result(&group, None);
+        let pdf = PDF::new_group_result(&group, None, &get_competition());
         match pdf.to_http_message() {
             Ok(_) => {}
             Err(err) => panic!("Error while writing PDF to bytes: {err}"),
@@ -397,14 +415,14 @@ mod tests {
             Some(123),
             None
         );
-        let pdf = PDF::new_certificate(&athlete);
+        let pdf = PDF::new_certificate(&athlete, &get_competition());
         let pdf_write_result = pdf._write_pdf("tests/output/write_decathlon_certificate.pdf");
         match pdf_write_result {
             Ok(_) => {}
             Err(err) => panic!("Error while writing PDF: {err}"),
         }
 
-        let pdf = PDF::new_certificate(&athlete);
+        let pdf = PDF::new_certificate(&athlete, &get_competition());
         match pdf.to_http_message() {
             Ok(_) => {}
             Err(err) => panic!("Error while writing PDF to bytes: {err}"),
@@ -425,14 +443,14 @@ mod tests {
             None,
             None
         );
-        let pdf = PDF::new_certificate(&athlete);
+        let pdf = PDF::new_certificate(&athlete, &get_competition());
         let pdf_write_result = pdf._write_pdf("tests/output/write_triathlon_certificate.pdf");
         match pdf_write_result {
             Ok(_) => {}
             Err(err) => panic!("Error while writing PDF: {err}"),
         }
 
-        let pdf = PDF::new_certificate(&athlete);
+        let pdf = PDF::new_certificate(&athlete, &get_competition());
         match pdf.to_http_message() {
             Ok(_) => {}
             Err(err) => panic!("Error while writing PDF to bytes: {err}"),
@@ -453,14 +471,14 @@ mod tests {
             None,
             None
         );
-        let pdf = PDF::new_certificate(&athlete);
+        let pdf = PDF::new_certificate(&athlete, &get_competition());
         let pdf_write_result = pdf._write_pdf("tests/output/write_pentathlon_certificate.pdf");
         match pdf_write_result {
             Ok(_) => {}
             Err(err) => panic!("Error while writing PDF: {err}"),
         }
 
-        let pdf = PDF::new_certificate(&athlete);
+        let pdf = PDF::new_certificate(&athlete, &get_competition());
         match pdf.to_http_message() {
             Ok(_) => {}
             Err(err) => panic!("Error while writing PDF to bytes: {err}"),
@@ -481,17 +499,66 @@ mod tests {
         let team3 = Team::new("Team 2 ist hier und viel zu lange ist der Name ist einfach nur viel viel viel zu lange".to_string(), false, athletes.clone());
 
         let teams = vec![team1, team2, team3, team4];
-        let pdf = PDF::new_team_result(&teams);
+        let pdf = PDF::new_team_result(&teams, &get_competition());
         let pdf_write_result = pdf._write_pdf("tests/output/write_team_result.pdf");
         match pdf_write_result {
             Ok(_) => {}
             Err(err) => panic!("Error while writing PDF: {err}"),
         }
 
-        let pdf = PDF::new_team_result(&teams);
+        let pdf = PDF::new_team_result(&teams, &get_competition());
         match pdf.to_http_message() {
             Ok(_) => {}
             Err(err) => panic!("Error while writing PDF to bytes: {err}"),
         };
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn write_official_roster() {
+        let entry = RosterEntry {
+            group: "Gruppe 1".to_string(),
+            discipline: "Weitsprung".to_string(),
+            location: "Weit 1".to_string(),
+            start_time: Utc.with_ymd_and_hms(2025, 9, 27, 7, 0, 0).unwrap(),
+            end_time: Utc.with_ymd_and_hms(2025, 9, 27, 8, 0, 0).unwrap(),
+            officials: vec![RosterOfficial { name: "Anna Muster".to_string(), role: OfficialRole::Judge, contact: None }],
+        };
+        let warning = StaffingWarning {
+            group: "Gruppe 2".to_string(),
+            discipline: "Kugelstoß".to_string(),
+            location: "Kugel".to_string(),
+            message: "No judge assigned".to_string(),
+        };
+        let roster = Roster { entries: vec![entry; 60], warnings: vec![warning] };
+        let pdf = PDF::new_official_roster(&roster, &get_competition());
+        let pdf_write_result = pdf._write_pdf("tests/output/write_official_roster.pdf");
+        match pdf_write_result {
+            Ok(_) => {}
+            Err(err) => panic!("Error while writing PDF: {err}"),
+        }
+    }
+
+    #[test]
+    fn write_discipline_protocol_with_notes() {
+        let time_table = serde_json::json!({
+            "Dates": {"Samstag": "27.09.2025"},
+            "DisciplineTypes": {"Weitsprung": "Default"},
+            "Groups": {"Gruppe 1": {"Weitsprung": {"location": "Weit 1", "time": "09:00, Samstag"}}},
+        });
+        let time_groups = build_time_groups(&time_table, DEFAULT_TIME_ZONE).expect("Time groups should be buildable");
+        let discipline = time_groups[0].get_discipline("Weitsprung").expect("Discipline should exist");
+        let group = Group::new("Gruppe 1", vec![get_athlete()], CompetitionType::Decathlon);
+
+        let note_id = NoteID { discipline: "Weitsprung".to_string(), group_name: "Gruppe 1".to_string() };
+        let input = NoteInput::from_json(r#"{"text": "Protest gegen den zweiten Versuch, Brett wurde nicht richtig abgelesen", "category": "Protest",
+            "athlete_id": {"name": "Fabian", "surname": "Traxler"}}"#).expect("Note should be valid");
+        let notes: Vec<NoteEntry> = (0..40).map(|_| NoteEntry::new(&note_id, input.clone(), Some("Group".to_string()))).collect();
+
+        let pdf = PDF::new_discipline_protocol(&group, discipline, &get_competition(), &notes);
+        let pdf_write_result = pdf._write_pdf("tests/output/write_discipline_protocol_with_notes.pdf");
+        match pdf_write_result {
+            Ok(_) => {}
+            Err(err) => panic!("Error while writing PDF: {err}"),
+        }
+    }
+}
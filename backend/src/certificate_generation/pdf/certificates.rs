@@ -2,9 +2,7 @@ use std::fs::File;
 use printpdf::{IndirectFontRef, Mm, PdfDocumentReference, PdfLayerIndex, PdfLayerReference, PdfPageIndex, TextRenderingMode};
 use crate::certificate_generation::{competition_order, Athlete, CompetitionType, Group};
 use crate::certificate_generation::pdf::pdf_generation::{add_logo, add_pdf_page, setup_pdf};
-use crate::certificate_generation::pdf::{COMPETITION_NUMBER, DATE};
-
-const AGE_GROUPS_WO_POINTS:[&'static str; 6] = ["M-U4", "W-U4", "M-U6", "W-U6", "W-U8", "M-U8"];
+use crate::competitions::Competition;
 
 fn add_name(pdf_layer: &PdfLayerReference, font: &IndirectFontRef, athlete: &Athlete) {
     // Define font metrics
@@ -74,7 +72,7 @@ fn add_achievements(pdf_layer: &PdfLayerReference, font: &IndirectFontRef, athle
                 let (final_result, unit) = achievement.fmt_final_result();
 
 
-                if !AGE_GROUPS_WO_POINTS.contains(&athlete.age_group().as_str()){ // only print for older athletes
+                if athlete.show_points() { // only print for older athletes
                     if final_result == "".to_string() {
                         pdf_layer.use_text("DNF", font_size, Mm(achievement_align), Mm(current_height), font);
                         pdf_layer.use_text("0", font_size, Mm(point_align), Mm(current_height), font);
@@ -82,10 +80,16 @@ fn add_achievements(pdf_layer: &PdfLayerReference, font: &IndirectFontRef, athle
                         pdf_layer.use_text(achievement.points(athlete).to_string(), font_size, Mm(point_align), Mm(current_height), font);
                         pdf_layer.use_text(format!("{}", final_result), font_size, Mm(achievement_align), Mm(current_height), font);
                         pdf_layer.use_text(format!("{}", unit ), font_size, Mm(achievement_align + 20.), Mm(current_height), font);
+                        if athlete.is_personal_best(discipline_name) {
+                            pdf_layer.use_text("PB", font_size, Mm(achievement_align + 35.), Mm(current_height), font);
+                        }
                     }
                 } else {
                     pdf_layer.use_text(format!("{}", final_result), font_size, Mm(point_align), Mm(current_height), font);
                     pdf_layer.use_text(format!("{}", unit ), font_size, Mm(point_align + 15.), Mm(current_height), font);
+                    if athlete.is_personal_best(discipline_name) {
+                        pdf_layer.use_text("PB", font_size, Mm(point_align + 25.), Mm(current_height), font);
+                    }
                 }
             }
             None => {
@@ -98,7 +102,7 @@ fn add_achievements(pdf_layer: &PdfLayerReference, font: &IndirectFontRef, athle
     pdf_layer.end_text_section();
 }
 
-pub fn all_group_certificates(group: &Group) -> PdfDocumentReference {
+pub fn all_group_certificates(group: &Group, competition: &Competition) -> PdfDocumentReference {
     let (mut pdf, mut page, mut layer) = setup_pdf(format!("Ergebnisse {}", group.name()).as_str(),
                                        false);
 
@@ -110,11 +114,11 @@ pub fn all_group_certificates(group: &Group) -> PdfDocumentReference {
     }
 
     for athlete in &athletes{
-        pdf = match athlete.competition_type() {
-            CompetitionType::Decathlon => new_decathlon_certificate(athlete, pdf, page, layer),
-            CompetitionType::Heptathlon => new_heptathlon_certificate(athlete, pdf, page, layer),
-            CompetitionType::Triathlon => new_triathlon_certificate(athlete, pdf, page, layer),
-            CompetitionType::Pentathlon => new_pentathlon_certificate(athlete, pdf, page, layer)
+        pdf = match athlete.certificate_layout() {
+            CompetitionType::Decathlon => new_decathlon_certificate(athlete, competition, pdf, page, layer),
+            CompetitionType::Heptathlon => new_heptathlon_certificate(athlete, competition, pdf, page, layer),
+            CompetitionType::Triathlon => new_triathlon_certificate(athlete, competition, pdf, page, layer),
+            CompetitionType::Pentathlon => new_pentathlon_certificate(athlete, competition, pdf, page, layer)
         };
         (page, layer) = add_pdf_page(&pdf, "", false);
     };
@@ -122,20 +126,20 @@ pub fn all_group_certificates(group: &Group) -> PdfDocumentReference {
     pdf
 }
 
-pub fn get_certificate(athlete: &Athlete) -> PdfDocumentReference {
+pub fn get_certificate(athlete: &Athlete, competition: &Competition) -> PdfDocumentReference {
     let (mut pdf, page, layer) = setup_pdf(format!("Urkunde {}", athlete.full_name()).as_str(),
                                        false);
-    pdf = match athlete.competition_type() {
-        CompetitionType::Decathlon => new_decathlon_certificate(athlete, pdf, page, layer),
-        CompetitionType::Heptathlon => new_heptathlon_certificate(athlete, pdf, page, layer),
-        CompetitionType::Triathlon => new_triathlon_certificate(athlete, pdf, page, layer),
-        CompetitionType::Pentathlon => new_pentathlon_certificate(athlete, pdf, page, layer)
+    pdf = match athlete.certificate_layout() {
+        CompetitionType::Decathlon => new_decathlon_certificate(athlete, competition, pdf, page, layer),
+        CompetitionType::Heptathlon => new_heptathlon_certificate(athlete, competition, pdf, page, layer),
+        CompetitionType::Triathlon => new_triathlon_certificate(athlete, competition, pdf, page, layer),
+        CompetitionType::Pentathlon => new_pentathlon_certificate(athlete, competition, pdf, page, layer)
     };
 
     pdf
 }
 
-fn new_triathlon_certificate(athlete: &Athlete, pdf: PdfDocumentReference, page: PdfPageIndex,
+fn new_triathlon_certificate(athlete: &Athlete, competition: &Competition, pdf: PdfDocumentReference, page: PdfPageIndex,
                                  layer: PdfLayerIndex) -> PdfDocumentReference {
     let current_layer = pdf.get_page(page).get_layer(layer);
 
@@ -158,13 +162,13 @@ fn new_triathlon_certificate(athlete: &Athlete, pdf: PdfDocumentReference, page:
     // Write JZK Heading
     current_layer.set_text_rendering_mode(TextRenderingMode::Fill);
     let font_size = 36.0;
-    current_layer.use_text(format!("{COMPETITION_NUMBER}. Favoritner"), font_size, Mm(60.0), Mm(230.0), &font_bold);
+    current_layer.use_text(format!("{}. Favoritner", competition.number), font_size, Mm(60.0), Mm(230.0), &font_bold);
     current_layer.use_text("Jedermann Zehnkampf", font_size, Mm(40.0), Mm(215.0), &font_bold);
-    current_layer.use_text(format!("{DATE}"), 20.0, Mm(65.0), Mm(205.0), &font);
+    current_layer.use_text(competition.date_description(), 20.0, Mm(65.0), Mm(205.0), &font);
 
     add_logo(current_layer.clone(), false);
     add_name(&current_layer, &font_bold, &athlete);
-    if !AGE_GROUPS_WO_POINTS.contains(&athlete.age_group().as_str()) { // only print for older athletes
+    if athlete.show_points() { // only print for older athletes
         add_total_points(&current_layer, &font_bold, &athlete);
     } else {
         add_successfully_completion(&current_layer, &font)
@@ -180,7 +184,7 @@ fn new_triathlon_certificate(athlete: &Athlete, pdf: PdfDocumentReference, page:
     pdf
 }
 
-fn new_pentathlon_certificate(athlete: &Athlete, pdf: PdfDocumentReference, page: PdfPageIndex,
+fn new_pentathlon_certificate(athlete: &Athlete, competition: &Competition, pdf: PdfDocumentReference, page: PdfPageIndex,
                                   layer: PdfLayerIndex) -> PdfDocumentReference {
 
     let current_layer = pdf.get_page(page).get_layer(layer);
@@ -204,9 +208,9 @@ fn new_pentathlon_certificate(athlete: &Athlete, pdf: PdfDocumentReference, page
     // Write JZK Heading
     current_layer.set_text_rendering_mode(TextRenderingMode::Fill);
     let font_size = 36.0;
-    current_layer.use_text(format!("{COMPETITION_NUMBER}. Favoritner"), font_size, Mm(60.0), Mm(230.0), &font_bold);
+    current_layer.use_text(format!("{}. Favoritner", competition.number), font_size, Mm(60.0), Mm(230.0), &font_bold);
     current_layer.use_text("Jedermann Zehnkampf", font_size, Mm(40.0), Mm(215.0), &font_bold);
-    current_layer.use_text(format!("{DATE}"), 20.0, Mm(65.0), Mm(205.0), &font);
+    current_layer.use_text(competition.date_description(), 20.0, Mm(65.0), Mm(205.0), &font);
 
     add_logo(current_layer.clone(), false);
     add_name(&current_layer, &font_bold, &athlete);
@@ -224,7 +228,7 @@ fn new_pentathlon_certificate(athlete: &Athlete, pdf: PdfDocumentReference, page
     pdf
 }
 
-fn new_heptathlon_certificate(athlete: &Athlete, pdf: PdfDocumentReference, page: PdfPageIndex,
+fn new_heptathlon_certificate(athlete: &Athlete, competition: &Competition, pdf: PdfDocumentReference, page: PdfPageIndex,
                                   layer: PdfLayerIndex) -> PdfDocumentReference {
     let current_layer = pdf.get_page(page).get_layer(layer);
 
@@ -247,9 +251,9 @@ fn new_heptathlon_certificate(athlete: &Athlete, pdf: PdfDocumentReference, page
     // Write JZK Heading
     current_layer.set_text_rendering_mode(TextRenderingMode::Fill);
     let font_size = 36.0;
-    current_layer.use_text(format!("{COMPETITION_NUMBER}. Favoritner"), font_size, Mm(60.0), Mm(230.0), &font_bold);
+    current_layer.use_text(format!("{}. Favoritner", competition.number), font_size, Mm(60.0), Mm(230.0), &font_bold);
     current_layer.use_text("Jedermann Zehnkampf", font_size, Mm(40.0), Mm(215.0), &font_bold);
-    current_layer.use_text(format!("{DATE}"), 20.0, Mm(65.0), Mm(205.0), &font);
+    current_layer.use_text(competition.date_description(), 20.0, Mm(65.0), Mm(205.0), &font);
 
     add_logo(current_layer.clone(), false);
     add_name(&current_layer, &font_bold, &athlete);
@@ -267,7 +271,7 @@ fn new_heptathlon_certificate(athlete: &Athlete, pdf: PdfDocumentReference, page
     pdf
 }
 
-fn new_decathlon_certificate(athlete: &Athlete, pdf: PdfDocumentReference, page: PdfPageIndex,
+fn new_decathlon_certificate(athlete: &Athlete, competition: &Competition, pdf: PdfDocumentReference, page: PdfPageIndex,
                                  layer: PdfLayerIndex) -> PdfDocumentReference {
 
     let current_layer = pdf.get_page(page).get_layer(layer);
@@ -291,9 +295,9 @@ fn new_decathlon_certificate(athlete: &Athlete, pdf: PdfDocumentReference, page:
     // Write JZK Heading
     current_layer.set_text_rendering_mode(TextRenderingMode::Fill);
     let font_size = 36.0;
-    current_layer.use_text(format!("{COMPETITION_NUMBER}. Favoritner"), font_size, Mm(60.0), Mm(230.0), &font_bold);
+    current_layer.use_text(format!("{}. Favoritner", competition.number), font_size, Mm(60.0), Mm(230.0), &font_bold);
     current_layer.use_text("Jedermann Zehnkampf", font_size, Mm(40.0), Mm(215.0), &font_bold);
-    current_layer.use_text(format!("{DATE}"), 20.0, Mm(65.0), Mm(205.0), &font);
+    current_layer.use_text(competition.date_description(), 20.0, Mm(65.0), Mm(205.0), &font);
 
     add_logo(current_layer.clone(), false);
     add_name(&current_layer, &font_bold, &athlete);
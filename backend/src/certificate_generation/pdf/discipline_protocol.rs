@@ -2,11 +2,13 @@ use std::collections::HashMap;
 use printpdf::{IndirectFontRef, Line, Mm, PdfDocumentReference, PdfLayerReference, Point, TextRenderingMode};
 use printpdf::BuiltinFont::{Helvetica, HelveticaBold};
 use crate::certificate_generation::{Achievement, Athlete, CompetitionType, Float, Group};
-use crate::certificate_generation::pdf::{DATE, COMPETITION_NUMBER};
+use crate::competitions::Competition;
+use crate::notes::NoteEntry;
 use crate::certificate_generation::pdf::pdf_generation::{add_pdf_page, LEFT_PAGE_EDGE, setup_pdf};
-use crate::time_planner::{Discipline, DisciplineType, Run, StartingOrder};
+use crate::time_planner::{Discipline, DisciplineType, Run, StartingOrder, DEFAULT_LANES};
 
-pub fn get_discipline_protocol(group: &Group, discipline: &Discipline) -> PdfDocumentReference {
+pub fn get_discipline_protocol(group: &Group, discipline: &Discipline, competition: &Competition,
+                               notes: &[NoteEntry]) -> PdfDocumentReference {
     let landscape = match discipline.discipline_type() {
         DisciplineType::Height => true,
         _ => false
@@ -20,7 +22,7 @@ pub fn get_discipline_protocol(group: &Group, discipline: &Discipline) -> PdfDoc
     let font_bold = pdf.add_builtin_font(HelveticaBold)
         .expect("Builtin Font should be available");
 
-    add_header(&current_layer, &font_bold, group, discipline, landscape);
+    add_header(&current_layer, &font_bold, group, discipline, competition, landscape);
 
 
     match discipline.discipline_type() {
@@ -31,18 +33,74 @@ pub fn get_discipline_protocol(group: &Group, discipline: &Discipline) -> PdfDoc
             add_time_discipline(&current_layer, &font, &font_bold, group, discipline);
         }
         DisciplineType::Height => {
-            add_height_discipline(&pdf, current_layer, &font, &font_bold, group, discipline);
+            add_height_discipline(&pdf, current_layer, &font, &font_bold, group, discipline, competition);
         }
         DisciplineType::Distance => {
             add_distance_discipline(&current_layer, &font, &font_bold, group, discipline);
         }
     };
 
+    if !notes.is_empty() {
+        add_notes(&pdf, &font, &font_bold, notes, competition, landscape);
+    }
+
     pdf
 }
 
+/// Notes of the discipline on a separate page, edited notes show their current version
+fn add_notes(pdf: &PdfDocumentReference, font: &IndirectFontRef, font_bold: &IndirectFontRef,
+             notes: &[NoteEntry], competition: &Competition, landscape: bool) {
+    let max_note_char_len = if landscape { 130 } else { 90 };
+    let font_size = 10.0;
+    let line_height = font_size * 0.6;
+    let initial_y_coord = if landscape { 190. } else { 280. };
+    let min_y_coord = 20.;
+
+    let (page, layer) = add_pdf_page(pdf, "Notizen", landscape);
+    let mut current_layer = pdf.get_page(page).get_layer(layer);
+    current_layer.use_text("Notizen", 12., Mm(LEFT_PAGE_EDGE), Mm(initial_y_coord), font_bold);
+    let mut y_coord = initial_y_coord - 2. * line_height;
+
+    for note in notes {
+        let mut heading = match note.legacy {
+            true => String::from(note.category.description()),
+            false => format!("{} Uhr - {}", note.timestamp.with_timezone(&competition.config.time_zone).format("%H:%M"),
+                             note.category.description()),
+        };
+        if let Some(athlete_id) = &note.athlete_id {
+            heading = format!("{} - {}", heading, athlete_id.full_name());
+        }
+        if let Some(role) = &note.role {
+            heading = format!("{} ({})", heading, role);
+        }
+        if !note.history.is_empty() {
+            heading = format!("{} [bearbeitet]", heading);
+        }
+
+        let mut lines = vec![];
+        for paragraph in note.text.lines() {
+            let characters: Vec<char> = paragraph.chars().collect();
+            lines.extend(characters.chunks(max_note_char_len).map(|line| line.iter().collect::<String>()));
+        }
+        let num_lines = (lines.len() + 1) as f32;
+        if y_coord - line_height * num_lines < min_y_coord {
+            let (page, layer) = add_pdf_page(pdf, "Notizen", landscape);
+            current_layer = pdf.get_page(page).get_layer(layer);
+            y_coord = initial_y_coord;
+        }
+
+        current_layer.use_text(heading, font_size, Mm(LEFT_PAGE_EDGE), Mm(y_coord), font_bold);
+        y_coord -= line_height;
+        for line in lines {
+            current_layer.use_text(line, font_size, Mm(LEFT_PAGE_EDGE), Mm(y_coord), font);
+            y_coord -= line_height;
+        }
+        y_coord -= line_height / 2.;
+    }
+}
+
 fn add_header(current_layer: &PdfLayerReference, font: &IndirectFontRef, group: &Group,
-              discipline: &Discipline, landscape: bool) {
+              discipline: &Discipline, competition: &Competition, landscape: bool) {
 
     // Write JZK Heading
     current_layer.set_text_rendering_mode(TextRenderingMode::Fill);
@@ -57,13 +115,17 @@ fn add_header(current_layer: &PdfLayerReference, font: &IndirectFontRef, group:
         x = LEFT_PAGE_EDGE;
         y = 280.;
     }
-    current_layer.use_text(format!("{COMPETITION_NUMBER}. Favoritner Jedermann Zehnkampf - {DATE}"),
+    current_layer.use_text(format!("{} - {}", competition.title(), competition.date_description()),
                            font_size, Mm(x), Mm(y), &font);
 
     let font_size = 10.0;
     y = y - 10.;
-    current_layer.use_text(format!("{} - {}", group.name(), discipline.name()),
-                           font_size, Mm(x), Mm(y), &font);
+    let start_time = discipline.local_start_time(competition.config.time_zone).format("%d.%m.%Y %H:%M");
+    let heading = match discipline.starting_order() {
+        StartingOrder::NoOrder => format!("{} - {} - {} Uhr", group.name(), discipline.name(), start_time),
+        _ => format!("{} - {} ({}) - {} Uhr", group.name(), discipline.name(), discipline.order_description(), start_time),
+    };
+    current_layer.use_text(heading, font_size, Mm(x), Mm(y), &font);
 }
 
 fn add_athlete_info<'a>(current_layer: &PdfLayerReference, col_widths: &HashMap<&str, f32>,
@@ -340,8 +402,12 @@ fn add_track_discipline(pdf: &PdfDocumentReference, mut current_layer: PdfLayerR
     current_layer.use_text("Zeit", font_size, Mm(x_coord), Mm(y_coord), font);
     y_coord -= line_height;
 
+    let lanes = runs.iter().map(|run| run.athletes().len()).max().unwrap_or(DEFAULT_LANES);
+    // A page holds six runs of six lanes, each run needs two additional lines for its heading
+    let runs_per_page = ((6. * 8.2) / (lanes as f32 + 2.2)).floor().max(1.) as usize;
+
     while runs.len() > 0 {
-        for run_id in 0..6{
+        for run_id in 0..runs_per_page {
             let track_run;
             if run_id < runs.len() {
                 track_run = Some(&runs[run_id]);
@@ -354,12 +420,10 @@ fn add_track_discipline(pdf: &PdfDocumentReference, mut current_layer: PdfLayerR
             y_coord -= line_height;
             let initial_y_coord = y_coord - 1.;
 
-            for i in 0..6{
+            for i in 0..lanes {
                 let run_athletes = match track_run {
                     Some(track_run) => {
-                        let athletes_tuple = track_run.athletes().clone();
-                        let athletes = vec![athletes_tuple.0, athletes_tuple.1, athletes_tuple.2, athletes_tuple.3, athletes_tuple.4, athletes_tuple.5];
-                        let run_names: Vec<String> = athletes.iter().map(|athlete| {
+                        let run_names: Vec<String> = track_run.athletes().iter().map(|athlete| {
                             if let Some(athlete) = athlete{
                                 athlete.full_name()
                             }else{
@@ -424,8 +488,8 @@ fn add_track_discipline(pdf: &PdfDocumentReference, mut current_layer: PdfLayerR
             }
 
         }
-        if runs.len() >= 6 {
-            runs.drain(0..6);
+        if runs.len() >= runs_per_page {
+            runs.drain(0..runs_per_page);
             let (new_page_index, new_layer_index) = add_pdf_page(pdf, "", false);
             current_layer = pdf.get_page(new_page_index).get_layer(new_layer_index);
             y_coord = initial_y_coord;
@@ -448,7 +512,7 @@ fn add_track_discipline(pdf: &PdfDocumentReference, mut current_layer: PdfLayerR
 }
 
 fn add_height_discipline(pdf: &PdfDocumentReference, current_layer: PdfLayerReference, font: &IndirectFontRef, font_bold: &IndirectFontRef, group: &Group,
-                        discipline: &Discipline) {
+                        discipline: &Discipline, competition: &Competition) {
     let font_size = 10.0;
     let line_height = font_size * 0.5;
     let initial_y_coord = 175.;
@@ -475,7 +539,7 @@ fn add_height_discipline(pdf: &PdfDocumentReference, current_layer: PdfLayerRefe
     // Add second page
     let (page_idx, layer_idx) = add_pdf_page(pdf, "Page 2", true);
     let current_layer = pdf.get_page(page_idx).get_layer(layer_idx);
-    add_header(&current_layer, &font_bold, group, discipline, true);
+    add_header(&current_layer, &font_bold, group, discipline, competition, true);
     start_height = start_height + 10 * height_increase;
     add_height_page(current_layer, &col_widths, font_size, line_height, font, font_bold, group,
                     discipline, initial_y_coord, columns.clone(), start_height, height_increase)
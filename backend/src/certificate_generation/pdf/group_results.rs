@@ -1,6 +1,6 @@
 use crate::certificate_generation::pdf::pdf_generation::{add_logo, setup_pdf, LEFT_PAGE_EDGE};
-use crate::certificate_generation::pdf::{COMPETITION_NUMBER, DATE};
 use crate::certificate_generation::{athletes, competition_order, CompetitionType, Group};
+use crate::competitions::Competition;
 use chrono::Datelike;
 use printpdf::rectangle::Rect;
 use printpdf::BuiltinFont::HelveticaBold;
@@ -17,11 +17,11 @@ use super::pdf_generation::add_pdf_page;
 const DEFAULT_DISCIPLINE_WIDTH: f32 = 11.5;
 const POINTS_WIDTH: f32 = 8.;
 
-pub fn new_group_result(group: &Group, disciplines: Option<Vec<String>>) -> PdfDocumentReference {
+pub fn new_group_result(group: &Group, disciplines: Option<Vec<String>>, competition: &Competition) -> PdfDocumentReference {
     if group.competition_type() == CompetitionType::Decathlon {
         // One result for full group
         let (pdf, page, layer) = setup_pdf(format!("Ergebnis {}", group.name()).as_str(), true);
-        add_group_result_to_page(group, pdf, page, layer, disciplines)
+        add_group_result_to_page(group, pdf, page, layer, disciplines, competition)
     } else {
         // Separated result for M and W
         let w_group = Group::new(
@@ -36,7 +36,7 @@ pub fn new_group_result(group: &Group, disciplines: Option<Vec<String>>) -> PdfD
         );
         let (mut pdf, mut page, mut layer) =
             setup_pdf(format!("Ergebnis {}", w_group.name()).as_str(), true);
-        pdf = add_group_result_to_page(&w_group, pdf, page, layer, disciplines.clone());
+        pdf = add_group_result_to_page(&w_group, pdf, page, layer, disciplines.clone(), competition);
 
         let m_group = Group::new(
             format!("{} - {}", group.name(), "M").as_str(),
@@ -49,7 +49,7 @@ pub fn new_group_result(group: &Group, disciplines: Option<Vec<String>>) -> PdfD
             group.competition_type(),
         );
         (page, layer) = add_pdf_page(&pdf, format!("Ergebnis {}", m_group.name()).as_str(), true);
-        add_group_result_to_page(&m_group, pdf, page, layer, disciplines)
+        add_group_result_to_page(&m_group, pdf, page, layer, disciplines, competition)
     }
 }
 
@@ -58,7 +58,8 @@ pub fn add_group_result_to_page(
     pdf: PdfDocumentReference,
     page: PdfPageIndex,
     layer: PdfLayerIndex,
-    disciplines: Option<Vec<String>>
+    disciplines: Option<Vec<String>>,
+    competition: &Competition,
 ) -> PdfDocumentReference {
     let comic_font = pdf
         .add_external_font(
@@ -104,27 +105,35 @@ pub fn add_group_result_to_page(
     alignments.insert("Hepathlon: Speerwurf", 76.);
     alignments.insert("Hepathlon: 1000 Meter Lauf", 76.);
 
-    let current_layer = setup_new_page(&pdf, page, layer, group, &alignments);
+    let current_layer = setup_new_page(&pdf, page, layer, group, &alignments, competition);
 
     // Add athletes
+    let context = ResultContext { pdf: &pdf, group, competition, alignments: &alignments };
     add_group_athletes(
         current_layer,
         &comic_font,
         &comic_font_bold,
-        &alignments,
-        group,
-        &pdf,
-        disciplines
+        &context,
+        disciplines,
     );
     pdf
 }
 
+/// Document, group and layout of a group result, shared by all pages
+struct ResultContext<'a> {
+    pdf: &'a PdfDocumentReference,
+    group: &'a Group,
+    competition: &'a Competition,
+    alignments: &'a HashMap<&'a str, f32>,
+}
+
 fn setup_new_page(
     pdf: &PdfDocumentReference,
     page: PdfPageIndex,
     layer: PdfLayerIndex,
     group: &Group,
     alignments: &HashMap<&str, f32>,
+    competition: &Competition,
 ) -> PdfLayerReference {
     let current_layer = pdf.get_page(page).get_layer(layer);
 
@@ -143,7 +152,7 @@ fn setup_new_page(
     current_layer.begin_text_section();
     let font_size = 18.0;
     current_layer.use_text(
-        format!("{COMPETITION_NUMBER}. Favoritner Jedermann - Zehnkampf {DATE}"),
+        format!("{} {}", competition.title(), competition.date_description()),
         font_size,
         Mm(65.0),
         Mm(195.0),
@@ -295,11 +304,10 @@ fn add_group_athletes(
     mut pdf_layer: PdfLayerReference,
     font: &IndirectFontRef,
     font_bold: &IndirectFontRef,
-    alignments: &HashMap<&str, f32>,
-    group: &Group,
-    pdf: &PdfDocumentReference,
-    included_disciplines: Option<Vec<String>>
+    context: &ResultContext,
+    included_disciplines: Option<Vec<String>>,
 ) {
+    let ResultContext { pdf, group, competition, alignments } = *context;
     let font_size = 10.0;
     let line_height = font_size * 0.5;
     let initial_y_coord = 170.;
@@ -446,6 +454,16 @@ fn add_group_athletes(
             }else{
                 (achievement_string, points_string) = ("".to_string(), "".to_string()); // Don't show results if not included
             }
+            if !achievement_string.is_empty() && athlete.is_personal_best(discipline_name) {
+                // Small PB marker in the upper right corner of the result cell
+                pdf_layer.use_text(
+                    "PB",
+                    font_size * 0.5,
+                    Mm(x_coord + discipline_width - 3.5),
+                    Mm(y_coord + 1.5),
+                    font_bold,
+                );
+            }
             pdf_layer.use_text(
                 achievement_string,
                 font_size,
@@ -497,7 +515,7 @@ fn add_group_athletes(
                     true,
                 );
 
-            pdf_layer = setup_new_page(&pdf, pdf_page_index, pdf_layer_index, group, &alignments);
+            pdf_layer = setup_new_page(&pdf, pdf_page_index, pdf_layer_index, group, &alignments, competition);
             y_coord = initial_y_coord;
         }
     }
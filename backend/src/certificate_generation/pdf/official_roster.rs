@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+use printpdf::{IndirectFontRef, Line, Mm, PdfDocumentReference, PdfLayerReference, Point, TextRenderingMode};
+use printpdf::BuiltinFont::{Helvetica, HelveticaBold};
+use crate::certificate_generation::pdf::pdf_generation::{add_pdf_page, LEFT_PAGE_EDGE, setup_pdf};
+use crate::competitions::Competition;
+use crate::officials::{Roster, RosterEntry};
+
+const FONT_SIZE: f32 = 10.;
+const LINE_HEIGHT: f32 = FONT_SIZE * 0.6;
+const INITIAL_Y_COORD: f32 = 260.;
+const MIN_Y_COORD: f32 = 20.;
+
+/// Officials per station and time slot, stations without a judge are listed at the end
+pub fn get_official_roster(roster: &Roster, competition: &Competition) -> PdfDocumentReference {
+    let (pdf, page, layer) = setup_pdf("Einteilung Kampfrichter", false);
+    let mut current_layer = pdf.get_page(page).get_layer(layer);
+
+    let font = pdf.add_builtin_font(Helvetica)
+        .expect("Builtin Font should be available");
+    let font_bold = pdf.add_builtin_font(HelveticaBold)
+        .expect("Builtin Font should be available");
+
+    current_layer.set_text_rendering_mode(TextRenderingMode::Fill);
+    current_layer.use_text(format!("{} - {}", competition.title(), competition.date_description()),
+                           16., Mm(LEFT_PAGE_EDGE), Mm(280.), &font_bold);
+    current_layer.use_text("Einteilung Kampfrichter", FONT_SIZE, Mm(LEFT_PAGE_EDGE), Mm(270.), &font_bold);
+
+    let mut col_widths = HashMap::new();
+    col_widths.insert("time", 25.);
+    col_widths.insert("location", 30.);
+    col_widths.insert("group", 25.);
+    col_widths.insert("discipline", 35.);
+    col_widths.insert("officials", 75.);
+
+    let mut y_coord = add_heading(&current_layer, &col_widths, &font_bold);
+    for entry in &roster.entries {
+        let num_lines = entry.officials.len().max(1) as f32;
+        if y_coord - LINE_HEIGHT * num_lines < MIN_Y_COORD {
+            let (page, layer) = add_pdf_page(&pdf, "", false);
+            current_layer = pdf.get_page(page).get_layer(layer);
+            y_coord = add_heading(&current_layer, &col_widths, &font_bold);
+        }
+        y_coord = add_entry(&current_layer, &col_widths, entry, competition, y_coord, &font);
+    }
+
+    if !roster.warnings.is_empty() {
+        y_coord -= LINE_HEIGHT;
+        for (index, warning) in roster.warnings.iter().enumerate() {
+            if y_coord < MIN_Y_COORD {
+                let (page, layer) = add_pdf_page(&pdf, "", false);
+                current_layer = pdf.get_page(page).get_layer(layer);
+                y_coord = INITIAL_Y_COORD;
+            }
+            if index == 0 {
+                current_layer.use_text("Fehlende Kampfrichter", FONT_SIZE, Mm(LEFT_PAGE_EDGE), Mm(y_coord), &font_bold);
+                y_coord -= LINE_HEIGHT;
+            }
+            current_layer.use_text(format!("{} - {} ({}): {}", warning.location, warning.discipline, warning.group, warning.message),
+                                   FONT_SIZE, Mm(LEFT_PAGE_EDGE), Mm(y_coord), &font);
+            y_coord -= LINE_HEIGHT;
+        }
+    }
+
+    pdf
+}
+
+fn add_heading(current_layer: &PdfLayerReference, col_widths: &HashMap<&str, f32>, font_bold: &IndirectFontRef) -> f32 {
+    let mut x_coord = LEFT_PAGE_EDGE;
+    for (column, heading) in [("time", "Zeit"), ("location", "Ort"), ("group", "Gruppe"), ("discipline", "Disziplin"), ("officials", "Kampfrichter")] {
+        current_layer.use_text(heading, FONT_SIZE, Mm(x_coord), Mm(INITIAL_Y_COORD), font_bold);
+        x_coord += *col_widths.get(column).expect("Value defined before");
+    }
+    current_layer.add_line(Line {
+        points: vec![
+            (Point::new(Mm(LEFT_PAGE_EDGE - 1.), Mm(INITIAL_Y_COORD - 1.)), false),
+            (Point::new(Mm(x_coord - 1.), Mm(INITIAL_Y_COORD - 1.)), false),
+        ],
+        is_closed: true,
+    });
+    INITIAL_Y_COORD - LINE_HEIGHT
+}
+
+/// Add one line per official of the entry, returns the y coordinate of the next entry
+fn add_entry(current_layer: &PdfLayerReference, col_widths: &HashMap<&str, f32>, entry: &RosterEntry,
+             competition: &Competition, y_coord: f32, font: &IndirectFontRef) -> f32 {
+    let time_zone = competition.config.time_zone;
+    let time = format!("{} - {}",
+                       entry.start_time.with_timezone(&time_zone).format("%H:%M"),
+                       entry.end_time.with_timezone(&time_zone).format("%H:%M"));
+
+    let mut x_coord = LEFT_PAGE_EDGE;
+    for (column, text) in [("time", time.as_str()), ("location", &entry.location), ("group", &entry.group), ("discipline", &entry.discipline)] {
+        current_layer.use_text(text, FONT_SIZE, Mm(x_coord), Mm(y_coord), font);
+        x_coord += *col_widths.get(column).expect("Value defined before");
+    }
+
+    let mut line_y_coord = y_coord;
+    if entry.officials.is_empty() {
+        current_layer.use_text("-", FONT_SIZE, Mm(x_coord), Mm(line_y_coord), font);
+        line_y_coord -= LINE_HEIGHT;
+    }
+    for official in &entry.officials {
+        let text = match &official.contact {
+            Some(contact) => format!("{} ({:?}, {})", official.name, official.role, contact),
+            None => format!("{} ({:?})", official.name, official.role),
+        };
+        current_layer.use_text(text, FONT_SIZE, Mm(x_coord), Mm(line_y_coord), font);
+        line_y_coord -= LINE_HEIGHT;
+    }
+    x_coord += *col_widths.get("officials").expect("Value defined before");
+
+    current_layer.set_outline_thickness(0.5);
+    current_layer.add_line(Line {
+        points: vec![
+            (Point::new(Mm(LEFT_PAGE_EDGE - 1.), Mm(line_y_coord + LINE_HEIGHT - 1.)), false),
+            (Point::new(Mm(x_coord - 1.), Mm(line_y_coord + LINE_HEIGHT - 1.)), false),
+        ],
+        is_closed: true,
+    });
+    current_layer.set_outline_thickness(1.);
+    line_y_coord
+}
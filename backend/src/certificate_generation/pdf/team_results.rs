@@ -1,5 +1,5 @@
 use crate::certificate_generation::pdf::pdf_generation::{add_logo, setup_pdf, LEFT_PAGE_EDGE};
-use crate::certificate_generation::pdf::{COMPETITION_NUMBER, DATE};
+use crate::competitions::Competition;
 use crate::certificate_generation::{athletes, competition_order, CompetitionType, Group};
 use crate::teams::Team;
 use chrono::Datelike;
@@ -17,9 +17,9 @@ use std::fs::File;
 const DEFAULT_DISCIPLINE_WIDTH: f32 = 11.5;
 const POINTS_WIDTH: f32 = 8.;
 
-pub fn new_team_result(teams: &Vec<Team>) -> PdfDocumentReference {
+pub fn new_team_result(teams: &Vec<Team>, competition: &Competition) -> PdfDocumentReference {
     let (pdf, page, layer) = setup_pdf("Ergebnis Teams", true);
-    add_team_result_to_page(teams, pdf, page, layer)
+    add_team_result_to_page(teams, pdf, page, layer, competition)
 }
 
 pub fn add_team_result_to_page(
@@ -27,6 +27,7 @@ pub fn add_team_result_to_page(
     pdf: PdfDocumentReference,
     page: PdfPageIndex,
     layer: PdfLayerIndex,
+    competition: &Competition,
 ) -> PdfDocumentReference {
     let comic_font = pdf
         .add_external_font(
@@ -50,7 +51,7 @@ pub fn add_team_result_to_page(
     alignments.insert("member_name_points", 90.);
 
 
-    let current_layer = setup_new_page(&pdf, page, layer, &alignments);
+    let current_layer = setup_new_page(&pdf, page, layer, &alignments, competition);
 
     // Add athletes
     add_teams(
@@ -59,7 +60,8 @@ pub fn add_team_result_to_page(
         &comic_font_bold,
         &alignments,
         teams,
-        &pdf
+        &pdf,
+        competition
     );
     pdf
 }
@@ -69,6 +71,7 @@ fn setup_new_page(
     page: PdfPageIndex,
     layer: PdfLayerIndex,
     alignments: &HashMap<&str, f32>,
+    competition: &Competition,
 ) -> PdfLayerReference {
     let current_layer = pdf.get_page(page).get_layer(layer);
 
@@ -87,7 +90,7 @@ fn setup_new_page(
     current_layer.begin_text_section();
     let font_size = 18.0;
     current_layer.use_text(
-        format!("{COMPETITION_NUMBER}. Favoritner Jedermann - Zehnkampf {DATE}"),
+        format!("{} {}", competition.title(), competition.date_description()),
         font_size,
         Mm(65.0),
         Mm(195.0),
@@ -184,7 +187,8 @@ fn add_teams(
     font_bold: &IndirectFontRef,
     alignments: &HashMap<&str, f32>,
     teams: &Vec<Team>,
-    pdf: &PdfDocumentReference
+    pdf: &PdfDocumentReference,
+    competition: &Competition,
 ) {
     let mut teams = teams.clone();
     let font_size: f32 = 10.0;
@@ -353,7 +357,7 @@ fn add_teams(
                     true,
                 );
 
-            pdf_layer = setup_new_page(&pdf, pdf_page_index, pdf_layer_index, &alignments);
+            pdf_layer = setup_new_page(&pdf, pdf_page_index, pdf_layer_index, &alignments, competition);
             y_coord = initial_y_coord;
         }
     }
@@ -0,0 +1,211 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Utc};
+use chrono::serde::ts_seconds;
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::error::Error;
+
+use crate::certificate_generation::{Athlete, AthleteID, CompetitionType};
+use crate::time_planner::{Station, DEFAULT_LANES, DEFAULT_TIME_ZONE};
+
+const MONTHS: [&str; 12] = [
+    "Jänner", "Februar", "März", "April", "Mai", "Juni",
+    "Juli", "August", "September", "Oktober", "November", "Dezember"
+];
+
+/// Hurdle runs need a lane for the older age groups, inner and outer lanes
+const MIN_LANES: usize = 3;
+const MAX_LANES: usize = 10;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CompetitionID {
+    pub number: String,
+}
+
+/// One edition of the competition (e.g. the 30th Favoritner Jedermann Zehnkampf).
+/// All athletes, groups, time groups, teams and notes belong to exactly one competition, logins are shared.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Competition {
+    pub number: String,
+    pub name: String,
+    #[serde(with = "ts_seconds")]
+    pub start_date: DateTime<Utc>,
+    #[serde(with = "ts_seconds")]
+    pub end_date: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub venue: Option<String>,
+    #[serde(default)]
+    pub config: CompetitionConfig,
+    #[serde(default)]
+    pub active: bool,
+    /// Personal data of all athletes was replaced by pseudonyms after the retention period
+    #[serde(default)]
+    pub anonymized: bool,
+}
+
+/// Settings which may change from one edition to the next
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CompetitionConfig {
+    #[serde(default = "all_competition_types")]
+    pub competition_types: Vec<CompetitionType>,
+    /// Number of lanes of the track at the venue
+    #[serde(default = "default_lanes")]
+    pub lanes: usize,
+    /// IANA time zone of the venue, the time table and all displayed times are local to it
+    #[serde(default = "default_time_zone")]
+    pub time_zone: Tz,
+    /// Stations of the venue, time table locations are not checked if none are defined
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stations: Vec<Station>,
+}
+
+impl Default for CompetitionConfig {
+    fn default() -> Self {
+        CompetitionConfig {
+            competition_types: all_competition_types(),
+            lanes: default_lanes(),
+            time_zone: default_time_zone(),
+            stations: vec![],
+        }
+    }
+}
+
+fn default_lanes() -> usize {
+    DEFAULT_LANES
+}
+
+fn default_time_zone() -> Tz {
+    DEFAULT_TIME_ZONE
+}
+
+fn all_competition_types() -> Vec<CompetitionType> {
+    vec![
+        CompetitionType::Decathlon,
+        CompetitionType::Triathlon,
+        CompetitionType::Pentathlon,
+        CompetitionType::Heptathlon,
+    ]
+}
+
+impl Competition {
+    pub fn from_json(json_str: &str) -> Result<Self, Box<dyn Error>> {
+        let competition: Competition = serde_json::from_str(json_str)?;
+        competition.validate()?;
+        Ok(competition)
+    }
+
+    pub fn update_values(&mut self, json_str: &str) -> Result<(), Box<dyn Error>> {
+        let json_value: Value = serde_json::from_str(json_str)?;
+
+        if json_value.get("number").is_some() {
+            return Err(Box::from("Competition number can not be changed"));
+        }
+        if let Some(name) = json_value.get("name") {
+            self.name = Value::as_str(name)
+                .ok_or("Invalid format for name. Expected string")?
+                .to_string();
+        }
+        if let Some(start_date) = json_value.get("start_date") {
+            self.start_date = serde_json::from_value::<TimestampWrapper>(start_date.clone())?.0;
+        }
+        if let Some(end_date) = json_value.get("end_date") {
+            self.end_date = serde_json::from_value::<TimestampWrapper>(end_date.clone())?.0;
+        }
+        if let Some(venue) = json_value.get("venue") {
+            self.venue = Value::as_str(venue).map(String::from);
+        }
+        if let Some(config) = json_value.get("config") {
+            self.config = serde_json::from_value(config.clone())?;
+        }
+
+        self.validate()
+    }
+
+    fn validate(&self) -> Result<(), Box<dyn Error>> {
+        if self.number.trim().is_empty() {
+            return Err(Box::from("Competition number not given"));
+        }
+        if self.end_date < self.start_date {
+            return Err(Box::from("Competition ends before it starts"));
+        }
+        if !(MIN_LANES..=MAX_LANES).contains(&self.config.lanes) {
+            return Err(Box::from(format!("Number of lanes must be between {MIN_LANES} and {MAX_LANES}")));
+        }
+        for (index, station) in self.config.stations.iter().enumerate() {
+            if station.name.trim().is_empty() {
+                return Err(Box::from("Name of the station not given"));
+            }
+            if station.capacity == 0 {
+                return Err(Box::from(format!("Capacity of station {} must be at least 1", station.name)));
+            }
+            if self.config.stations[..index].iter().any(|other| other.name == station.name) {
+                return Err(Box::from(format!("Station {} is defined twice", station.name)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Heading used on certificates and result lists, e.g. "30. Favoritner Jedermann Zehnkampf"
+    pub fn title(&self) -> String {
+        format!("{}. {}", self.number, self.name)
+    }
+
+    /// Human-readable (german) date of the competition in the time zone of the venue, e.g. "27. / 28. September 2025"
+    pub fn date_description(&self) -> String {
+        let time_zone = self.config.time_zone;
+        let (start, end) = (self.start_date.with_timezone(&time_zone), self.end_date.with_timezone(&time_zone));
+        let month_name = |date: &DateTime<Tz>| MONTHS[date.month0() as usize];
+
+        if start.date_naive() == end.date_naive() {
+            format!("{}. {} {}", start.day(), month_name(&start), start.year())
+        } else if start.year() != end.year() {
+            format!("{}. {} {} / {}. {} {}",
+                    start.day(), month_name(&start), start.year(),
+                    end.day(), month_name(&end), end.year())
+        } else if start.month() != end.month() {
+            format!("{}. {} / {}. {} {}",
+                    start.day(), month_name(&start), end.day(), month_name(&end), end.year())
+        } else {
+            format!("{}. / {}. {} {}", start.day(), end.day(), month_name(&end), end.year())
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct TimestampWrapper(#[serde(with = "ts_seconds")] DateTime<Utc>);
+
+#[async_trait]
+pub trait CompetitionStorage {
+    async fn get_competitions(&self) -> Result<Vec<Competition>, Box<dyn Error>>;
+    async fn get_competition(&self, competition_id: &CompetitionID) -> Option<Competition>;
+    async fn store_competition(&self, competition: Competition) -> Result<String, Box<dyn Error>>;
+    /// The active competition is the one all other storage calls are scoped to
+    async fn get_active_competition(&self) -> Option<Competition>;
+    async fn set_active_competition(&self, competition_id: &CompetitionID) -> Result<String, Box<dyn Error>>;
+    /// Entries of the given athletes in all competitions (including the active one)
+    async fn get_athlete_editions(&self, athlete_ids: &[AthleteID]) -> Result<HashMap<AthleteID, Vec<(Competition, Athlete)>>, Box<dyn Error>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Competition;
+
+    fn get_competition(start_date: i64, end_date: i64) -> Result<Competition, Box<dyn std::error::Error>> {
+        Competition::from_json(format!(r#"{{"number": "30", "name": "Favoritner Jedermann Zehnkampf",
+            "start_date": {start_date}, "end_date": {end_date}}}"#).as_str())
+    }
+
+    #[test]
+    fn date_description() {
+        let competition = get_competition(1758931200, 1759017600).expect("Competition should be valid");
+        assert_eq!(competition.date_description(), "27. / 28. September 2025");
+        assert_eq!(competition.title(), "30. Favoritner Jedermann Zehnkampf");
+
+        let competition = get_competition(1759190400, 1759276800).expect("Competition should be valid");
+        assert_eq!(competition.date_description(), "30. September / 1. Oktober 2025");
+
+        assert!(get_competition(1759017600, 1758931200).is_err());
+    }
+}
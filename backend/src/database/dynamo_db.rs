@@ -1,30 +1,156 @@
+use crate::audit::{AuditAction, AuditRecord, AuditStorage};
 use crate::authenticate::{AuthenticateStorage, LoginInfo, Role};
+use crate::competitions::{Competition, CompetitionID, CompetitionStorage};
 use crate::certificate_generation::{Achievement, AchievementID, AchievementStorage, AgeGroup, AgeGroupID,
-    AgeGroupSelector, Athlete, AthleteID, Group, GroupID, GroupStore, SwitchGroupID
+    AgeGroupSelector, Athlete, AthleteID, AthleteUpdate, CompetitionType, Group, GroupID, GroupStore, GroupSwitch, SwitchGroupID
 };
 use crate::database::db_errors::ItemNotFound;
-use crate::notes::{NoteID, NoteStorage};
+use crate::notes::{NoteEntry, NoteID, NoteStorage};
+use crate::privacy::{assign_pseudonyms, pseudonym, pseudonymize_text, AthleteRecord, PrivacyStorage};
 use crate::teams::{Team, TeamID, TeamStorage};
-use crate::time_planner::{TimeGroup, TimeGroupID, TimePlanStorage};
+use crate::time_planner::{TimeGroup, TimeGroupID, TimePlanStorage, TimeTableChanges};
+use crate::waitlist::{Waitlist, WaitlistStorage};
+use crate::officials::{Official, OfficialID, OfficialStorage};
 use crate::{time_planner, Storage};
 use async_trait::async_trait;
 use aws_config::BehaviorVersion;
-use aws_sdk_dynamodb::types::{AttributeValue, KeysAndAttributes};
+use aws_sdk_dynamodb::types::{AttributeValue, KeysAndAttributes, Put, TransactWriteItem, Update};
+use aws_sdk_dynamodb::types::builders::PutBuilder;
 use aws_sdk_dynamodb::Client;
 use serde_json::Value;
 use std::collections::HashMap;
 use std::error::Error;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
 use log::error;
 
+/// Partition key of all tables which hold data of a single competition
+const COMPETITION_KEY: &str = "competition";
+
+/// How long the active competition is cached. Other instances of the backend pick up a change of the active
+/// competition after at most this time.
+const ACTIVE_COMPETITION_TTL: Duration = Duration::from_secs(5);
+
+/// Competition the store reads and writes
+enum CompetitionScope {
+    /// The active competition, cached with the time it was loaded
+    Active(RwLock<Option<(String, Instant)>>),
+    /// A fixed competition, e.g. for the anonymisation of an expired one
+    Fixed(String),
+}
+
 pub struct DynamoDB {
     client: Client,
+    competition: CompetitionScope,
 }
 
 impl DynamoDB {
     pub fn new() -> Self {
         let config = futures::executor::block_on(aws_config::load_from_env());
         let client = Client::new(&config);
-        DynamoDB { client }
+        DynamoDB {
+            client,
+            competition: CompetitionScope::Active(RwLock::new(None)),
+        }
+    }
+
+    /// Number of the active competition as DynamoDB partition key. Loaded from the competition table at most
+    /// every `ACTIVE_COMPETITION_TTL`, falls back to the COMPETITION env variable.
+    async fn competition_key(&self) -> AttributeValue {
+        let cache = match &self.competition {
+            CompetitionScope::Fixed(number) => return AttributeValue::S(number.clone()),
+            CompetitionScope::Active(cache) => cache,
+        };
+        if let Some((number, loaded)) = cache.read().expect("RwLock poisoned").clone() {
+            if loaded.elapsed() < ACTIVE_COMPETITION_TTL {
+                return AttributeValue::S(number);
+            }
+        }
+        let number = match self.load_active_competition().await {
+            Some(competition) => competition.number,
+            None => std::env::var("COMPETITION").unwrap_or("default".to_string()),
+        };
+        *cache.write().expect("RwLock poisoned") = Some((number.clone(), Instant::now()));
+        AttributeValue::S(number)
+    }
+
+    /// Partition key for writes. The cached active competition is checked to be still active, so an instance does
+    /// not write into a competition which was deactivated on another instance within `ACTIVE_COMPETITION_TTL`.
+    async fn write_competition_key(&self) -> Result<AttributeValue, Box<dyn Error>> {
+        let competition_key = self.competition_key().await;
+        let (CompetitionScope::Active(cache), AttributeValue::S(number)) = (&self.competition, &competition_key) else {
+            return Ok(competition_key);
+        };
+        let item = self
+            .client
+            .get_item()
+            .table_name(std::env::var("DB_NAME_COMPETITION").unwrap_or("competition_store".to_string()))
+            .key("number", AttributeValue::S(number.clone()))
+            .projection_expression("#active")
+            .expression_attribute_names("#active", "active")
+            .consistent_read(true)
+            .send()
+            .await?;
+        // Without a stored competition the key comes from the COMPETITION env variable
+        if let Some(AttributeValue::Bool(false)) = item.item().and_then(|item| item.get("active")) {
+            *cache.write().expect("RwLock poisoned") = None;
+            return Err(Box::from(format!("Competition {number} is no longer active, reload and try again")));
+        }
+        Ok(competition_key)
+    }
+
+    /// Store handle scoped to the given competition instead of the active one
+    fn for_competition(&self, competition_number: &str) -> DynamoDB {
+        DynamoDB {
+            client: self.client.clone(),
+            competition: CompetitionScope::Fixed(competition_number.to_string()),
+        }
+    }
+
+    async fn load_active_competition(&self) -> Option<Competition> {
+        let competitions = self.get_competitions().await.ok()?;
+        competitions.into_iter().find(|competition| competition.active)
+    }
+
+    /// Get all items of the given table which belong to the active competition
+    async fn query_competition(&self, table_name: String) -> Result<Vec<HashMap<String, AttributeValue>>, Box<dyn Error>> {
+        self.query_items(table_name, None).await
+    }
+
+    /// Items of the active competition whose range key starts with the prefix
+    async fn query_competition_prefix(&self, table_name: String, range_key: &str, prefix: &str) -> Result<Vec<HashMap<String, AttributeValue>>, Box<dyn Error>> {
+        self.query_items(table_name, Some((range_key, prefix))).await
+    }
+
+    async fn query_items(&self, table_name: String, range_prefix: Option<(&str, &str)>) -> Result<Vec<HashMap<String, AttributeValue>>, Box<dyn Error>> {
+        let key_condition = match range_prefix {
+            Some(_) => "#competition = :competition AND begins_with(#range, :prefix)",
+            None => "#competition = :competition",
+        };
+        let mut items = vec![];
+        let mut last_evaluated_key = None;
+        loop {
+            let mut query_call = self
+                .client
+                .query()
+                .table_name(table_name.clone())
+                .key_condition_expression(key_condition)
+                .expression_attribute_names("#competition", COMPETITION_KEY)
+                .expression_attribute_values(":competition", self.competition_key().await)
+                .set_exclusive_start_key(last_evaluated_key);
+            if let Some((range_key, prefix)) = range_prefix {
+                query_call = query_call
+                    .expression_attribute_names("#range", range_key)
+                    .expression_attribute_values(":prefix", AttributeValue::S(prefix.to_string()));
+            }
+            let result = query_call.send().await?;
+            items.extend(result.items().to_vec());
+            last_evaluated_key = result.last_evaluated_key;
+            if last_evaluated_key.is_none() {
+                break;
+            }
+        }
+        Ok(items)
     }
 
     async fn overwrite_achievement(
@@ -36,10 +162,12 @@ impl DynamoDB {
             .athlete_name
             .ok_or("Athlete name not given")?;
 
+        let competition_key = self.write_competition_key().await?;
         let mut update_call = self
             .client
             .update_item()
-            .table_name(std::env::var("DB_NAME_ATHLETE").unwrap_or("athlete_store".to_string()))
+            .table_name(std::env::var("DB_NAME_ATHLETE").unwrap_or("athlete_store_v2".to_string()))
+            .key(COMPETITION_KEY, competition_key)
             .key("athlete_id", AttributeValue::S(athlete_name));
 
         update_call = update_call
@@ -69,6 +197,33 @@ impl DynamoDB {
     }
 }
 
+/// Put of an item with the next version, the item is only written if the stored version is still the given one
+/// (see version_condition)
+fn versioned_put(table_name: &str, item: HashMap<String, AttributeValue>, version: u64) -> PutBuilder {
+    Put::builder()
+        .table_name(table_name)
+        .set_item(Some(item))
+        .item("version", AttributeValue::N((version + 1).to_string()))
+        .expression_attribute_values(":version", AttributeValue::N(version.to_string()))
+}
+
+/// Audit record item, keyed by the full names of the athletes it concerns (attribute athletes), so the records of an
+/// athlete can be found for the data export and anonymization
+fn audit_item(record: &AuditRecord) -> Result<HashMap<String, AttributeValue>, Box<dyn Error>> {
+    let mut item: HashMap<String, AttributeValue> = serde_dynamo::to_item(record)?;
+    let athletes = record.action.athlete_ids().iter().map(|athlete_id| AttributeValue::S(athlete_id.full_name())).collect();
+    item.insert(String::from("athletes"), AttributeValue::L(athletes));
+    Ok(item)
+}
+
+/// Condition that the stored item has the version, items stored before versions were introduced have version 0
+fn version_condition(version: u64) -> String {
+    match version {
+        0 => String::from("attribute_not_exists(version) OR version = :version"),
+        _ => String::from("version = :version"),
+    }
+}
+
 #[async_trait]
 impl AchievementStorage for DynamoDB {
     async fn get_athlete(&self, athlete_id: &AthleteID) -> Option<Athlete> {
@@ -76,7 +231,8 @@ impl AchievementStorage for DynamoDB {
         let item = self
             .client
             .get_item()
-            .table_name(std::env::var("DB_NAME_ATHLETE").unwrap_or("athlete_store".to_string()))
+            .table_name(std::env::var("DB_NAME_ATHLETE").unwrap_or("athlete_store_v2".to_string()))
+            .key(COMPETITION_KEY, self.competition_key().await)
             .key("athlete_id", AttributeValue::S(athlete_name))
             .send()
             .await;
@@ -92,25 +248,19 @@ impl AchievementStorage for DynamoDB {
     }
 
     async fn get_athletes(&self) -> HashMap<String, Vec<Athlete>> {
-        let results = self.client.scan().table_name(std::env::var("DB_NAME_GROUP").unwrap_or("group_store".to_string())).send().await;
-
-        let group_ids: Vec<GroupID> = match results {
+        let group_ids: Vec<GroupID> = match self.query_competition(std::env::var("DB_NAME_GROUP").unwrap_or("group_store_v2".to_string())).await {
             Ok(items) => {
-                if let Some(items) = items.items {
-                    items
-                        .iter()
-                        .map(|v| {
-                            let group_name = v
-                                .get("name")
-                                .expect("Name should be available since it is primary key")
-                                .as_s()
-                                .expect("Name should be convertable to string");
-                            GroupID::new(group_name)
-                        })
-                        .collect()
-                } else {
-                    vec![]
-                }
+                items
+                    .iter()
+                    .map(|v| {
+                        let group_name = v
+                            .get("name")
+                            .expect("Name should be available since it is primary key")
+                            .as_s()
+                            .expect("Name should be convertable to string");
+                        GroupID::new(group_name)
+                    })
+                    .collect()
             }
             Err(_) => vec![],
         };
@@ -127,6 +277,13 @@ impl AchievementStorage for DynamoDB {
         groups
     }
 
+    async fn get_all_athletes(&self) -> Result<Vec<Athlete>, Box<dyn Error>> {
+        let items = self
+            .query_competition(std::env::var("DB_NAME_ATHLETE").unwrap_or("athlete_store_v2".to_string()))
+            .await?;
+        Ok(serde_dynamo::from_items(items)?)
+    }
+
     async fn write_athlete(
         &self,
         athlete_id: AthleteID,
@@ -134,10 +291,12 @@ impl AchievementStorage for DynamoDB {
     ) -> Result<String, Box<dyn Error>> {
         let athlete_name = athlete_id.full_name();
         let item = serde_dynamo::to_item(athlete)?;
+        let competition_key = self.write_competition_key().await?;
         self.client
             .put_item()
-            .table_name(std::env::var("DB_NAME_ATHLETE").unwrap_or("athlete_store".to_string()))
+            .table_name(std::env::var("DB_NAME_ATHLETE").unwrap_or("athlete_store_v2".to_string()))
             .set_item(Some(item))
+            .item(COMPETITION_KEY, competition_key)
             .item("athlete_id", AttributeValue::S(athlete_name))
             .send()
             .await?;
@@ -151,17 +310,20 @@ impl AchievementStorage for DynamoDB {
         &self,
         athlete_id: AthleteID,
         json_string: &str,
-    ) -> Result<String, Box<dyn Error>> {
-        let mut result = "Athlete updated";
-        if let None = self.get_athlete(&athlete_id).await {
-            return Err(Box::from("Athlete not found. Insert new athlete"));
-        }
+    ) -> Result<AthleteUpdate, Box<dyn Error>> {
+        let was_deregistered = match self.get_athlete(&athlete_id).await {
+            Some(athlete) => athlete.is_deregistered(),
+            None => return Err(Box::from("Athlete not found. Insert new athlete")),
+        };
+        let mut is_deregistered = was_deregistered;
 
         let athlete_name = athlete_id.full_name();
+        let competition_key = self.write_competition_key().await?;
         let mut update_call = self
             .client
             .update_item()
-            .table_name(std::env::var("DB_NAME_ATHLETE").unwrap_or("athlete_store".to_string()))
+            .table_name(std::env::var("DB_NAME_ATHLETE").unwrap_or("athlete_store_v2".to_string()))
+            .key(COMPETITION_KEY, competition_key)
             .key("athlete_id", AttributeValue::S(athlete_name));
 
         let json_value: Value = serde_json::from_str(json_string)?;
@@ -247,11 +409,7 @@ impl AchievementStorage for DynamoDB {
 
             match Value::as_bool(starting_number) {
                 Some(val) => {
-                    if val{
-                        result = "Athlete deregistered";
-                    } else {
-                        result = "Athlete registered";
-                    }
+                    is_deregistered = val;
                     update_call = update_call.expression_attribute_values(
                         String::from(":de"),
                         AttributeValue::Bool(val),
@@ -265,6 +423,15 @@ impl AchievementStorage for DynamoDB {
                 }
             }
         }
+        if let Some(declared_best_times) = json_value.get("declared_best_times") {
+            let declared_best_times: HashMap<String, u32> = serde_json::from_value(declared_best_times.clone())
+                .map_err(|e| format!("Invalid format for declared_best_times. Expected milliseconds by discipline: {e}"))?;
+            update_expressions.push(String::from(" declared_best_times = :bt"));
+            update_call = update_call.expression_attribute_values(
+                String::from(":bt"),
+                serde_dynamo::to_attribute_value(declared_best_times)?,
+            );
+        }
         if update_expressions.len() > 0 {
             update_call =
                 update_call.update_expression(format!("SET {}", update_expressions.join(",")));
@@ -272,7 +439,7 @@ impl AchievementStorage for DynamoDB {
 
         update_call.send().await?;
 
-        Ok(String::from(result))
+        Ok(AthleteUpdate::from_registration(was_deregistered, is_deregistered))
     }
 
     async fn delete_athlete(&self, athlete_id: AthleteID) -> Result<String, Box<dyn Error>> {
@@ -280,7 +447,7 @@ impl AchievementStorage for DynamoDB {
         // Delete Athlete from athlete_store
         // self.client
         //     .delete_item()
-        //     .table_name(std::env::var("DB_NAME_ATHLETE").unwrap_or("athlete_store".to_string()))
+        //     .table_name(std::env::var("DB_NAME_ATHLETE").unwrap_or("athlete_store_v2".to_string()))
         //     .key("athlete_id", AttributeValue::S(athlete_id.full_name().into()))
         //     .send().await?;
 
@@ -290,17 +457,10 @@ impl AchievementStorage for DynamoDB {
         athlete_map.insert("name".to_string(), AttributeValue::S(athlete_id.name()));
         athlete_map.insert("surname".to_string(), AttributeValue::S(athlete_id.surname()));
 
-        let groups = match self.client
-            .scan()
-            .table_name(std::env::var("DB_NAME_GROUP").unwrap_or("group_store".to_string()))
-            .send()
+        let groups = match self
+            .query_competition(std::env::var("DB_NAME_GROUP").unwrap_or("group_store_v2".to_string()))
             .await{
-                Ok(groups) => {
-                    match groups.items{
-                        Some(groups) => Ok(groups),
-                        None => Err(format!("No groups found"))
-                    }
-                },
+                Ok(groups) => Ok(groups),
                 Err(e) => Err(format!("Error loading groups: {}", e))
             }?;
 
@@ -344,7 +504,8 @@ impl AchievementStorage for DynamoDB {
         let item = self
             .client
             .get_item()
-            .table_name(std::env::var("DB_NAME_GROUP").unwrap_or("group_store".to_string()))
+            .table_name(std::env::var("DB_NAME_GROUP").unwrap_or("group_store_v2".to_string()))
+            .key(COMPETITION_KEY, self.competition_key().await)
             .key("name", AttributeValue::S(group_name))
             .send();
         
@@ -355,32 +516,38 @@ impl AchievementStorage for DynamoDB {
                     serde_dynamo::from_item(item_map.clone()).unwrap_or(None)?;
                 
                 if group_store.athlete_ids.len() == 0 {
-                    return Some(Group::new(
+                    let mut group = Group::new(
                         group_store.name.as_str(),
                         Vec::new(),
                         group_store.competition_type,
-                    ))
+                    );
+                    group.set_capacity(group_store.capacity);
+                    return Some(group)
                 }
 
+                let competition_key = self.competition_key().await;
                 let mut group_athletes_keys = KeysAndAttributes::builder();
                 for athlete_id in &group_store.athlete_ids{
-                    group_athletes_keys = group_athletes_keys.keys(HashMap::from([(
-                        "athlete_id".to_string(),
-                        AttributeValue::S(
-                            athlete_id.full_name().clone()
-                        ),
-                    )]));
+                    group_athletes_keys = group_athletes_keys.keys(HashMap::from([
+                        (COMPETITION_KEY.to_string(), competition_key.clone()),
+                        (
+                            "athlete_id".to_string(),
+                            AttributeValue::S(
+                                athlete_id.full_name().clone()
+                            ),
+                        )
+                    ]));
                 };
 
                 let athlete_result = self.client
                 .batch_get_item()
                 .request_items(
-                    std::env::var("DB_NAME_ATHLETE").unwrap_or("athlete_store".to_string()),
+                    std::env::var("DB_NAME_ATHLETE").unwrap_or("athlete_store_v2".to_string()),
                     group_athletes_keys.build().ok()?,
                 )
                 .send().await.ok()?;
 
-                let athlete_items = athlete_result.responses()?.get(std::env::var("DB_NAME_ATHLETE").unwrap_or("athlete_store".to_string()).as_str())?; 
+                let athlete_items = athlete_result.responses()?.get(std::env::var("DB_NAME_ATHLETE").unwrap_or("athlete_store_v2".to_string()).as_str())?; 
                 let mut athletes = Vec::new();
 
                 for athlete_data in athlete_items{
@@ -394,11 +561,13 @@ impl AchievementStorage for DynamoDB {
                     }
                 }
 
-                Some(Group::new(
+                let mut group = Group::new(
                     group_store.name.as_str(),
                     athletes,
                     group_store.competition_type,
-                ))
+                );
+                group.set_capacity(group_store.capacity);
+                Some(group)
             }
             Err(e) => {
                 error!("Group not found {}", e);
@@ -407,6 +576,23 @@ impl AchievementStorage for DynamoDB {
         }
     }
 
+    async fn get_group_store(&self, group_id: &GroupID) -> Option<GroupStore> {
+        let group_name = group_id.name.clone()?;
+        let item = self
+            .client
+            .get_item()
+            .table_name(std::env::var("DB_NAME_GROUP").unwrap_or("group_store_v2".to_string()))
+            .key(COMPETITION_KEY, self.competition_key().await)
+            .key("name", AttributeValue::S(group_name))
+            .send()
+            .await;
+
+        match item {
+            Ok(item) => serde_dynamo::from_item(item.item()?.clone()).unwrap_or(None),
+            Err(_) => None,
+        }
+    }
+
     async fn write_group_store(
         &self,
         _: GroupID,
@@ -421,11 +607,18 @@ impl AchievementStorage for DynamoDB {
                 )));
             }
         }
+        // Only written if the group was not changed since it was loaded
+        let version = group_store.version;
         let item = serde_dynamo::to_item(group_store)?;
+        let competition_key = self.write_competition_key().await?;
         self.client
             .put_item()
-            .table_name(std::env::var("DB_NAME_GROUP").unwrap_or("group_store".to_string()))
+            .table_name(std::env::var("DB_NAME_GROUP").unwrap_or("group_store_v2".to_string()))
             .set_item(Some(item))
+            .item(COMPETITION_KEY, competition_key)
+            .item("version", AttributeValue::N((version + 1).to_string()))
+            .condition_expression(version_condition(version))
+            .expression_attribute_values(":version", AttributeValue::N(version.to_string()))
             .send()
             .await?;
 
@@ -433,14 +626,8 @@ impl AchievementStorage for DynamoDB {
     }
 
     async fn write_group(&self, group_id: GroupID, group: Group) -> Result<String, Box<dyn Error>> {
-        let group_store = GroupStore {
-            name: group.name().to_string(),
-            athlete_ids: group.athlete_ids(),
-            competition_type: group.competition_type(),
-            notes: group.notes().clone(),
-        };
-
-        self.write_group_store(group_id, group_store).await
+        let version = self.get_group_store(&group_id).await.map_or(0, |group_store| group_store.version);
+        self.write_group_store(group_id, GroupStore::from_group(&group, version)).await
     }
 
     async fn update_group(
@@ -449,37 +636,23 @@ impl AchievementStorage for DynamoDB {
         json_string: &str,
         only_time_group: bool
     ) -> Result<String, Box<dyn Error>> {
+        // The group is only written if it was not changed in the meantime
+        let version = self.get_group_store(&group_id).await.map_or(0, |group_store| group_store.version);
         let mut group = self
             .get_group(&group_id)
             .await
             .ok_or(ItemNotFound::new("Key not found", "404"))?;
 
-        let group_name = match group_id.name.clone() {
-            Some(name) => Ok(name),
-            None => Err("Group name not suuplied"),
-        }?;
-
-        let mut update_call = self
-            .client
-            .update_item()
-            .table_name(std::env::var("DB_NAME_GROUP").unwrap_or("group_store".to_string()))
-            .key("name", AttributeValue::S(group_name));
-
         let json_value: Value = serde_json::from_str(json_string)?;
 
-        let mut update_expressions: Vec<String> = vec![];
-
         let mut new_athletes: Vec<Athlete> = vec![];
         let mut deleted_athletes: Vec<Athlete> = vec![];
 
-        // Update specific fields from JSON to struct
+        // The name is the key of the group
         if let Some(name) = json_value.get("name").and_then(Value::as_str) {
-            update_expressions.push(String::from(" name = :n"));
-
-            update_call = update_call.expression_attribute_values(
-                String::from(":n"),
-                AttributeValue::S(name.to_string()),
-            );
+            if name != group.name() {
+                return Err(Box::from("Groups can not be renamed"));
+            }
         }
         if let Some(athletes) = json_value.get("athletes").and_then(Value::as_array) {
             for athlete_value in athletes {
@@ -542,37 +715,19 @@ impl AchievementStorage for DynamoDB {
             }
         }
         
-        if deleted_athletes.len() > 0 || new_athletes.len() > 0 {
-            update_expressions.push(String::from(" athletes = :a"));
-
-            let mut athlete_values = vec![];
-            for athlete in group.athletes() {
-                let mut athlete_map = HashMap::new();
-                athlete_map.insert(
-                    "name".to_string(),
-                    AttributeValue::S(athlete.name().to_string()),
-                );
-                athlete_map.insert(
-                    "surname".to_string(),
-                    AttributeValue::S(athlete.surname().to_string()),
-                );
-                athlete_values.push(AttributeValue::M(athlete_map));
-            }
-
-            update_call = update_call
-                .expression_attribute_values(String::from(":a"), AttributeValue::L(athlete_values));
-        }
-
-        if update_expressions.len() > 0 {
-            update_call =
-                update_call.update_expression(format!("SET {}", update_expressions.join(",")));
+        // A capacity of null removes the limit
+        let capacity = json_value.get("capacity");
+        if let Some(capacity) = capacity {
+            group.set_capacity(serde_json::from_value(capacity.clone())?);
         }
         let group_name = group.name().to_string();
 
+        // Re-registered athletes (only_time_group) count towards the capacity as well
+        if !new_athletes.is_empty() || capacity.is_some() {
+            group.check_capacity()?;
+        }
         if !only_time_group {
-            update_call.send().await?;
-            self.write_group(group_id, group).await?;
-
+            self.write_group_store(group_id, GroupStore::from_group(&group, version)).await?;
         }
 
         if new_athletes.len() > 0 {
@@ -623,13 +778,48 @@ impl AchievementStorage for DynamoDB {
         json_string: &str,
     ) -> Result<String, Box<dyn Error>> {
 
-        let from_group_id = GroupID::new(group_info.from.ok_or("From group not given")?.as_str());
-        let to_group_id = GroupID::new(group_info.to.ok_or("To group not given")?.as_str());
-
-        self.update_group(to_group_id, json_string, false).await?;
+        let switch = GroupSwitch::load(self, group_info, json_string).await?;
+        let competition_key = self.write_competition_key().await?;
+        let group_table = std::env::var("DB_NAME_GROUP").unwrap_or("group_store_v2".to_string());
+        let time_group_table = std::env::var("DB_NAME_TIMEGROUP").unwrap_or("time_group_store_v2".to_string());
+
+        // The source group is only written if it still contains all moved athletes, all groups and time groups
+        // only if they were not changed since they were loaded
+        let mut from_put = versioned_put(&group_table, serde_dynamo::to_item(&switch.from)?, switch.from.version)
+            .item(COMPETITION_KEY, competition_key.clone());
+        let mut conditions = vec![];
+        for (index, athlete_id) in switch.athlete_ids.iter().enumerate() {
+            let key = format!(":athlete{}", index);
+            conditions.push(format!("contains(athlete_ids, {})", key));
+            from_put = from_put.expression_attribute_values(key, AttributeValue::M(serde_dynamo::to_item(athlete_id)?));
+        }
+        from_put = from_put.condition_expression(format!("({}) AND {}", version_condition(switch.from.version), conditions.join(" AND ")));
+
+        let mut transact_items = vec![
+            TransactWriteItem::builder().put(from_put.build()?).build(),
+            TransactWriteItem::builder().put(versioned_put(&group_table, serde_dynamo::to_item(&switch.to)?, switch.to.version)
+                .item(COMPETITION_KEY, competition_key.clone())
+                .condition_expression(version_condition(switch.to.version))
+                .build()?).build(),
+        ];
+        for time_group in [&switch.from_time_group, &switch.to_time_group].into_iter().flatten() {
+            transact_items.push(TransactWriteItem::builder().put(versioned_put(&time_group_table, serde_dynamo::to_item(time_group)?, time_group.version())
+                .item(COMPETITION_KEY, competition_key.clone())
+                .condition_expression(version_condition(time_group.version()))
+                .build()?).build());
+        }
+        transact_items.push(TransactWriteItem::builder().put(Put::builder()
+            .table_name(std::env::var("DB_NAME_AUDIT").unwrap_or("audit_store".to_string()))
+            .set_item(Some(audit_item(&switch.audit_record)?))
+            .item(COMPETITION_KEY, competition_key)
+            .condition_expression("attribute_not_exists(id)")
+            .build()?).build());
 
-        let delete_json_string = json_string.replace("athlete_ids", "delete_athlete_ids");
-        self.update_group(from_group_id, &delete_json_string, false).await?;
+        self.client
+            .transact_write_items()
+            .set_transact_items(Some(transact_items))
+            .send()
+            .await?;
 
         Ok(String::from("Group updated"))
     }
@@ -640,22 +830,18 @@ impl AchievementStorage for DynamoDB {
                 let mut athletes: Vec<Athlete> = Vec::new();
 
                 let all_athletes: Vec<Option<Athlete>> =
-                    match self.client.scan().table_name(std::env::var("DB_NAME_ATHLETE").unwrap_or("athlete_store".to_string())).send().await {
+                    match self.query_competition(std::env::var("DB_NAME_ATHLETE").unwrap_or("athlete_store_v2".to_string())).await {
                         Ok(items) => {
-                            if let Some(items) = items.items {
-                                Some(
-                                    items
-                                        .iter()
-                                        .map(|v| {
-                                            let athlete: Option<Athlete> =
-                                                serde_dynamo::from_item(v.clone()).unwrap_or(None);
-                                            athlete
-                                        })
-                                        .collect(),
-                                )
-                            } else {
-                                None
-                            }
+                            Some(
+                                items
+                                    .iter()
+                                    .map(|v| {
+                                        let athlete: Option<Athlete> =
+                                            serde_dynamo::from_item(v.clone()).unwrap_or(None);
+                                        athlete
+                                    })
+                                    .collect(),
+                            )
                         }
                         Err(e) => {
                             println!("{:?}", e);
@@ -701,10 +887,12 @@ impl AchievementStorage for DynamoDB {
             .athlete_name
             .ok_or("Athlete name not given")?;
 
+        let competition_key = self.write_competition_key().await?;
         let mut update_call = self
             .client
             .update_item()
-            .table_name(std::env::var("DB_NAME_ATHLETE").unwrap_or("athlete_store".to_string()))
+            .table_name(std::env::var("DB_NAME_ATHLETE").unwrap_or("athlete_store_v2".to_string()))
+            .key(COMPETITION_KEY, competition_key)
             .key("athlete_id", AttributeValue::S(athlete_name));
 
         update_call = update_call
@@ -747,9 +935,12 @@ impl AchievementStorage for DynamoDB {
     async fn get_athlete_group(&self, athlete_id: &AthleteID) -> Option<GroupID>{
         let athlete_map: HashMap<String, AttributeValue>  = serde_dynamo::to_item(athlete_id).ok()?;  
         let results = self.client
-            .scan()
-            .table_name(std::env::var("DB_NAME_GROUP").unwrap_or("group_store".to_string()))
+            .query()
+            .table_name(std::env::var("DB_NAME_GROUP").unwrap_or("group_store_v2".to_string()))
+            .key_condition_expression("#competition = :competition")
             .filter_expression("contains (athlete_ids, :m)")
+            .expression_attribute_names("#competition", COMPETITION_KEY)
+            .expression_attribute_values(":competition", self.competition_key().await)
             .expression_attribute_values(":m", AttributeValue::M(athlete_map))
             .send()
             .await;
@@ -780,7 +971,8 @@ impl TimePlanStorage for DynamoDB {
         let item = self
             .client
             .get_item()
-            .table_name(std::env::var("DB_NAME_TIMEGROUP").unwrap_or("time_group_store".to_string()))
+            .table_name(std::env::var("DB_NAME_TIMEGROUP").unwrap_or("time_group_store_v2".to_string()))
+            .key(COMPETITION_KEY, self.competition_key().await)
             .key("name", AttributeValue::S(group_name))
             .send()
             .await;
@@ -795,7 +987,10 @@ impl TimePlanStorage for DynamoDB {
         }
     }
 
-    async fn store_time_plan(&self, time_table: Value) -> Result<String, Box<dyn Error>> {
+    async fn store_time_plan(&self, time_table: Value) -> Result<TimeTableChanges, Box<dyn Error>> {
+        let (lanes, time_zone) = self.get_active_competition().await
+            .map_or((time_planner::DEFAULT_LANES, time_planner::DEFAULT_TIME_ZONE),
+                    |competition| (competition.config.lanes, competition.config.time_zone));
         match time_table {
             Value::Object(time_table_map) => {
                 let date_info = match time_table_map.get("Dates") {
@@ -853,6 +1048,7 @@ impl TimePlanStorage for DynamoDB {
                     self.store_role(login_info).await?;
                 }
 
+                let mut changes = TimeTableChanges::default();
                 match time_table_map.get("Groups") {
                     Some(group_value) => {
                         if let Value::Object(group_map) = group_value {
@@ -882,9 +1078,14 @@ impl TimePlanStorage for DynamoDB {
                                     &date_info,
                                     &discipline_info,
                                     group_athletes,
+                                    lanes,
+                                    time_zone,
                                 )?;
-                                self.store_time_group(group).await?;
+                                let stored = self.get_time_group(&TimeGroupID::from_time_group(&group)).await;
+                                self.store_time_group(changes.merge(stored, group)).await?;
                             }
+                            let stored_groups = self.get_time_groups().await?;
+                            changes.missing_groups(&stored_groups, &group_map.keys().cloned().collect::<Vec<String>>());
                         } else {
                             return Err(Box::from("Group information in invalid format"));
                         }
@@ -892,18 +1093,25 @@ impl TimePlanStorage for DynamoDB {
                     None => return Err(Box::from("Group information not found")),
                 }
 
-                Ok(String::from("Time table stored"))
+                Ok(changes)
             }
             _ => Err(Box::from("Invalid format")),
         }
     }
 
     async fn store_time_group(&self, group: TimeGroup) -> Result<String, Box<dyn Error>> {
+        // Only written if the time group was not changed since it was loaded
+        let version = group.version();
         let item = serde_dynamo::to_item(group)?;
+        let competition_key = self.write_competition_key().await?;
         self.client
             .put_item()
-            .table_name(std::env::var("DB_NAME_TIMEGROUP").unwrap_or("time_group_store".to_string()))
+            .table_name(std::env::var("DB_NAME_TIMEGROUP").unwrap_or("time_group_store_v2".to_string()))
             .set_item(Some(item))
+            .item(COMPETITION_KEY, competition_key)
+            .item("version", AttributeValue::N((version + 1).to_string()))
+            .condition_expression(version_condition(version))
+            .expression_attribute_values(":version", AttributeValue::N(version.to_string()))
             .send()
             .await?;
 
@@ -911,13 +1119,9 @@ impl TimePlanStorage for DynamoDB {
     }
     
     async fn get_all_athlete_states(&self) -> Result<HashMap<String, bool>, Box<dyn Error>>{
-        let items = self
-            .client
-            .scan()
-            .table_name(std::env::var("DB_NAME_ATHLETE").unwrap_or("athlete_store".to_string()))
-            .send()
-            .await;
-        let item_map = items?.items().to_vec();
+        let item_map = self
+            .query_competition(std::env::var("DB_NAME_ATHLETE").unwrap_or("athlete_store_v2".to_string()))
+            .await?;
         let athletes: Vec<Athlete> = serde_dynamo::from_items(item_map)?;
 
         let mut athlete_states: HashMap<String, bool> = HashMap::new();
@@ -927,6 +1131,13 @@ impl TimePlanStorage for DynamoDB {
 
         return Ok(athlete_states);
     }
+
+    async fn get_time_groups(&self) -> Result<Vec<TimeGroup>, Box<dyn Error>> {
+        let item_map = self
+            .query_competition(std::env::var("DB_NAME_TIMEGROUP").unwrap_or("time_group_store_v2".to_string()))
+            .await?;
+        Ok(serde_dynamo::from_items(item_map)?)
+    }
 }
 
 
@@ -938,7 +1149,8 @@ impl NoteStorage for DynamoDB {
         let item = self
             .client
             .get_item()
-            .table_name(std::env::var("DB_NAME_GROUP").unwrap_or("group_store".to_string()))
+            .table_name(std::env::var("DB_NAME_GROUP").unwrap_or("group_store_v2".to_string()))
+            .key(COMPETITION_KEY, self.competition_key().await)
             .key("name", AttributeValue::S(group_name))
             .projection_expression("notes")
             .send()
@@ -962,29 +1174,35 @@ impl NoteStorage for DynamoDB {
         }
     }
 
-    async fn save_note(&self, note_id: NoteID, note: String) -> Result<String, Box<dyn Error>> {
-        let mut update_call = self
-            .client
-            .update_item()
-            .table_name(std::env::var("DB_NAME_GROUP").unwrap_or("group_store".to_string()))
-            .key("name", AttributeValue::S(note_id.group_name.clone()));
-
-        update_call = update_call.expression_attribute_values(
-                String::from(":n"),
-                AttributeValue::S(note)
-        );
-        update_call = update_call.expression_attribute_names(
-                String::from("#noteName"),
-                note_id.discipline
-        );
-        update_call = update_call.update_expression("SET notes.#noteName = :n");
+    async fn get_note_entries(&self, note_id: &NoteID) -> Result<Vec<NoteEntry>, Box<dyn Error>> {
+        // The ids of the entries start with the group and discipline
+        let prefix = format!("{}#{}#", note_id.group_name, note_id.discipline);
+        let items = self.query_competition_prefix(std::env::var("DB_NAME_NOTE").unwrap_or("note_store".to_string()), "id", &prefix).await?;
+        Ok(serde_dynamo::from_items(items)?)
+    }
 
-        update_call.send().await?;
-        Ok(String::from("Note added"))
+    async fn store_note_entry(&self, entry: &NoteEntry) -> Result<String, Box<dyn Error>> {
+        let competition_key = self.write_competition_key().await?;
+        let mut put_call = self
+            .client
+            .put_item()
+            .table_name(std::env::var("DB_NAME_NOTE").unwrap_or("note_store".to_string()))
+            .set_item(Some(serde_dynamo::to_item(entry)?))
+            .item(COMPETITION_KEY, competition_key);
+        // Entries are only added, edits require the stored version the edit is based on
+        put_call = match entry.history.len() {
+            0 => put_call.condition_expression("attribute_not_exists(id)"),
+            revisions => put_call
+                .condition_expression("size(history) = :revisions")
+                .expression_attribute_values(":revisions", AttributeValue::N((revisions - 1).to_string())),
+        };
+        put_call.send().await?;
+        Ok(String::from("Note stored"))
     }
 }
 
 
+/// Logins are shared by all competitions, the authentication table is not scoped to the active one
 #[async_trait]
 impl AuthenticateStorage for DynamoDB {
     async fn get_role_and_group(&self, login_info: LoginInfo) -> Option<Role> {
@@ -1025,7 +1243,8 @@ impl TeamStorage for DynamoDB {
         let item = self
             .client
             .get_item()
-            .table_name(std::env::var("DB_NAME_TEAM").unwrap_or("team_store".to_string()))
+            .table_name(std::env::var("DB_NAME_TEAM").unwrap_or("team_store_v2".to_string()))
+            .key(COMPETITION_KEY, self.competition_key().await)
             .key("team_name", AttributeValue::S(team_name))
             .send()
             .await?;
@@ -1036,13 +1255,9 @@ impl TeamStorage for DynamoDB {
 
     
     async fn get_teams(&self) -> Result<Vec<Team>, Box<dyn Error>> {
-        let items = self
-            .client
-            .scan()
-            .table_name(std::env::var("DB_NAME_TEAM").unwrap_or("team_store".to_string()))
-            .send()
-            .await;
-        let item_map = items?.items().to_vec();
+        let item_map = self
+            .query_competition(std::env::var("DB_NAME_TEAM").unwrap_or("team_store_v2".to_string()))
+            .await?;
         let mut teams: Vec<Team> = serde_dynamo::from_items(item_map)?;
 
         let athlete_ids: Vec<AthleteID> = teams.iter().flat_map(|team| {
@@ -1080,10 +1295,12 @@ impl TeamStorage for DynamoDB {
     async fn save_team(&self, team: &Team) -> Result<String, Box<dyn Error>> {
         if let Some(team_name) = team.team_name.clone() {
             let item = serde_dynamo::to_item(team)?;
+            let competition_key = self.write_competition_key().await?;
             self.client
                 .put_item()
-                .table_name(std::env::var("DB_NAME_TEAM").unwrap_or("team_store".to_string()))
+                .table_name(std::env::var("DB_NAME_TEAM").unwrap_or("team_store_v2".to_string()))
                 .set_item(Some(item))
+                .item(COMPETITION_KEY, competition_key)
                 .item("team_name", AttributeValue::S(team_name))
                 .send()
                 .await?;
@@ -1109,10 +1326,12 @@ impl TeamStorage for DynamoDB {
         };
 
         let item = serde_dynamo::to_item(&team)?;
+        let competition_key = self.write_competition_key().await?;
         self.client
             .put_item()
-            .table_name(std::env::var("DB_NAME_TEAM").unwrap_or("team_store".to_string()))
+            .table_name(std::env::var("DB_NAME_TEAM").unwrap_or("team_store_v2".to_string()))
             .set_item(Some(item))
+            .item(COMPETITION_KEY, competition_key)
             .item("team_name", AttributeValue::S(team_name))
             .send()
             .await?;
@@ -1126,9 +1345,11 @@ impl TeamStorage for DynamoDB {
     }
 
     async fn delete_team(&self, team_id: &TeamID) -> Result<(), Box<dyn Error>> {
+        let competition_key = self.write_competition_key().await?;
         self.client
             .delete_item()
-            .table_name(std::env::var("DB_NAME_TEAM").unwrap_or("team_store".to_string()))
+            .table_name(std::env::var("DB_NAME_TEAM").unwrap_or("team_store_v2".to_string()))
+            .key(COMPETITION_KEY, competition_key)
             .key("team_name", AttributeValue::S(team_id.name.clone()))
             .send()
             .await?;
@@ -1137,6 +1358,437 @@ impl TeamStorage for DynamoDB {
 
 }
 
+#[async_trait]
+impl CompetitionStorage for DynamoDB {
+    async fn get_competitions(&self) -> Result<Vec<Competition>, Box<dyn Error>> {
+        let items = self
+            .client
+            .scan()
+            .table_name(std::env::var("DB_NAME_COMPETITION").unwrap_or("competition_store".to_string()))
+            .send()
+            .await?;
+        let competitions: Vec<Competition> = serde_dynamo::from_items(items.items().to_vec())?;
+        Ok(competitions)
+    }
+
+    async fn get_competition(&self, competition_id: &CompetitionID) -> Option<Competition> {
+        let item = self
+            .client
+            .get_item()
+            .table_name(std::env::var("DB_NAME_COMPETITION").unwrap_or("competition_store".to_string()))
+            .key("number", AttributeValue::S(competition_id.number.clone()))
+            .send()
+            .await;
+
+        match item {
+            Ok(item) => {
+                let item_map = item.item()?;
+                let competition: Competition = serde_dynamo::from_item(item_map.clone()).unwrap_or(None)?;
+                Some(competition)
+            }
+            Err(_) => None,
+        }
+    }
+
+    async fn store_competition(&self, competition: Competition) -> Result<String, Box<dyn Error>> {
+        let item = serde_dynamo::to_item(competition)?;
+        self.client
+            .put_item()
+            .table_name(std::env::var("DB_NAME_COMPETITION").unwrap_or("competition_store".to_string()))
+            .set_item(Some(item))
+            .send()
+            .await?;
+        Ok(String::from("Competition stored"))
+    }
+
+    async fn get_active_competition(&self) -> Option<Competition> {
+        let number = match self.competition_key().await {
+            AttributeValue::S(number) => number,
+            _ => return None,
+        };
+        self.get_competition(&CompetitionID { number }).await
+    }
+
+    async fn set_active_competition(&self, competition_id: &CompetitionID) -> Result<String, Box<dyn Error>> {
+        let mut competition = self
+            .get_competition(competition_id)
+            .await
+            .ok_or(ItemNotFound::new("Competition not found", "404"))?;
+
+        if let Some(mut previous) = self.get_active_competition().await {
+            if previous.number != competition.number {
+                previous.active = false;
+                self.store_competition(previous).await?;
+            }
+        }
+        competition.active = true;
+        self.store_competition(competition).await?;
+
+        if let CompetitionScope::Active(cache) = &self.competition {
+            *cache.write().expect("RwLock poisoned") = Some((competition_id.number.clone(), Instant::now()));
+        }
+        Ok(String::from("Active competition changed"))
+    }
+
+    async fn get_athlete_editions(&self, athlete_ids: &[AthleteID]) -> Result<HashMap<AthleteID, Vec<(Competition, Athlete)>>, Box<dyn Error>> {
+        let athlete_table = std::env::var("DB_NAME_ATHLETE").unwrap_or("athlete_store_v2".to_string());
+        let mut editions: HashMap<AthleteID, Vec<(Competition, Athlete)>> = HashMap::new();
+
+        let competitions = self.get_competitions().await?;
+        for competition in competitions {
+            // batch_get_item is limited to 100 keys per request
+            for athlete_id_chunk in athlete_ids.chunks(100) {
+                let mut athlete_keys = KeysAndAttributes::builder();
+                for athlete_id in athlete_id_chunk {
+                    athlete_keys = athlete_keys.keys(HashMap::from([
+                        (COMPETITION_KEY.to_string(), AttributeValue::S(competition.number.clone())),
+                        ("athlete_id".to_string(), AttributeValue::S(athlete_id.full_name())),
+                    ]));
+                }
+                // Keys which were not processed (e.g. because of throttling) are requested again
+                let mut request_items = Some(HashMap::from([(athlete_table.clone(), athlete_keys.build()?)]));
+                let mut backoff = std::time::Duration::from_millis(50);
+                while let Some(items) = request_items.take().filter(|items| !items.is_empty()) {
+                    let result = self.client
+                        .batch_get_item()
+                        .set_request_items(Some(items))
+                        .send()
+                        .await?;
+
+                    let athlete_items = result
+                        .responses()
+                        .and_then(|responses| responses.get(athlete_table.as_str()))
+                        .cloned()
+                        .unwrap_or_default();
+                    for athlete_item in athlete_items {
+                        let athlete: Athlete = serde_dynamo::from_item(athlete_item)?;
+                        editions
+                            .entry(AthleteID::from_athlete(&athlete))
+                            .or_default()
+                            .push((competition.clone(), athlete));
+                    }
+                    request_items = result.unprocessed_keys;
+                    if request_items.as_ref().is_some_and(|items| !items.is_empty()) {
+                        actix_rt::time::sleep(backoff).await;
+                        backoff = (backoff * 2).min(std::time::Duration::from_secs(2));
+                    }
+                }
+            }
+        }
+
+        Ok(editions)
+    }
+}
+
+#[async_trait]
+impl WaitlistStorage for DynamoDB {
+    async fn get_waitlist(&self, competition_type: &CompetitionType) -> Result<Waitlist, Box<dyn Error>> {
+        let item = self
+            .client
+            .get_item()
+            .table_name(std::env::var("DB_NAME_WAITLIST").unwrap_or("waitlist_store".to_string()))
+            .key(COMPETITION_KEY, self.competition_key().await)
+            .key("competition_type", AttributeValue::S(competition_type.to_string()))
+            .send()
+            .await?;
+
+        match item.item() {
+            Some(item_map) => Ok(serde_dynamo::from_item(item_map.clone())?),
+            None => Ok(Waitlist::new(competition_type.clone())),
+        }
+    }
+
+    async fn store_waitlist(&self, waitlist: &Waitlist) -> Result<String, Box<dyn Error>> {
+        let item = serde_dynamo::to_item(waitlist)?;
+        let competition_key = self.write_competition_key().await?;
+        self.client
+            .put_item()
+            .table_name(std::env::var("DB_NAME_WAITLIST").unwrap_or("waitlist_store".to_string()))
+            .set_item(Some(item))
+            .item(COMPETITION_KEY, competition_key)
+            .item("competition_type", AttributeValue::S(waitlist.competition_type.to_string()))
+            .send()
+            .await?;
+        Ok(String::from("Waitlist stored"))
+    }
+}
+
+#[async_trait]
+impl OfficialStorage for DynamoDB {
+    async fn get_officials(&self) -> Result<Vec<Official>, Box<dyn Error>> {
+        let items = self.query_competition(std::env::var("DB_NAME_OFFICIAL").unwrap_or("official_store".to_string())).await?;
+        Ok(serde_dynamo::from_items(items)?)
+    }
+
+    async fn get_official(&self, official_id: &OfficialID) -> Option<Official> {
+        let item = self
+            .client
+            .get_item()
+            .table_name(std::env::var("DB_NAME_OFFICIAL").unwrap_or("official_store".to_string()))
+            .key(COMPETITION_KEY, self.competition_key().await)
+            .key("name", AttributeValue::S(official_id.name.clone()))
+            .send()
+            .await
+            .ok()?;
+        serde_dynamo::from_item(item.item()?.clone()).ok()
+    }
+
+    async fn store_official(&self, official: &Official) -> Result<String, Box<dyn Error>> {
+        let item = serde_dynamo::to_item(official)?;
+        let competition_key = self.write_competition_key().await?;
+        self.client
+            .put_item()
+            .table_name(std::env::var("DB_NAME_OFFICIAL").unwrap_or("official_store".to_string()))
+            .set_item(Some(item))
+            .item(COMPETITION_KEY, competition_key)
+            .send()
+            .await?;
+        Ok(String::from("Official stored"))
+    }
+
+    async fn delete_official(&self, official_id: &OfficialID) -> Result<(), Box<dyn Error>> {
+        let competition_key = self.write_competition_key().await?;
+        self.client
+            .delete_item()
+            .table_name(std::env::var("DB_NAME_OFFICIAL").unwrap_or("official_store".to_string()))
+            .key(COMPETITION_KEY, competition_key)
+            .key("name", AttributeValue::S(official_id.name.clone()))
+            .send()
+            .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AuditStorage for DynamoDB {
+    async fn get_audit_records(&self) -> Result<Vec<AuditRecord>, Box<dyn Error>> {
+        let items = self.query_competition(std::env::var("DB_NAME_AUDIT").unwrap_or("audit_store".to_string())).await?;
+        let mut records: Vec<AuditRecord> = serde_dynamo::from_items(items)?;
+        records.sort_by(|record, other| record.id.cmp(&other.id));
+        Ok(records)
+    }
+
+    async fn get_athlete_audit_records(&self, athlete_id: &AthleteID) -> Result<Vec<AuditRecord>, Box<dyn Error>> {
+        let mut items = vec![];
+        let mut last_evaluated_key = None;
+        loop {
+            let result = self
+                .client
+                .query()
+                .table_name(std::env::var("DB_NAME_AUDIT").unwrap_or("audit_store".to_string()))
+                .key_condition_expression("#competition = :competition")
+                .filter_expression("contains(athletes, :athlete)")
+                .expression_attribute_names("#competition", COMPETITION_KEY)
+                .expression_attribute_values(":competition", self.competition_key().await)
+                .expression_attribute_values(":athlete", AttributeValue::S(athlete_id.full_name()))
+                .set_exclusive_start_key(last_evaluated_key)
+                .send()
+                .await?;
+            items.extend(result.items().to_vec());
+            last_evaluated_key = result.last_evaluated_key;
+            if last_evaluated_key.is_none() {
+                break;
+            }
+        }
+        let mut records: Vec<AuditRecord> = serde_dynamo::from_items(items)?;
+        records.sort_by(|record, other| record.id.cmp(&other.id));
+        Ok(records)
+    }
+
+    async fn store_audit_record(&self, record: &AuditRecord) -> Result<String, Box<dyn Error>> {
+        let item = audit_item(record)?;
+        let competition_key = self.write_competition_key().await?;
+        self.client
+            .put_item()
+            .table_name(std::env::var("DB_NAME_AUDIT").unwrap_or("audit_store".to_string()))
+            .set_item(Some(item))
+            .item(COMPETITION_KEY, competition_key)
+            .condition_expression("attribute_not_exists(id)")
+            .send()
+            .await?;
+        Ok(String::from("Audit record stored"))
+    }
+
+    async fn store_result_change(&self, achievement_id: &AchievementID, new: Option<&Achievement>, record: &AuditRecord) -> Result<String, Box<dyn Error>> {
+        let athlete_name = achievement_id.athlete_name.clone().ok_or("Athlete name not given")?;
+        let competition_key = self.write_competition_key().await?;
+
+        // The athlete has to exist, a new result must not overwrite a result entered in the meantime
+        let is_new = matches!(&record.action, AuditAction::ResultChange { old: None, .. });
+        let mut update = Update::builder()
+            .table_name(std::env::var("DB_NAME_ATHLETE").unwrap_or("athlete_store_v2".to_string()))
+            .key(COMPETITION_KEY, competition_key.clone())
+            .key("athlete_id", AttributeValue::S(athlete_name))
+            .expression_attribute_names("#achievement_name", achievement_id.name.clone())
+            .condition_expression(match is_new {
+                true => "attribute_exists(athlete_id) AND attribute_not_exists(achievements.#achievement_name)",
+                false => "attribute_exists(athlete_id)",
+            });
+        update = match new {
+            Some(achievement) => update
+                .update_expression("SET achievements.#achievement_name = :a")
+                .expression_attribute_values(":a", AttributeValue::M(serde_dynamo::to_item(achievement)?)),
+            None => update.update_expression("REMOVE achievements.#achievement_name"),
+        };
+        let audit_item = audit_item(record)?;
+
+        self.client
+            .transact_write_items()
+            .transact_items(TransactWriteItem::builder().update(update.build()?).build())
+            .transact_items(TransactWriteItem::builder().put(Put::builder()
+                .table_name(std::env::var("DB_NAME_AUDIT").unwrap_or("audit_store".to_string()))
+                .set_item(Some(audit_item))
+                .item(COMPETITION_KEY, competition_key)
+                .condition_expression("attribute_not_exists(id)")
+                .build()?).build())
+            .send()
+            .await?;
+        Ok(String::from("Result stored"))
+    }
+}
+
+#[async_trait]
+impl PrivacyStorage for DynamoDB {
+    async fn get_athlete_records(&self, athlete_id: &AthleteID) -> Result<Vec<AthleteRecord>, Box<dyn Error>> {
+        let mut editions = self.get_athlete_editions(std::slice::from_ref(athlete_id)).await?;
+
+        let mut records = vec![];
+        for (competition, athlete) in editions.remove(athlete_id).unwrap_or_default() {
+            let store = self.for_competition(&competition.number);
+            let group = store.get_athlete_group(athlete_id).await.and_then(|group_id| group_id.name);
+            let teams: Vec<Team> = serde_dynamo::from_items(
+                store.query_competition(std::env::var("DB_NAME_TEAM").unwrap_or("team_store_v2".to_string())).await?
+            )?;
+            let teams = teams
+                .into_iter()
+                .filter(|team| team.athletes.as_ref().is_some_and(|athletes| athletes.contains(&athlete.athlete_id())))
+                .filter_map(|team| team.team_name)
+                .collect();
+            let audit_records = store
+                .get_athlete_audit_records(athlete_id)
+                .await?
+                .into_iter()
+                .map(|record| record.for_athlete(athlete_id))
+                .collect();
+            let notes: Vec<NoteEntry> = serde_dynamo::from_items(
+                store.query_competition(std::env::var("DB_NAME_NOTE").unwrap_or("note_store".to_string())).await?
+            )?;
+            let notes = notes.into_iter().filter(|entry| entry.concerns(athlete_id)).collect();
+            records.push(AthleteRecord { competition, athlete, group, teams, audit_records, notes });
+        }
+        Ok(records)
+    }
+
+    async fn anonymize_competition(&self, competition_id: &CompetitionID) -> Result<usize, Box<dyn Error>> {
+        match self.get_competition(competition_id).await {
+            None => return Err(Box::new(ItemNotFound::new("Competition not found", "404"))),
+            Some(competition) if competition.active => return Err(Box::from("The active competition can not be anonymized")),
+            Some(_) => {}
+        }
+        let athlete_table = std::env::var("DB_NAME_ATHLETE").unwrap_or("athlete_store_v2".to_string());
+        let store = self.for_competition(&competition_id.number);
+
+        let athletes: Vec<Athlete> = serde_dynamo::from_items(store.query_competition(athlete_table.clone()).await?)?;
+        let pseudonyms = assign_pseudonyms(&athletes);
+        let new_names: HashMap<String, (String, String)> = pseudonyms
+            .iter()
+            .map(|(athlete_id, pseudonym)| (athlete_id.full_name(), (pseudonym.name(), pseudonym.surname())))
+            .collect();
+
+        // Store anonymized athletes first, so groups and teams never link to missing athletes
+        for mut athlete in athletes {
+            if let Some(pseudonym) = pseudonyms.get(&AthleteID::from_athlete(&athlete)) {
+                athlete.anonymize(&pseudonym.name(), &pseudonym.surname());
+                store.write_athlete(pseudonym.clone(), athlete).await?;
+            }
+        }
+
+        let group_stores: Vec<GroupStore> = serde_dynamo::from_items(
+            store.query_competition(std::env::var("DB_NAME_GROUP").unwrap_or("group_store_v2".to_string())).await?
+        )?;
+        for mut group_store in group_stores {
+            group_store.athlete_ids = group_store.athlete_ids
+                .into_iter()
+                .map(|athlete_id| pseudonyms.get(&athlete_id).cloned().unwrap_or(athlete_id))
+                .collect();
+            group_store.notes.values_mut().for_each(|note| *note = pseudonymize_text(note, &pseudonyms));
+            store.write_group_store(GroupID::from_group_store(&group_store), group_store).await?;
+        }
+
+        let time_groups: Vec<TimeGroup> = serde_dynamo::from_items(
+            store.query_competition(std::env::var("DB_NAME_TIMEGROUP").unwrap_or("time_group_store_v2".to_string())).await?
+        )?;
+        for mut time_group in time_groups {
+            time_group.rename_athletes(&new_names);
+            store.store_time_group(time_group).await?;
+        }
+
+        let teams: Vec<Team> = serde_dynamo::from_items(
+            store.query_competition(std::env::var("DB_NAME_TEAM").unwrap_or("team_store_v2".to_string())).await?
+        )?;
+        for mut team in teams {
+            if let Some(athletes) = &mut team.athletes {
+                for athlete in athletes.iter_mut() {
+                    if let Some((name, surname)) = new_names.get(athlete) {
+                        *athlete = format!("{}_{}", name, surname);
+                    }
+                }
+            }
+            store.save_team(&team).await?;
+        }
+
+        let waitlists: Vec<Waitlist> = serde_dynamo::from_items(
+            store.query_competition(std::env::var("DB_NAME_WAITLIST").unwrap_or("waitlist_store".to_string())).await?
+        )?;
+        for mut waitlist in waitlists {
+            waitlist.rename_athletes(|athlete_id| pseudonym(&pseudonyms, athlete_id));
+            store.store_waitlist(&waitlist).await?;
+        }
+
+        // Audit records are overwritten in place, their id stays the same
+        let audit_table = std::env::var("DB_NAME_AUDIT").unwrap_or("audit_store".to_string());
+        let audit_records: Vec<AuditRecord> = serde_dynamo::from_items(store.query_competition(audit_table.clone()).await?)?;
+        for mut record in audit_records {
+            record.action.rename_athletes(|athlete_id| pseudonym(&pseudonyms, athlete_id));
+            let item = audit_item(&record)?;
+            store.client
+                .put_item()
+                .table_name(audit_table.clone())
+                .set_item(Some(item))
+                .item(COMPETITION_KEY, store.competition_key().await)
+                .send()
+                .await?;
+        }
+
+        // Notes are overwritten in place as well, without adding a revision
+        let note_table = std::env::var("DB_NAME_NOTE").unwrap_or("note_store".to_string());
+        let notes: Vec<NoteEntry> = serde_dynamo::from_items(store.query_competition(note_table.clone()).await?)?;
+        for mut entry in notes {
+            entry.anonymize(&pseudonyms);
+            let item = serde_dynamo::to_item(&entry)?;
+            store.client
+                .put_item()
+                .table_name(note_table.clone())
+                .set_item(Some(item))
+                .item(COMPETITION_KEY, store.competition_key().await)
+                .send()
+                .await?;
+        }
+
+        for athlete_id in pseudonyms.keys() {
+            store.client
+                .delete_item()
+                .table_name(athlete_table.clone())
+                .key(COMPETITION_KEY, store.competition_key().await)
+                .key("athlete_id", AttributeValue::S(athlete_id.full_name()))
+                .send()
+                .await?;
+        }
+
+        Ok(pseudonyms.len())
+    }
+}
+
 impl Storage for DynamoDB {
     fn serialize(&self) {}
 
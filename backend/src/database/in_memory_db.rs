@@ -1,6 +1,9 @@
+use crate::audit::{AuditAction, AuditRecord, AuditStorage};
 use crate::authenticate::{AuthenticateStorage, LoginInfo, Role};
-use crate::certificate_generation::{Achievement, AchievementID, AgeGroup, AgeGroupID, AgeGroupSelector, Athlete, AthleteID, Group, GroupID, GroupStore, SwitchGroupID};
-use crate::notes::{NoteID, NoteStorage};
+use crate::competitions::{Competition, CompetitionID, CompetitionStorage};
+use crate::certificate_generation::{Achievement, AchievementID, AgeGroup, AgeGroupID, AgeGroupSelector, Athlete, AthleteID, AthleteUpdate, CompetitionType, Group, GroupID, GroupStore, GroupSwitch, SwitchGroupID};
+use crate::notes::{NoteEntry, NoteID, NoteStorage};
+use crate::privacy::{assign_pseudonyms, pseudonym, pseudonymize_text, AthleteRecord, PrivacyStorage};
 use crate::teams::{TeamStorage, Team, TeamID};
 use std::collections::HashMap;
 use std::error::Error;
@@ -16,13 +19,22 @@ use bincode;
 
 use crate::{AchievementStorage, Storage, time_planner};
 use crate::database::db_errors::ItemNotFound;
-use crate::time_planner::{TimeGroup, TimeGroupID, TimePlanStorage};
+use crate::time_planner::{TimeGroup, TimeGroupID, TimePlanStorage, TimeTableChanges};
+use crate::waitlist::{Waitlist, WaitlistStorage};
+use crate::officials::{Official, OfficialID, OfficialStorage};
 
+/// Competitions are shared, all other stores hold the data of each competition keyed by its number (like the
+/// competition key in DynamoDB) and only show the data of the active competition
 #[derive(Debug, Serialize, Deserialize)]
 pub struct InMemoryDB {
-    athlete_store: Mutex<HashMap<AthleteID, Athlete>>,
-    group_store: Mutex<HashMap<GroupID, GroupStore>>,
-    time_group_store: Mutex<HashMap<TimeGroupID, TimeGroup>>,
+    athlete_store: Mutex<HashMap<String, HashMap<AthleteID, Athlete>>>,
+    group_store: Mutex<HashMap<String, HashMap<GroupID, GroupStore>>>,
+    time_group_store: Mutex<HashMap<String, HashMap<TimeGroupID, TimeGroup>>>,
+    competition_store: Mutex<HashMap<CompetitionID, Competition>>,
+    waitlist_store: Mutex<HashMap<String, HashMap<CompetitionType, Waitlist>>>,
+    audit_store: Mutex<HashMap<String, Vec<AuditRecord>>>,
+    official_store: Mutex<HashMap<String, HashMap<OfficialID, Official>>>,
+    note_store: Mutex<HashMap<String, HashMap<String, NoteEntry>>>,
 }
 
 unsafe impl Send for InMemoryDB {}
@@ -35,30 +47,48 @@ impl InMemoryDB {
             athlete_store: Mutex::new(HashMap::new()),
             group_store: Mutex::new(HashMap::new()),
             time_group_store: Mutex::new(HashMap::new()),
+            competition_store: Mutex::new(HashMap::new()),
+            waitlist_store: Mutex::new(HashMap::new()),
+            audit_store: Mutex::new(HashMap::new()),
+            official_store: Mutex::new(HashMap::new()),
+            note_store: Mutex::new(HashMap::new()),
         }
     }
 
-    fn select_age_group_athletes(&self, age_group_selector: AgeGroupSelector) -> Vec<Athlete> {
-        let mut result: Vec<Athlete> = Vec::new();
+    /// Number of the active competition, data stored without an active competition belongs to "default"
+    fn competition_key(&self) -> String {
+        self.competition_store.lock().expect("Mutex Lox poised")
+            .values()
+            .find(|competition| competition.active)
+            .map_or(String::from("default"), |competition| competition.number.clone())
+    }
 
-        for athlete in self.athlete_store.lock().expect("Mutex Lock posied").values() {
-            if age_group_selector == *athlete {
-                result.push(athlete.clone());
-            }
-        }
+    /// Run the closure on the data of the active competition in the store
+    fn scoped<T: Default, R>(&self, store: &Mutex<HashMap<String, T>>, f: impl FnOnce(&mut T) -> R) -> R {
+        let competition_key = self.competition_key();
+        let mut store = store.lock().expect("Mutex Lox poised");
+        f(store.entry(competition_key).or_default())
+    }
 
-        result
+    fn select_age_group_athletes(&self, age_group_selector: AgeGroupSelector) -> Vec<Athlete> {
+        self.scoped(&self.athlete_store, |athletes| {
+            athletes
+                .values()
+                .filter(|athlete| age_group_selector == **athlete)
+                .cloned()
+                .collect()
+        })
     }
 }
 
 #[async_trait]
 impl AchievementStorage for InMemoryDB {
     async fn get_athlete(&self, athlete_id: &AthleteID) -> Option<Athlete> {
-        self.athlete_store.lock().expect("Mutex Lox poised").get(athlete_id).cloned()
+        self.scoped(&self.athlete_store, |athletes| athletes.get(athlete_id).cloned())
     }
 
     async fn get_athletes(&self) -> HashMap<String, Vec<Athlete>> {
-        let group_ids: Vec<GroupID> = self.group_store.lock().expect("Mutex Lox poised").keys().cloned().collect();
+        let group_ids: Vec<GroupID> = self.scoped(&self.group_store, |groups| groups.keys().cloned().collect());
 
         let mut groups = HashMap::new();
 
@@ -74,8 +104,12 @@ impl AchievementStorage for InMemoryDB {
         groups
     }
 
+    async fn get_all_athletes(&self) -> Result<Vec<Athlete>, Box<dyn Error>> {
+        Ok(self.scoped(&self.athlete_store, |athletes| athletes.values().cloned().collect()))
+    }
+
     async fn write_athlete(&self, athlete_id: AthleteID, athlete: Athlete) -> Result<String, Box<dyn Error>> {
-        match self.athlete_store.lock().expect("Mutex Lox poised").insert(athlete_id, athlete) {
+        match self.scoped(&self.athlete_store, |athletes| athletes.insert(athlete_id, athlete)) {
             Some(_) => Ok(String::from("Old athlete overwritten")),
             None => Ok(String::from("New athlete inserted"))
         }
@@ -85,16 +119,18 @@ impl AchievementStorage for InMemoryDB {
         todo!("Implement method")
     }
 
-    async fn update_athlete(&self, athlete_id: AthleteID, json_string: &str) -> Result<String, Box<dyn Error>> {
+    async fn update_athlete(&self, athlete_id: AthleteID, json_string: &str) -> Result<AthleteUpdate, Box<dyn Error>> {
         // TODO: Implement check if key is updated and then update key also
         let mut athlete = self.get_athlete(&athlete_id).await.ok_or(ItemNotFound::new("Key not found", "404"))?;
+        let was_deregistered = athlete.is_deregistered();
         athlete.update_values(json_string)?;
+        let result = AthleteUpdate::from_registration(was_deregistered, athlete.is_deregistered());
         self.write_athlete(athlete_id, athlete).await?;
-        Ok(String::from("Athlete updated"))
+        Ok(result)
     }
 
     async fn get_group(&self, group_id: &GroupID) -> Option<Group> {
-        let group_store = self.group_store.lock().expect("Mutex Lox poised").get(group_id).cloned();
+        let group_store = self.scoped(&self.group_store, |groups| groups.get(group_id).cloned());
         match group_store {
             Some(group_store) => {
                 let mut athletes = Vec::new();
@@ -108,36 +144,35 @@ impl AchievementStorage for InMemoryDB {
                     }
                 }
 
-                Some(Group::new(group_store.name.as_str(), athletes, group_store.competition_type))
+                let mut group = Group::new(group_store.name.as_str(), athletes, group_store.competition_type);
+                group.set_capacity(group_store.capacity);
+                Some(group)
             }
             None => None
         }
     }
 
+    async fn get_group_store(&self, group_id: &GroupID) -> Option<GroupStore> {
+        self.scoped(&self.group_store, |groups| groups.get(group_id).cloned())
+    }
+
     async fn write_group_store(&self, group_id: GroupID, group: GroupStore) -> Result<String, Box<dyn Error>> {
         {
             // Check if all athletes exists
             for athlete_id in &group.athlete_ids {
-                if !self.athlete_store.lock().expect("Mutex Lock posed").contains_key(athlete_id) {
+                if !self.scoped(&self.athlete_store, |athletes| athletes.contains_key(athlete_id)) {
                     return Err(Box::from(format!("Athlete with ID {:?} not found", athlete_id)));
                 }
             }
         }
-        match self.group_store.lock().expect("Mutex Lock poised").insert(group_id, group) {
+        match self.scoped(&self.group_store, |groups| groups.insert(group_id, group)) {
             Some(_) => Ok(String::from("Old group overwritten")),
             None => Ok(String::from("New group inserted"))
         }
     }
 
     async fn write_group(&self, group_id: GroupID, group: Group) -> Result<String, Box<dyn Error>> {
-        let group_store = GroupStore {
-            name: group.name().to_string(),
-            athlete_ids: group.athlete_ids(),
-            competition_type: group.competition_type(),
-            notes: group.notes().clone(),
-        };
-
-        self.write_group_store(group_id, group_store).await
+        self.write_group_store(group_id, GroupStore::from_group(&group, 0)).await
     }
 
     async fn update_group(&self, group_id: GroupID, json_string: &str, only_time_group: bool) -> Result<String, Box<dyn Error>> {
@@ -175,7 +210,16 @@ impl AchievementStorage for InMemoryDB {
         }
 
 
+        // A capacity of null removes the limit
+        let capacity = json_value.get("capacity");
+        if let Some(capacity) = capacity {
+            group.set_capacity(serde_json::from_value(capacity.clone())?);
+        }
+
         let all_athletes = group.athletes().clone();
+        if all_athletes.len() > old_athletes.len() || capacity.is_some() {
+            group.check_capacity()?;
+        }
         let group_name = group.name().to_string();
         self.write_group(group_id, group).await?;
         let num_new_athletes = all_athletes.len() - old_athletes.len();
@@ -202,7 +246,31 @@ impl AchievementStorage for InMemoryDB {
     }
 
     async fn switch_group(&self, group_info: SwitchGroupID, json_string: &str) -> Result<String, Box<dyn Error>> {
-        todo!("Implement")
+        let switch = GroupSwitch::load(self, group_info, json_string).await?;
+
+        // Hold all locks while writing, so no other request sees a partial switch
+        let competition_key = self.competition_key();
+        let mut group_store = self.group_store.lock().expect("Mutex Lox poised");
+        let mut time_group_store = self.time_group_store.lock().expect("Mutex Lox poised");
+        let mut audit_store = self.audit_store.lock().expect("Mutex Lox poised");
+        let groups = group_store.entry(competition_key.clone()).or_default();
+        let time_groups = time_group_store.entry(competition_key.clone()).or_default();
+        let audit_records = audit_store.entry(competition_key).or_default();
+        let still_in_group = groups
+            .get(&GroupID::from_group_store(&switch.from))
+            .is_some_and(|from| switch.athlete_ids.iter().all(|athlete_id| from.athlete_ids.contains(athlete_id)));
+        if !still_in_group {
+            return Err(Box::from("Group changed during the switch"));
+        }
+
+        for time_group in [switch.from_time_group, switch.to_time_group].into_iter().flatten() {
+            time_groups.insert(TimeGroupID::new(time_group.name().to_string()), time_group);
+        }
+        groups.insert(GroupID::from_group_store(&switch.from), switch.from);
+        groups.insert(GroupID::from_group_store(&switch.to), switch.to);
+        audit_records.push(switch.audit_record);
+
+        Ok(String::from("Group updated"))
     }
 
 
@@ -255,7 +323,10 @@ impl AchievementStorage for InMemoryDB {
     }
 
     async fn get_athlete_group(&self, athlete_id: &AthleteID) -> Option<GroupID>{
-        !todo!("Implement method to get athlete group")
+        self.scoped(&self.group_store, |groups| groups
+            .values()
+            .find(|group_store| group_store.athlete_ids.contains(athlete_id))
+            .map(|group_store| GroupID::new(&group_store.name)))
     }
 
 }
@@ -263,10 +334,13 @@ impl AchievementStorage for InMemoryDB {
 #[async_trait]
 impl TimePlanStorage for InMemoryDB {
     async fn get_time_group(&self, group_id: &TimeGroupID) -> Option<TimeGroup> {
-        self.time_group_store.lock().expect("Mutex Lox poised").get(group_id).cloned()
+        self.scoped(&self.time_group_store, |time_groups| time_groups.get(group_id).cloned())
     }
 
-    async fn store_time_plan(&self, time_table: Value) -> Result<String, Box<dyn Error>> {
+    async fn store_time_plan(&self, time_table: Value) -> Result<TimeTableChanges, Box<dyn Error>> {
+        let (lanes, time_zone) = self.get_active_competition().await
+            .map_or((time_planner::DEFAULT_LANES, time_planner::DEFAULT_TIME_ZONE),
+                    |competition| (competition.config.lanes, competition.config.time_zone));
         match time_table {
             Value::Object(time_table_map) => {
                 let date_info = match time_table_map.get("Dates") {
@@ -291,6 +365,7 @@ impl TimePlanStorage for InMemoryDB {
                     None => return Err(Box::from("Discipline information not found"))
                 };
 
+                let mut changes = TimeTableChanges::default();
                 match time_table_map.get("Groups") {
                     Some(group_value) => {
                         if let Value::Object(group_map) = group_value {
@@ -309,10 +384,15 @@ impl TimePlanStorage for InMemoryDB {
                                     }
                                 }
 
-                                let group = TimeGroup::build(group_name, times, &date_info, &discipline_info, group_athletes)?;
-                                self.time_group_store.lock().expect("Mutex Lox poised")
-                                    .insert(TimeGroupID::from_time_group(&group), group);
+                                let group = TimeGroup::build(group_name, times, &date_info, &discipline_info, group_athletes, lanes, time_zone)?;
+                                let stored = self.get_time_group(&TimeGroupID::from_time_group(&group)).await;
+                                let group = changes.merge(stored, group);
+                                self.scoped(&self.time_group_store, |time_groups| {
+                                    time_groups.insert(TimeGroupID::from_time_group(&group), group)
+                                });
                             }
+                            let stored_groups = self.get_time_groups().await?;
+                            changes.missing_groups(&stored_groups, &group_map.keys().cloned().collect::<Vec<String>>());
                         } else {
                             return Err(Box::from("Group information in invalid format"));
                         }
@@ -320,19 +400,21 @@ impl TimePlanStorage for InMemoryDB {
                     None => return Err(Box::from("Group information not found"))
                 }
 
-                Ok(String::from("Time table stored"))
+                Ok(changes)
             }
             _ => Err(Box::from("Invalid format"))
         }
     }
     async fn store_time_group(&self, group: TimeGroup) -> Result<String, Box<dyn Error>> {
-        self.time_group_store.lock().expect("Mutex Lox poised")
-            .insert(TimeGroupID::from_time_group(&group), group);
+        self.scoped(&self.time_group_store, |time_groups| time_groups.insert(TimeGroupID::from_time_group(&group), group));
         Ok(String::from("New group stored"))
     }
     async fn get_all_athlete_states(&self) -> Result<HashMap<String, bool>, Box<dyn Error>>{
         !todo!("Implement method to get all athlete states")
     }
+    async fn get_time_groups(&self) -> Result<Vec<TimeGroup>, Box<dyn Error>> {
+        Ok(self.scoped(&self.time_group_store, |time_groups| time_groups.values().cloned().collect()))
+    }
 }
 
 #[async_trait]
@@ -352,8 +434,21 @@ impl NoteStorage for InMemoryDB {
         todo!("Implement")
     }
 
-    async fn save_note(&self, note_id: NoteID, note: String) -> Result<String, Box<dyn Error>> {
-        todo!("Implement")
+    async fn get_note_entries(&self, note_id: &NoteID) -> Result<Vec<NoteEntry>, Box<dyn Error>> {
+        Ok(self.scoped(&self.note_store, |notes| {
+            notes.values().filter(|entry| entry.note_id() == *note_id).cloned().collect()
+        }))
+    }
+
+    async fn store_note_entry(&self, entry: &NoteEntry) -> Result<String, Box<dyn Error>> {
+        self.scoped(&self.note_store, |notes| {
+            let stored_revisions = notes.get(&entry.id).map(|stored| stored.history.len() + 1);
+            if stored_revisions.unwrap_or(0) != entry.history.len() {
+                return Err(Box::from("Note was changed in the meantime"));
+            }
+            notes.insert(entry.id.clone(), entry.clone());
+            Ok(String::from("Note stored"))
+        })
     }
 }
 
@@ -376,6 +471,218 @@ impl TeamStorage for InMemoryDB {
     }
 }
 
+#[async_trait]
+impl CompetitionStorage for InMemoryDB {
+    async fn get_competitions(&self) -> Result<Vec<Competition>, Box<dyn Error>> {
+        Ok(self.competition_store.lock().expect("Mutex Lox poised").values().cloned().collect())
+    }
+
+    async fn get_competition(&self, competition_id: &CompetitionID) -> Option<Competition> {
+        self.competition_store.lock().expect("Mutex Lox poised").get(competition_id).cloned()
+    }
+
+    async fn store_competition(&self, competition: Competition) -> Result<String, Box<dyn Error>> {
+        let competition_id = CompetitionID { number: competition.number.clone() };
+        self.competition_store.lock().expect("Mutex Lox poised").insert(competition_id, competition);
+        Ok(String::from("Competition stored"))
+    }
+
+    async fn get_active_competition(&self) -> Option<Competition> {
+        self.competition_store.lock().expect("Mutex Lox poised")
+            .values()
+            .find(|competition| competition.active)
+            .cloned()
+    }
+
+    async fn set_active_competition(&self, competition_id: &CompetitionID) -> Result<String, Box<dyn Error>> {
+        let mut competition_store = self.competition_store.lock().expect("Mutex Lox poised");
+        if !competition_store.contains_key(competition_id) {
+            return Err(Box::new(ItemNotFound::new("Competition not found", "404")));
+        }
+        for (id, competition) in competition_store.iter_mut() {
+            competition.active = id == competition_id;
+        }
+        Ok(String::from("Active competition changed"))
+    }
+
+    async fn get_athlete_editions(&self, athlete_ids: &[AthleteID]) -> Result<HashMap<AthleteID, Vec<(Competition, Athlete)>>, Box<dyn Error>> {
+        let competitions = self.get_competitions().await?;
+        let athlete_store = self.athlete_store.lock().expect("Mutex Lox poised");
+        let mut editions: HashMap<AthleteID, Vec<(Competition, Athlete)>> = HashMap::new();
+        for competition in competitions {
+            let Some(athletes) = athlete_store.get(&competition.number) else { continue };
+            for athlete_id in athlete_ids {
+                if let Some(athlete) = athletes.get(athlete_id) {
+                    editions.entry(athlete_id.clone()).or_default().push((competition.clone(), athlete.clone()));
+                }
+            }
+        }
+        Ok(editions)
+    }
+}
+
+#[async_trait]
+impl WaitlistStorage for InMemoryDB {
+    async fn get_waitlist(&self, competition_type: &CompetitionType) -> Result<Waitlist, Box<dyn Error>> {
+        Ok(self.scoped(&self.waitlist_store, |waitlists| waitlists.get(competition_type).cloned())
+            .unwrap_or(Waitlist::new(competition_type.clone())))
+    }
+
+    async fn store_waitlist(&self, waitlist: &Waitlist) -> Result<String, Box<dyn Error>> {
+        self.scoped(&self.waitlist_store, |waitlists| waitlists.insert(waitlist.competition_type.clone(), waitlist.clone()));
+        Ok(String::from("Waitlist stored"))
+    }
+}
+
+#[async_trait]
+impl OfficialStorage for InMemoryDB {
+    async fn get_officials(&self) -> Result<Vec<Official>, Box<dyn Error>> {
+        Ok(self.scoped(&self.official_store, |officials| officials.values().cloned().collect()))
+    }
+
+    async fn get_official(&self, official_id: &OfficialID) -> Option<Official> {
+        self.scoped(&self.official_store, |officials| officials.get(official_id).cloned())
+    }
+
+    async fn store_official(&self, official: &Official) -> Result<String, Box<dyn Error>> {
+        self.scoped(&self.official_store, |officials| officials.insert(official.official_id(), official.clone()));
+        Ok(String::from("Official stored"))
+    }
+
+    async fn delete_official(&self, official_id: &OfficialID) -> Result<(), Box<dyn Error>> {
+        self.scoped(&self.official_store, |officials| officials.remove(official_id))
+            .ok_or(ItemNotFound::new("Official not found", "404"))?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AuditStorage for InMemoryDB {
+    async fn get_audit_records(&self) -> Result<Vec<AuditRecord>, Box<dyn Error>> {
+        Ok(self.scoped(&self.audit_store, |audit_records| audit_records.clone()))
+    }
+
+    async fn get_athlete_audit_records(&self, athlete_id: &AthleteID) -> Result<Vec<AuditRecord>, Box<dyn Error>> {
+        Ok(self.scoped(&self.audit_store, |audit_records| audit_records
+            .iter()
+            .filter(|record| record.action.athlete_ids().contains(&athlete_id))
+            .cloned()
+            .collect()))
+    }
+
+    async fn store_audit_record(&self, record: &AuditRecord) -> Result<String, Box<dyn Error>> {
+        self.scoped(&self.audit_store, |audit_records| audit_records.push(record.clone()));
+        Ok(String::from("Audit record stored"))
+    }
+
+    async fn store_result_change(&self, achievement_id: &AchievementID, new: Option<&Achievement>, record: &AuditRecord) -> Result<String, Box<dyn Error>> {
+        let athlete_id = achievement_id.athlete_id().ok_or("Athlete ID not given")?;
+        // Hold both locks while writing, so the result is never stored without its record
+        let competition_key = self.competition_key();
+        let mut athlete_store = self.athlete_store.lock().expect("Mutex Lox poised");
+        let mut audit_store = self.audit_store.lock().expect("Mutex Lox poised");
+        let athlete = athlete_store
+            .entry(competition_key.clone())
+            .or_default()
+            .get_mut(&athlete_id)
+            .ok_or(ItemNotFound::new("Athlete not found", "404"))?;
+        let is_new = matches!(&record.action, AuditAction::ResultChange { old: None, .. });
+        match new {
+            Some(achievement) if is_new => athlete.add_achievement(achievement.clone())?,
+            Some(achievement) => athlete.update_achievement(achievement.clone())?,
+            None => {
+                athlete.delete_achievement(&achievement_id.name);
+                String::new()
+            }
+        };
+        audit_store.entry(competition_key).or_default().push(record.clone());
+        Ok(String::from("Result stored"))
+    }
+}
+
+#[async_trait]
+impl PrivacyStorage for InMemoryDB {
+    async fn get_athlete_records(&self, athlete_id: &AthleteID) -> Result<Vec<AthleteRecord>, Box<dyn Error>> {
+        // The in memory store holds no teams
+        let mut editions = self.get_athlete_editions(std::slice::from_ref(athlete_id)).await?;
+        let group_store = self.group_store.lock().expect("Mutex Lox poised");
+        let audit_store = self.audit_store.lock().expect("Mutex Lox poised");
+        let note_store = self.note_store.lock().expect("Mutex Lox poised");
+        Ok(editions
+            .remove(athlete_id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(competition, athlete)| {
+                let group = group_store
+                    .get(&competition.number)
+                    .and_then(|groups| groups.values().find(|group_store| group_store.athlete_ids.contains(athlete_id)))
+                    .map(|group_store| group_store.name.clone());
+                let audit_records = audit_store
+                    .get(&competition.number)
+                    .into_iter()
+                    .flatten()
+                    .filter(|record| record.action.athlete_ids().contains(&athlete_id))
+                    .map(|record| record.clone().for_athlete(athlete_id))
+                    .collect();
+                let notes = note_store
+                    .get(&competition.number)
+                    .into_iter()
+                    .flat_map(|notes| notes.values())
+                    .filter(|entry| entry.concerns(athlete_id))
+                    .cloned()
+                    .collect();
+                AthleteRecord { competition, athlete, group, teams: vec![], audit_records, notes }
+            })
+            .collect())
+    }
+
+    async fn anonymize_competition(&self, competition_id: &CompetitionID) -> Result<usize, Box<dyn Error>> {
+        match self.get_competition(competition_id).await {
+            None => return Err(Box::new(ItemNotFound::new("Competition not found", "404"))),
+            Some(competition) if competition.active => return Err(Box::from("The active competition can not be anonymized")),
+            Some(_) => {}
+        }
+
+        let competition_key = competition_id.number.clone();
+        let mut athlete_store = self.athlete_store.lock().expect("Mutex Lox poised");
+        let athlete_store = athlete_store.entry(competition_key.clone()).or_default();
+        let athletes: Vec<Athlete> = athlete_store.values().cloned().collect();
+        let pseudonyms = assign_pseudonyms(&athletes);
+        let new_names: HashMap<String, (String, String)> = pseudonyms
+            .iter()
+            .map(|(athlete_id, pseudonym)| (athlete_id.full_name(), (pseudonym.name(), pseudonym.surname())))
+            .collect();
+
+        for (athlete_id, pseudonym) in &pseudonyms {
+            if let Some(mut athlete) = athlete_store.remove(athlete_id) {
+                athlete.anonymize(&pseudonym.name(), &pseudonym.surname());
+                athlete_store.insert(pseudonym.clone(), athlete);
+            }
+        }
+        for group_store in self.group_store.lock().expect("Mutex Lox poised").entry(competition_key.clone()).or_default().values_mut() {
+            group_store.athlete_ids = group_store.athlete_ids
+                .drain()
+                .map(|athlete_id| pseudonyms.get(&athlete_id).cloned().unwrap_or(athlete_id))
+                .collect();
+            group_store.notes.values_mut().for_each(|note| *note = pseudonymize_text(note, &pseudonyms));
+        }
+        for time_group in self.time_group_store.lock().expect("Mutex Lox poised").entry(competition_key.clone()).or_default().values_mut() {
+            time_group.rename_athletes(&new_names);
+        }
+        for waitlist in self.waitlist_store.lock().expect("Mutex Lox poised").entry(competition_key.clone()).or_default().values_mut() {
+            waitlist.rename_athletes(|athlete_id| pseudonym(&pseudonyms, athlete_id));
+        }
+        for record in self.audit_store.lock().expect("Mutex Lox poised").entry(competition_key.clone()).or_default() {
+            record.action.rename_athletes(|athlete_id| pseudonym(&pseudonyms, athlete_id));
+        }
+        for entry in self.note_store.lock().expect("Mutex Lox poised").entry(competition_key).or_default().values_mut() {
+            entry.anonymize(&pseudonyms);
+        }
+
+        Ok(pseudonyms.len())
+    }
+}
+
 impl Storage for InMemoryDB {
     fn serialize(&self) {
         let mut f = File::options()
@@ -400,6 +707,11 @@ impl Storage for InMemoryDB {
         *self.athlete_store.lock().unwrap() = db.athlete_store.lock().unwrap().clone();
         *self.group_store.lock().unwrap() = db.group_store.lock().unwrap().clone();
         *self.time_group_store.lock().unwrap() = db.time_group_store.lock().unwrap().clone();
+        *self.competition_store.lock().unwrap() = db.competition_store.lock().unwrap().clone();
+        *self.waitlist_store.lock().unwrap() = db.waitlist_store.lock().unwrap().clone();
+        *self.audit_store.lock().unwrap() = db.audit_store.lock().unwrap().clone();
+        *self.official_store.lock().unwrap() = db.official_store.lock().unwrap().clone();
+        *self.note_store.lock().unwrap() = db.note_store.lock().unwrap().clone();
     }
 }
 
@@ -407,7 +719,16 @@ impl Storage for InMemoryDB {
 mod tests {
     use std::collections::HashMap;
 
-    use crate::certificate_generation::{AgeGroup, AgeGroupID, Athlete, AthleteID, CompetitionType, Group, GroupID, AchievementStorage};
+    use crate::certificate_generation::{Achievement, AchievementID, AgeGroup, AgeGroupID, Athlete, AthleteID, AthleteUpdate, CompetitionType, Group, GroupID, AchievementStorage,
+                                        AllocationRequest, GroupAllocation, SwitchGroupID};
+    use crate::audit::AuditStorage;
+    use crate::result_history;
+    use crate::competitions::{Competition, CompetitionID, CompetitionStorage};
+    use crate::privacy::PrivacyStorage;
+    use crate::waitlist::{self, WaitlistStorage};
+    use crate::notes::{self, NoteCategory, NoteEntryID, NoteID, NoteInput};
+    use crate::time_planner::{self, DisciplineState, StateChange};
+    use serde_json::json;
 
     use super::InMemoryDB;
     use chrono::{Utc, NaiveDateTime, TimeZone};
@@ -673,6 +994,307 @@ mod tests {
         }
     }
 
+    #[actix_rt::test]
+    async fn anonymize_competition() {
+        let db = InMemoryDB::_new();
+        for (number, start_date) in [("28", 1695427200), ("29", 1727049600)] {
+            let competition = Competition::from_json(&format!(r#"{{"number": "{number}", "name": "Favoritner Jedermann Zehnkampf",
+                "start_date": {start_date}, "end_date": {start_date}}}"#)).expect("Competition should be valid");
+            db.store_competition(competition).await.expect("Write should not fail in this test");
+        }
+        let competition_id = CompetitionID { number: String::from("28") };
+        let active_id = CompetitionID { number: String::from("29") };
+
+        // The athletes take part in both editions
+        let athletes = get_athletes();
+        let group_key = GroupID::new("Gruppe 1");
+        for competition_id in [&competition_id, &active_id] {
+            db.set_active_competition(competition_id).await.expect("Competition should exist");
+            for athlete in &athletes {
+                db.write_athlete(AthleteID::from_athlete(athlete), athlete.clone()).await.expect("Write should not fail in this test");
+            }
+            db.write_group(group_key.clone(), Group::new("Gruppe 1", athletes.clone(), CompetitionType::Decathlon))
+                .await
+                .expect("Write should not fail in this test");
+        }
+
+        assert!(db.anonymize_competition(&active_id).await.is_err());
+        let anonymized = db.anonymize_competition(&competition_id).await.expect("Anonymization should not fail");
+        assert_eq!(anonymized, athletes.len());
+
+        // The active edition keeps the personal data
+        assert!(db.get_athlete(&AthleteID::from_athlete(&athletes[0])).await.is_some());
+
+        db.set_active_competition(&competition_id).await.expect("Competition should exist");
+        let group = db.get_group(&group_key).await.expect("Group should still exist");
+        assert_eq!(group.athletes().len(), athletes.len());
+        assert!(group.athletes().iter().all(|athlete| athlete.name() == "Anonym"));
+        assert!(db.get_athlete(&AthleteID::from_athlete(&athletes[0])).await.is_none());
+
+        // Already anonymized athletes keep their pseudonym
+        db.set_active_competition(&active_id).await.expect("Competition should exist");
+        assert_eq!(db.anonymize_competition(&competition_id).await.expect("Anonymization should not fail"), 0);
+    }
+
+    #[actix_rt::test]
+    async fn anonymize_personal_data() {
+        let db = InMemoryDB::_new();
+        for (number, start_date) in [("28", 1695427200), ("29", 1727049600)] {
+            let competition = Competition::from_json(&format!(r#"{{"number": "{number}", "name": "Favoritner Jedermann Zehnkampf",
+                "start_date": {start_date}, "end_date": {start_date}}}"#)).expect("Competition should be valid");
+            db.store_competition(competition).await.expect("Write should not fail in this test");
+        }
+        let competition_id = CompetitionID { number: String::from("28") };
+        db.set_active_competition(&competition_id).await.expect("Competition should exist");
+
+        let names = [("Zenobia", "Quastenflosser"), ("Eberhard", "Wurzelsepp")];
+        let athletes: Vec<Athlete> = names
+            .iter()
+            .map(|(name, surname)| Athlete::new(name, surname, Utc.with_ymd_and_hms(1985, 6, 15, 0, 0, 0).single(), "M",
+                                                HashMap::new(), CompetitionType::Decathlon, None, None, None))
+            .collect();
+        for athlete in &athletes {
+            db.write_athlete(AthleteID::from_athlete(athlete), athlete.clone()).await.expect("Write should not fail in this test");
+        }
+        db.write_group(GroupID::new("Gruppe 1"), Group::new("Gruppe 1", athletes.clone(), CompetitionType::Decathlon))
+            .await.expect("Write should not fail in this test");
+        db.write_group(GroupID::new("Gruppe 2"), Group::new("Gruppe 2", vec![], CompetitionType::Decathlon))
+            .await.expect("Write should not fail in this test");
+
+        // Personal data in the audit log (group switch and result change) and on the waitlist
+        let athlete_id = AthleteID::from_athlete(&athletes[0]);
+        let json_string = format!(r#"{{"athlete_ids": [{{"name": "{}", "surname": "{}"}}]}}"#, athlete_id.name(), athlete_id.surname());
+        db.switch_group(SwitchGroupID::new("Gruppe 1", "Gruppe 2"), &json_string).await.expect("Switch should not fail");
+        let run = Achievement::from_json(r#"{"Time": {"name": "100 Meter Lauf", "final_result": 12.5, "unit": "s"}}"#)
+            .expect("Achievement should be valid");
+        result_history::change_result(&db, &AchievementID::build(athlete_id.clone(), &run), None, None, Some(run))
+            .await
+            .expect("Result should be stored");
+        let mut waitlist = db.get_waitlist(&CompetitionType::Decathlon).await.expect("Waitlist should be available");
+        waitlist.add(AthleteID::from_athlete(&athletes[1]), "Gruppe 1");
+        waitlist.add(AthleteID::new("Gerlinde", "Abgemeldet"), "Gruppe 1");
+        db.store_waitlist(&waitlist).await.expect("Write should not fail in this test");
+        let note_id = NoteID { discipline: String::from("100 Meter Lauf"), group_name: String::from("Gruppe 2") };
+        let input = NoteInput { text: String::from("Zenobia Quastenflosser ist umgeknöchelt"), category: NoteCategory::Injury, athlete_id: None };
+        let entry = notes::add_note(&db, &note_id, input, None).await.expect("Note should be stored");
+        let edit = NoteInput { text: String::from("Umgeknöchelt, Wurzelsepp hilft"), category: NoteCategory::Injury, athlete_id: Some(athlete_id.clone()) };
+        notes::edit_note(&db, &note_id, &NoteEntryID { id: entry.id }, edit, None).await.expect("Note should be edited");
+
+        let records = db.get_athlete_records(&athlete_id).await.expect("Export should not fail");
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].audit_records.len(), 2);
+        assert_eq!(records[0].notes.len(), 1);
+
+        db.set_active_competition(&CompetitionID { number: String::from("29") }).await.expect("Competition should exist");
+        db.anonymize_competition(&competition_id).await.expect("Anonymization should not fail");
+
+        let stored = format!("{:?}", db);
+        for surname in ["Quastenflosser", "Wurzelsepp", "Abgemeldet"] {
+            assert!(!stored.contains(surname), "{surname} is still stored");
+        }
+        assert!(db.get_athlete_records(&athlete_id).await.expect("Export should not fail").is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn result_history() {
+        let db = InMemoryDB::_new();
+        let athlete = get_athletes().remove(0);
+        let athlete_id = AthleteID::from_athlete(&athlete);
+        db.write_athlete(athlete_id.clone(), athlete).await.expect("Write should not fail in this test");
+
+        let run = |final_result: &str| Achievement::from_json(&format!(
+            r#"{{"Time": {{"name": "100 Meter Lauf", "final_result": {final_result}, "unit": "s"}}}}"#)).expect("Achievement should be valid");
+        let achievement_id = AchievementID::build(athlete_id.clone(), &run("12.5"));
+        result_history::change_result(&db, &achievement_id, None, None, Some(run("12.5"))).await.expect("Result should be stored");
+        // A new result does not overwrite a result entered in the meantime
+        assert!(result_history::change_result(&db, &achievement_id, None, None, Some(run("13.0"))).await.is_err());
+        result_history::change_result(&db, &achievement_id, Some(String::from("Admin")), Some(run("12.5")), Some(run("12.7")))
+            .await
+            .expect("Result should be stored");
+
+        let events = result_history::result_events(db.get_audit_records().await.expect("Audit records should be available"), &Default::default());
+        assert_eq!(events.len(), 2);
+        assert_eq!(result_history::replay(&events, None), db.get_achievement(&achievement_id).await);
+
+        let restore = result_history::RestoreQuery { event_id: events[0].id.clone(), role: None };
+        let restored = result_history::restore_result(&db, &restore).await.expect("Result should be restored");
+        assert_eq!(restored, db.get_achievement(&achievement_id).await);
+        assert_eq!(restored.map(|achievement| achievement.result_value()), Some(run("12.5").result_value()));
+        assert_eq!(db.get_audit_records().await.expect("Audit records should be available").len(), 3);
+        assert_eq!(db.get_athlete_audit_records(&athlete_id).await.expect("Audit records should be available").len(), 3);
+        assert!(db.get_athlete_audit_records(&AthleteID::new("Max", "Muster")).await.expect("Audit records should be available").is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn finish_track_discipline() {
+        let db = InMemoryDB::_new();
+        let time_table = json!({
+            "Dates": {"Samstag": "20.09.2025"},
+            "DisciplineTypes": {"100 Meter Lauf": "Track", "Weitsprung": "Default"},
+            "Groups": {
+                "Gruppe 1": {
+                    "100 Meter Lauf": {"location": "Start 100m", "time": "08:30, Samstag"},
+                    "Weitsprung": {"location": "Weit 1", "time": "09:00, Samstag"},
+                },
+            },
+        });
+        let mut time_group = time_planner::build_time_groups(&time_table, time_planner::DEFAULT_TIME_ZONE)
+            .expect("Time groups should be buildable")
+            .remove(0);
+        let athletes: Vec<time_planner::Athlete> = get_athletes()
+            .iter()
+            .map(|athlete| time_planner::Athlete::new(athlete.name().to_string(), athlete.surname().to_string(), None))
+            .collect();
+        time_group.insert_athletes(&athletes);
+
+        // The times of the runs are entered by the timekeeping, the group finishes without them
+        assert!(time_planner::missing_results(&db, &time_group, "100 Meter Lauf").await.expect("Discipline should exist").is_empty());
+        for state in [DisciplineState::Active, DisciplineState::Finished] {
+            let change = StateChange { name: String::from("100 Meter Lauf"), state, force: false };
+            time_group.change_discipline_state(&change, &[]).expect("State change should be allowed");
+        }
+        assert_eq!(time_planner::missing_results(&db, &time_group, "Weitsprung").await.expect("Discipline should exist").len(), athletes.len());
+    }
+
+    #[actix_rt::test]
+    async fn separate_competitions() {
+        let db = InMemoryDB::_new();
+        for (number, start_date) in [("28", 1695427200), ("29", 1727049600)] {
+            let competition = Competition::from_json(&format!(r#"{{"number": "{number}", "name": "Favoritner Jedermann Zehnkampf",
+                "start_date": {start_date}, "end_date": {start_date}}}"#)).expect("Competition should be valid");
+            db.store_competition(competition).await.expect("Write should not fail in this test");
+        }
+        let athlete = get_athletes().remove(0);
+        let athlete_id = AthleteID::from_athlete(&athlete);
+        db.set_active_competition(&CompetitionID { number: String::from("28") }).await.expect("Competition should exist");
+        db.write_athlete(athlete_id.clone(), athlete.clone()).await.expect("Write should not fail in this test");
+        db.write_group(GroupID::new("Gruppe 1"), Group::new("Gruppe 1", vec![athlete], CompetitionType::Decathlon))
+            .await
+            .expect("Write should not fail in this test");
+
+        // The next edition starts empty, the athlete is only part of the history
+        db.set_active_competition(&CompetitionID { number: String::from("29") }).await.expect("Competition should exist");
+        assert!(db.get_athlete(&athlete_id).await.is_none());
+        assert!(db.get_group(&GroupID::new("Gruppe 1")).await.is_none());
+        let editions = db.get_athlete_editions(std::slice::from_ref(&athlete_id)).await.expect("Editions should be available");
+        let numbers: Vec<&str> = editions[&athlete_id].iter().map(|(competition, _)| competition.number.as_str()).collect();
+        assert_eq!(numbers, vec!["28"]);
+    }
+
+    #[actix_rt::test]
+    async fn commit_group_allocation() {
+        let db = InMemoryDB::_new();
+        let athletes = get_athletes();
+        for athlete in &athletes {
+            db.write_athlete(AthleteID::from_athlete(athlete), athlete.clone()).await.expect("Write should not fail in this test");
+        }
+        db.write_group(GroupID::new("Gruppe 3"), Group::new("Gruppe 3", athletes.clone(), CompetitionType::Decathlon))
+            .await
+            .expect("Write should not fail in this test");
+
+        let request = AllocationRequest { group_sizes: vec![athletes.len(), athletes.len()], keep_together: vec![] };
+        let allocation = GroupAllocation::propose(&athletes, &request);
+        allocation.clone().validate(&db).await.expect("Allocation should be valid").commit(&db).await.expect("Commit should not fail");
+
+        let group_1 = db.get_group(&GroupID::new("Gruppe 1")).await.expect("Group should be stored");
+        let group_2 = db.get_group(&GroupID::new("Gruppe 2")).await.expect("Group should be stored");
+        let group_3 = db.get_group(&GroupID::new("Gruppe 3")).await.expect("Group should still exist");
+        assert_eq!(group_1.athletes().len() + group_2.athletes().len(), allocation.groups.iter().map(|group| group.athlete_ids.len()).sum::<usize>());
+        assert!(group_3.athletes().is_empty());
+
+        // Committing the same allocation again changes nothing
+        allocation.clone().validate(&db).await.expect("Allocation should be valid").commit(&db).await.expect("Commit should not fail");
+        assert_eq!(db.get_group(&GroupID::new("Gruppe 1")).await.expect("Group should be stored").athlete_ids(), group_1.athlete_ids());
+    }
+
+    #[actix_rt::test]
+    async fn promote_from_waitlist() {
+        let db = InMemoryDB::_new();
+        let athletes = get_athletes();
+        for athlete in &athletes {
+            db.write_athlete(AthleteID::from_athlete(athlete), athlete.clone()).await.expect("Write should not fail in this test");
+        }
+        let mut group = Group::new("Gruppe 1", athletes[..2].to_vec(), CompetitionType::Decathlon);
+        group.set_capacity(Some(2));
+        let group_key = GroupID::new("Gruppe 1");
+        db.write_group(group_key.clone(), group).await.expect("Write should not fail in this test");
+
+        let waitlisted = AthleteID::from_athlete(&athletes[2]);
+        let add_json = format!(r#"{{"athlete_ids": [{{"name": "{}", "surname": "{}"}}]}}"#, waitlisted.name(), waitlisted.surname());
+        assert!(db.update_group(group_key.clone(), &add_json, false).await.is_err());
+
+        // The athlete registered for the group is promoted before athletes waiting for other groups
+        let other = AthleteID::from_athlete(&athletes[3]);
+        let mut waitlist = db.get_waitlist(&CompetitionType::Decathlon).await.expect("Waitlist should be available");
+        waitlist.add(other.clone(), "Gruppe 2");
+        waitlist.add(waitlisted.clone(), "Gruppe 1");
+        db.store_waitlist(&waitlist).await.expect("Write should not fail in this test");
+
+        let deregistered = db.update_athlete(AthleteID::from_athlete(&athletes[0]), r#"{"deregistered": true}"#)
+            .await
+            .expect("Update should not fail");
+        assert_eq!(deregistered, AthleteUpdate::Deregistered);
+
+        let promotion = waitlist::promote_from_waitlist(&db, &group_key)
+            .await
+            .expect("Promotion should not fail")
+            .expect("Athlete should be promoted");
+        assert_eq!(promotion.athlete_id, waitlisted);
+        let group = db.get_group(&group_key).await.expect("Group should exist");
+        assert!(group.athlete_ids().contains(&waitlisted));
+        let waitlist = db.get_waitlist(&CompetitionType::Decathlon).await.expect("Waitlist should be available");
+        assert_eq!(waitlist.entries.iter().map(|entry| &entry.athlete_id).collect::<Vec<_>>(), vec![&other]);
+
+        // The group is full again, the deregistered athlete can not register again and waits for a free place
+        let registered_again = AthleteID::from_athlete(&athletes[0]);
+        assert_eq!(waitlist::waitlist_if_full(&db, &registered_again).await.expect("Waitlist should be available"), Some(2));
+        db.update_athlete(waitlisted.clone(), r#"{"deregistered": true}"#).await.expect("Update should not fail");
+        let promotion = waitlist::promote_from_waitlist(&db, &group_key)
+            .await
+            .expect("Promotion should not fail")
+            .expect("Athlete should be promoted");
+        assert_eq!(promotion.athlete_id, registered_again);
+        assert!(!db.get_athlete(&registered_again).await.expect("Athlete should exist").is_deregistered());
+        let group = db.get_group(&group_key).await.expect("Group should exist");
+        assert_eq!(group.registered_athletes(), 2);
+        assert!(db.update_group(group_key.clone(), r#"{"capacity": 1}"#, false).await.is_err());
+        db.update_group(group_key.clone(), r#"{"capacity": null}"#, false).await.expect("Capacity should be removed");
+        assert_eq!(db.get_group(&group_key).await.expect("Group should exist").capacity(), None);
+    }
+
+    #[actix_rt::test]
+    async fn switch_group() {
+        let db = InMemoryDB::_new();
+        let athletes = get_athletes();
+        for athlete in &athletes {
+            db.write_athlete(AthleteID::from_athlete(athlete), athlete.clone()).await.expect("Write should not fail in this test");
+        }
+        db.write_group(GroupID::new("Gruppe 1"), Group::new("Gruppe 1", athletes[..2].to_vec(), CompetitionType::Decathlon))
+            .await.expect("Write should not fail in this test");
+        let mut to_group = Group::new("Gruppe 2", athletes[2..3].to_vec(), CompetitionType::Decathlon);
+        to_group.set_capacity(Some(2));
+        db.write_group(GroupID::new("Gruppe 2"), to_group).await.expect("Write should not fail in this test");
+
+        let switch_ids = SwitchGroupID { from: Some(String::from("Gruppe 1")), to: Some(String::from("Gruppe 2")) };
+        let moved = AthleteID::from_athlete(&athletes[0]);
+        let json_string = format!(r#"{{"athlete_ids": [{{"name": "{}", "surname": "{}"}}]}}"#, moved.name(), moved.surname());
+        db.switch_group(switch_ids.clone(), &json_string).await.expect("Switch should not fail");
+
+        let from_group = db.get_group(&GroupID::new("Gruppe 1")).await.expect("Group should exist");
+        let to_group = db.get_group(&GroupID::new("Gruppe 2")).await.expect("Group should exist");
+        assert!(!from_group.athlete_ids().contains(&moved));
+        assert!(to_group.athlete_ids().contains(&moved));
+        assert_eq!(db.get_audit_records().await.expect("Audit log should be available").len(), 1);
+
+        // The target group is full now, nothing may change
+        let second = AthleteID::from_athlete(&athletes[1]);
+        let json_string = format!(r#"{{"athlete_ids": [{{"name": "{}", "surname": "{}"}}]}}"#, second.name(), second.surname());
+        assert!(db.switch_group(switch_ids, &json_string).await.is_err());
+        let from_group = db.get_group(&GroupID::new("Gruppe 1")).await.expect("Group should exist");
+        assert!(from_group.athlete_ids().contains(&second));
+        assert_eq!(db.get_audit_records().await.expect("Audit log should be available").len(), 1);
+    }
+
     #[actix_rt::test]
     async fn access_age_group() {
         let db = InMemoryDB::_new();
@@ -682,7 +1304,7 @@ mod tests {
             db.write_athlete(athlete_key, athlete.clone()).await.expect("Write should not fail in this test");
         }
 
-        assert_eq!(athletes.len(), db.athlete_store.lock().expect("Mutex Lock poised").len());
+        assert_eq!(athletes.len(), db.scoped(&db.athlete_store, |athlete_store| athlete_store.len()));
 
         for athlete in &athletes {
             let athlete_key = AthleteID::from_athlete(&athlete);
@@ -9,16 +9,27 @@ mod certificate_generation;
 mod database;
 mod time_planner;
 mod authenticate;
+mod competitions;
 mod notes;
 mod teams;
+mod privacy;
+mod waitlist;
+mod audit;
+mod result_history;
+mod officials;
 
 use certificate_generation::AchievementStorage;
 use time_planner::TimePlanStorage;
 use database::Store;
 use notes::NoteStorage;
 use teams::TeamStorage;
+use competitions::CompetitionStorage;
+use privacy::PrivacyStorage;
+use waitlist::WaitlistStorage;
+use audit::AuditStorage;
+use officials::OfficialStorage;
 
-pub trait Storage: AchievementStorage + TimePlanStorage + AuthenticateStorage + NoteStorage + TeamStorage{
+pub trait Storage: AchievementStorage + TimePlanStorage + AuthenticateStorage + NoteStorage + TeamStorage + CompetitionStorage + PrivacyStorage + WaitlistStorage + AuditStorage + OfficialStorage{
     fn serialize(&self);
     fn load(&self);
 }
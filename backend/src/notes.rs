@@ -1,7 +1,13 @@
 use async_trait::async_trait;
+use chrono::{DateTime, SecondsFormat, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::error::Error;
 
+use crate::certificate_generation::{AthleteID, GroupID};
+use crate::privacy::{pseudonym, pseudonymize_text};
+use crate::Storage;
+
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct NoteID {
@@ -9,8 +15,230 @@ pub struct NoteID {
     pub group_name: String,
 }
 
+#[derive(Deserialize, Debug, Clone)]
+pub struct NoteEntryID {
+    pub id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub enum NoteCategory {
+    Injury,
+    Protest,
+    Equipment,
+    Weather,
+    #[default]
+    General,
+}
+
+impl NoteCategory {
+    /// German label used in the discipline protocol
+    pub fn description(&self) -> &str {
+        match self {
+            NoteCategory::Injury => "Verletzung",
+            NoteCategory::Protest => "Protest",
+            NoteCategory::Equipment => "Material",
+            NoteCategory::Weather => "Wetter",
+            NoteCategory::General => "Allgemein",
+        }
+    }
+}
+
+/// Content of a note as sent by the client
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct NoteInput {
+    pub text: String,
+    #[serde(default)]
+    pub category: NoteCategory,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub athlete_id: Option<AthleteID>,
+}
+
+impl NoteInput {
+    pub fn from_json(json_str: &str) -> Result<Self, Box<dyn Error>> {
+        let input: NoteInput = serde_json::from_str(json_str)?;
+        if input.text.trim().is_empty() {
+            return Err(Box::from("Text of the note not given"));
+        }
+        Ok(input)
+    }
+}
+
+/// Previous version of an edited note
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct NoteRevision {
+    pub timestamp: DateTime<Utc>,
+    pub role: Option<String>,
+    pub text: String,
+    pub category: NoteCategory,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub athlete_id: Option<AthleteID>,
+}
+
+/// Note of a group for a discipline. Notes are never deleted, edits keep the previous versions in the history.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct NoteEntry {
+    /// Sortable unique id (group, discipline and RFC 3339 time stamp with microseconds)
+    pub id: String,
+    pub group_name: String,
+    pub discipline: String,
+    /// Time of the last edit
+    pub timestamp: DateTime<Utc>,
+    /// Role of the user who wrote the current version, if known
+    pub role: Option<String>,
+    pub text: String,
+    pub category: NoteCategory,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub athlete_id: Option<AthleteID>,
+    /// Previous versions, oldest first
+    #[serde(default)]
+    pub history: Vec<NoteRevision>,
+    /// Free-text note stored with the group before notes had entries, it can not be edited
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub legacy: bool,
+}
+
+impl NoteEntry {
+    pub fn new(note_id: &NoteID, input: NoteInput, role: Option<String>) -> Self {
+        let timestamp = Utc::now();
+        NoteEntry {
+            id: format!("{}#{}#{}", note_id.group_name, note_id.discipline, timestamp.to_rfc3339_opts(SecondsFormat::Micros, true)),
+            group_name: note_id.group_name.clone(),
+            discipline: note_id.discipline.clone(),
+            timestamp,
+            role,
+            text: input.text,
+            category: input.category,
+            athlete_id: input.athlete_id,
+            history: vec![],
+            legacy: false,
+        }
+    }
+
+    fn from_legacy(note_id: &NoteID, text: &str) -> Self {
+        NoteEntry {
+            id: format!("{}#{}#legacy", note_id.group_name, note_id.discipline),
+            group_name: note_id.group_name.clone(),
+            discipline: note_id.discipline.clone(),
+            timestamp: DateTime::UNIX_EPOCH,
+            role: None,
+            text: text.to_string(),
+            category: NoteCategory::General,
+            athlete_id: None,
+            history: vec![],
+            legacy: true,
+        }
+    }
+
+    pub fn note_id(&self) -> NoteID {
+        NoteID { discipline: self.discipline.clone(), group_name: self.group_name.clone() }
+    }
+
+    /// Replace the content, the current version is moved to the history
+    pub fn edit(&mut self, input: NoteInput, role: Option<String>) -> Result<(), Box<dyn Error>> {
+        if self.legacy {
+            return Err(Box::from("Legacy notes can not be edited"));
+        }
+        self.history.push(NoteRevision {
+            timestamp: self.timestamp,
+            role: self.role.take(),
+            text: std::mem::take(&mut self.text),
+            category: self.category.clone(),
+            athlete_id: self.athlete_id.take(),
+        });
+        self.timestamp = Utc::now();
+        self.role = role;
+        self.text = input.text;
+        self.category = input.category;
+        self.athlete_id = input.athlete_id;
+        Ok(())
+    }
+
+    /// Whether a version of the note is about the athlete or mentions the full name
+    pub fn concerns(&self, athlete_id: &AthleteID) -> bool {
+        let full_name = format!("{} {}", athlete_id.name(), athlete_id.surname());
+        let concerns = |text: &str, about: &Option<AthleteID>| about.as_ref() == Some(athlete_id) || text.contains(&full_name);
+        concerns(&self.text, &self.athlete_id) || self.history.iter().any(|revision| concerns(&revision.text, &revision.athlete_id))
+    }
+
+    /// Replace the athletes and their names in all versions of the note by pseudonyms
+    pub fn anonymize(&mut self, pseudonyms: &HashMap<AthleteID, AthleteID>) {
+        self.text = pseudonymize_text(&self.text, pseudonyms);
+        self.athlete_id = self.athlete_id.as_ref().map(|athlete_id| pseudonym(pseudonyms, athlete_id));
+        for revision in &mut self.history {
+            revision.text = pseudonymize_text(&revision.text, pseudonyms);
+            revision.athlete_id = revision.athlete_id.as_ref().map(|athlete_id| pseudonym(pseudonyms, athlete_id));
+        }
+    }
+}
+
+/// Notes of the group for the discipline, oldest first. The free-text note stored with the group is listed first.
+pub async fn discipline_notes(storage: &(dyn Storage + Send + Sync), note_id: &NoteID) -> Result<Vec<NoteEntry>, Box<dyn Error>> {
+    let mut notes = vec![];
+    if let Some(group) = storage.get_group(&GroupID::new(&note_id.group_name)).await {
+        if let Some(text) = group.notes().get(&note_id.discipline).filter(|text| !text.trim().is_empty()) {
+            notes.push(NoteEntry::from_legacy(note_id, text));
+        }
+    }
+    let mut entries = storage.get_note_entries(note_id).await?;
+    entries.sort_by(|entry, other| entry.id.cmp(&other.id));
+    notes.extend(entries);
+    Ok(notes)
+}
+
+/// Add a note to the group and discipline
+pub async fn add_note(storage: &(dyn Storage + Send + Sync), note_id: &NoteID, input: NoteInput,
+                      role: Option<String>) -> Result<NoteEntry, Box<dyn Error>> {
+    let entry = NoteEntry::new(note_id, input, role);
+    storage.store_note_entry(&entry).await?;
+    Ok(entry)
+}
+
+/// Edit a stored note, the previous version is kept in its history
+pub async fn edit_note(storage: &(dyn Storage + Send + Sync), note_id: &NoteID, entry_id: &NoteEntryID,
+                       input: NoteInput, role: Option<String>) -> Result<NoteEntry, Box<dyn Error>> {
+    let mut entry = storage
+        .get_note_entries(note_id)
+        .await?
+        .into_iter()
+        .find(|entry| entry.id == entry_id.id)
+        .ok_or(format!("Note {} not found", entry_id.id))?;
+    entry.edit(input, role)?;
+    storage.store_note_entry(&entry).await?;
+    Ok(entry)
+}
+
 #[async_trait]
 pub trait NoteStorage {
+    /// Free-text note stored with the group, replaced by the note entries
     async fn get_note(&self, note_id: NoteID) -> Result<Option<String>, Box<dyn Error>>;
-    async fn save_note(&self, note_id: NoteID, note: String) ->  Result<String, Box<dyn Error>>;
+    async fn get_note_entries(&self, note_id: &NoteID) -> Result<Vec<NoteEntry>, Box<dyn Error>>;
+    /// Store a new entry or an edit of a stored one. Fails if the entry was changed in the meantime (the stored
+    /// history has to be one revision shorter), so concurrent edits can not overwrite each other.
+    async fn store_note_entry(&self, entry: &NoteEntry) -> Result<String, Box<dyn Error>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::certificate_generation::AthleteID;
+    use super::{NoteCategory, NoteEntry, NoteID, NoteInput};
+
+    #[test]
+    fn edit_history() {
+        let note_id = NoteID { discipline: String::from("Weitsprung"), group_name: String::from("Gruppe 1") };
+        let input = NoteInput::from_json(r#"{"text": "Umgeknöchelt", "category": "Injury"}"#).expect("Input should be valid");
+        let mut entry = NoteEntry::new(&note_id, input, Some(String::from("Group")));
+        assert!(entry.id.starts_with("Gruppe 1#Weitsprung#"));
+
+        let athlete_id = AthleteID::new("Max", "Muster");
+        let edit = NoteInput { text: String::from("Umgeknöchelt, 3. Versuch"), category: NoteCategory::Injury, athlete_id: Some(athlete_id.clone()) };
+        entry.edit(edit, Some(String::from("Admin"))).expect("Entry should be editable");
+        assert_eq!(entry.text, "Umgeknöchelt, 3. Versuch");
+        assert_eq!(entry.athlete_id, Some(athlete_id));
+        assert_eq!(entry.history.len(), 1);
+        assert_eq!(entry.history[0].text, "Umgeknöchelt");
+        assert_eq!(entry.history[0].role.as_deref(), Some("Group"));
+
+        assert!(NoteInput::from_json(r#"{"text": " "}"#).is_err());
+        assert!(NoteEntry::from_legacy(&note_id, "Alte Notiz").edit(NoteInput::from_json(r#"{"text": "Neu"}"#).unwrap(), None).is_err());
+    }
 }
@@ -0,0 +1,265 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+
+use crate::time_planner::{Discipline, TimeGroup};
+use crate::Storage;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OfficialID {
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum OfficialRole {
+    Referee,
+    Judge,
+    Starter,
+    Timekeeper,
+    Helper,
+}
+
+impl OfficialRole {
+    /// Referees and judges can run a station on their own
+    fn judges(&self) -> bool {
+        matches!(self, OfficialRole::Referee | OfficialRole::Judge)
+    }
+}
+
+/// Assignment of an official to a station (location) for a time slot
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Assignment {
+    pub location: String,
+    /// Only this discipline, all disciplines at the location if not given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub discipline: Option<String>,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl Assignment {
+    fn covers(&self, discipline: &Discipline) -> bool {
+        self.location == discipline.location()
+            && self.discipline.as_ref().is_none_or(|name| name == discipline.name())
+            && self.start < discipline.end_time()
+            && self.end > discipline.start_time()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Official {
+    /// Full name, used as id
+    pub name: String,
+    pub role: OfficialRole,
+    /// Phone number or e-mail address
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contact: Option<String>,
+    #[serde(default)]
+    pub assignments: Vec<Assignment>,
+}
+
+impl Official {
+    pub fn from_json(json_str: &str) -> Result<Self, Box<dyn Error>> {
+        let official: Official = serde_json::from_str(json_str)?;
+        if official.name.trim().is_empty() {
+            return Err(Box::from("Name of the official not given"));
+        }
+        if let Some(assignment) = official.assignments.iter().find(|assignment| assignment.end <= assignment.start) {
+            return Err(Box::from(format!("Assignment at {} ends before it starts", assignment.location)));
+        }
+        Ok(official)
+    }
+
+    pub fn official_id(&self) -> OfficialID {
+        OfficialID { name: self.name.clone() }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RosterOfficial {
+    pub name: String,
+    pub role: OfficialRole,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contact: Option<String>,
+}
+
+/// Scheduled discipline of a group and the officials at its station
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RosterEntry {
+    pub group: String,
+    pub discipline: String,
+    pub location: String,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    pub officials: Vec<RosterOfficial>,
+}
+
+/// Station without a judge during (a part of) a scheduled discipline
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StaffingWarning {
+    pub group: String,
+    pub discipline: String,
+    pub location: String,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Roster {
+    /// Ordered by start time and location
+    pub entries: Vec<RosterEntry>,
+    pub warnings: Vec<StaffingWarning>,
+}
+
+/// First time during the discipline without a judge, None if judges cover the whole discipline
+fn uncovered_from(discipline: &Discipline, judges: &[&Assignment]) -> Option<DateTime<Utc>> {
+    let mut slots: Vec<(DateTime<Utc>, DateTime<Utc>)> = judges.iter().map(|assignment| (assignment.start, assignment.end)).collect();
+    slots.sort();
+    let mut covered_until = discipline.start_time();
+    for (start, end) in slots {
+        if start > covered_until {
+            break;
+        }
+        covered_until = covered_until.max(end);
+    }
+    (covered_until < discipline.end_time()).then_some(covered_until)
+}
+
+impl Roster {
+    /// Officials of every scheduled discipline and warnings for stations without a judge
+    pub fn build(time_groups: &[TimeGroup], officials: &[Official]) -> Roster {
+        let mut roster = Roster::default();
+        for group in time_groups {
+            let time_zone: Tz = group.time_zone();
+            for discipline in group.get_disciplines() {
+                let assigned: Vec<(&Official, &Assignment)> = officials
+                    .iter()
+                    .flat_map(|official| official.assignments.iter().map(move |assignment| (official, assignment)))
+                    .filter(|(_, assignment)| assignment.covers(discipline))
+                    .collect();
+                let judges: Vec<&Assignment> = assigned
+                    .iter()
+                    .filter(|(official, _)| official.role.judges())
+                    .map(|(_, assignment)| *assignment)
+                    .collect();
+
+                let message = match uncovered_from(discipline, &judges) {
+                    _ if judges.is_empty() => Some(String::from("No judge assigned")),
+                    Some(time) => Some(format!("No judge assigned from {}", time.with_timezone(&time_zone).format("%H:%M"))),
+                    None => None,
+                };
+                if let Some(message) = message {
+                    roster.warnings.push(StaffingWarning {
+                        group: group.name().to_string(),
+                        discipline: discipline.name().to_string(),
+                        location: discipline.location().to_string(),
+                        message,
+                    });
+                }
+
+                let mut roster_officials: Vec<RosterOfficial> = vec![];
+                for (official, _) in assigned {
+                    if !roster_officials.iter().any(|roster_official| roster_official.name == official.name) {
+                        roster_officials.push(RosterOfficial {
+                            name: official.name.clone(),
+                            role: official.role.clone(),
+                            contact: official.contact.clone(),
+                        });
+                    }
+                }
+                roster.entries.push(RosterEntry {
+                    group: group.name().to_string(),
+                    discipline: discipline.name().to_string(),
+                    location: discipline.location().to_string(),
+                    start_time: discipline.start_time(),
+                    end_time: discipline.end_time(),
+                    officials: roster_officials,
+                });
+            }
+        }
+        roster.entries.sort_by(|entry, other| (entry.start_time, &entry.location).cmp(&(other.start_time, &other.location)));
+        roster
+    }
+
+    /// Disciplines the official is assigned to
+    pub fn assignments_of(&self, official_id: &OfficialID) -> Vec<RosterEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.officials.iter().any(|official| official.name == official_id.name))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Roster of the stored time groups and officials
+pub async fn build_roster(storage: &(dyn Storage + Send + Sync)) -> Result<Roster, Box<dyn Error>> {
+    let time_groups = storage.get_time_groups().await?;
+    let officials = storage.get_officials().await?;
+    Ok(Roster::build(&time_groups, &officials))
+}
+
+#[async_trait]
+pub trait OfficialStorage {
+    async fn get_officials(&self) -> Result<Vec<Official>, Box<dyn Error>>;
+    async fn get_official(&self, official_id: &OfficialID) -> Option<Official>;
+    /// Store a new official or replace the stored one with the same name
+    async fn store_official(&self, official: &Official) -> Result<String, Box<dyn Error>>;
+    async fn delete_official(&self, official_id: &OfficialID) -> Result<(), Box<dyn Error>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Utc};
+    use serde_json::json;
+    use crate::time_planner::{build_time_groups, DEFAULT_TIME_ZONE};
+    use super::{Assignment, Official, OfficialID, OfficialRole, Roster};
+
+    fn get_time(time: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(&format!("2025-09-20T{time}:00+02:00")).expect("Time should be valid").to_utc()
+    }
+
+    fn get_official(name: &str, role: OfficialRole, location: &str, start: &str, end: &str) -> Official {
+        Official {
+            name: name.to_string(),
+            role,
+            contact: None,
+            assignments: vec![Assignment { location: location.to_string(), discipline: None, start: get_time(start), end: get_time(end) }],
+        }
+    }
+
+    #[test]
+    fn staffing_warnings() {
+        let time_table = json!({
+            "Dates": {"Samstag": "20.09.2025"},
+            "DisciplineTypes": {"Weitsprung": "Default", "Kugelstoß": "Default"},
+            "Groups": {
+                "Gruppe 1": {
+                    "Weitsprung": {"location": "Weit 1", "time": "09:00, Samstag", "duration": 60},
+                    "Kugelstoß": {"location": "Kugel", "time": "10:00, Samstag", "duration": 60},
+                },
+            },
+        });
+        let time_groups = build_time_groups(&time_table, DEFAULT_TIME_ZONE).expect("Time groups should be buildable");
+        let officials = vec![
+            get_official("Anna Muster", OfficialRole::Judge, "Weit 1", "08:45", "09:30"),
+            get_official("Berta Muster", OfficialRole::Referee, "Weit 1", "09:30", "12:00"),
+            get_official("Clara Muster", OfficialRole::Judge, "Kugel", "10:00", "10:40"),
+            get_official("Dora Muster", OfficialRole::Helper, "Kugel", "10:00", "11:00"),
+        ];
+
+        let roster = Roster::build(&time_groups, &officials);
+        let disciplines: Vec<&str> = roster.entries.iter().map(|entry| entry.discipline.as_str()).collect();
+        assert_eq!(disciplines, vec!["Weitsprung", "Kugelstoß"]);
+        assert_eq!(roster.entries[0].officials.len(), 2);
+
+        // The helper does not count as judge
+        assert_eq!(roster.warnings.len(), 1);
+        assert_eq!(roster.warnings[0].discipline, "Kugelstoß");
+        assert_eq!(roster.warnings[0].message, "No judge assigned from 10:40");
+
+        let assignments = roster.assignments_of(&OfficialID { name: String::from("Dora Muster") });
+        assert_eq!(assignments.len(), 1);
+        assert_eq!(assignments[0].location, "Kugel");
+    }
+}
@@ -0,0 +1,216 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use chrono::serde::ts_seconds;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+
+use crate::audit::AuditRecord;
+use crate::certificate_generation::{Athlete, AthleteID};
+use crate::competitions::{Competition, CompetitionID};
+use crate::notes::NoteEntry;
+use crate::Storage;
+
+/// Name of all anonymized athletes, the surname is a running number (e.g. "Anonym 0042")
+pub const ANONYMOUS_NAME: &str = "Anonym";
+
+/// Surname of athletes which are only referenced (e.g. in the audit log) but no longer registered in the competition
+const UNREGISTERED_NUMBER: &str = "0000";
+
+/// Retention period used if RETENTION_DAYS is not set
+const DEFAULT_RETENTION_DAYS: i64 = 3 * 365;
+
+/// Entry of an athlete in one competition, including group and team membership
+#[derive(Clone, Debug, Serialize)]
+pub struct AthleteRecord {
+    pub competition: Competition,
+    pub athlete: Athlete,
+    pub group: Option<String>,
+    pub teams: Vec<String>,
+    /// Group switches and result changes of the athlete
+    pub audit_records: Vec<AuditRecord>,
+    /// Notes about the athlete
+    pub notes: Vec<NoteEntry>,
+}
+
+/// Everything stored about one athlete (GDPR data export)
+#[derive(Clone, Debug, Serialize)]
+pub struct AthleteExport {
+    pub athlete_id: AthleteID,
+    #[serde(with = "ts_seconds")]
+    pub exported_at: DateTime<Utc>,
+    pub records: Vec<AthleteRecord>,
+}
+
+#[async_trait]
+pub trait PrivacyStorage {
+    /// Entries of the athlete in all competitions
+    async fn get_athlete_records(&self, athlete_id: &AthleteID) -> Result<Vec<AthleteRecord>, Box<dyn Error>>;
+    /// Replace the personal data of all athletes in the competition by pseudonyms (in groups, time groups, teams,
+    /// waitlists, the audit log and notes as well).
+    /// Returns the number of anonymized athletes. The active competition is never anonymized.
+    async fn anonymize_competition(&self, competition_id: &CompetitionID) -> Result<usize, Box<dyn Error>>;
+}
+
+pub async fn export_athlete(storage: &(dyn Storage + Send + Sync), athlete_id: &AthleteID) -> Result<AthleteExport, Box<dyn Error>> {
+    let records = storage.get_athlete_records(athlete_id).await?;
+    Ok(AthleteExport {
+        athlete_id: athlete_id.clone(),
+        exported_at: Utc::now(),
+        records,
+    })
+}
+
+pub fn retention_period() -> Duration {
+    let days = std::env::var("RETENTION_DAYS")
+        .ok()
+        .and_then(|days| days.parse().ok())
+        .unwrap_or(DEFAULT_RETENTION_DAYS);
+    Duration::days(days)
+}
+
+/// Competitions which ended more than the retention period ago and are not anonymized yet.
+/// The active competition is never anonymized.
+pub fn is_expired(competition: &Competition, retention_period: Duration, now: DateTime<Utc>) -> bool {
+    !competition.active && !competition.anonymized && competition.end_date + retention_period < now
+}
+
+/// Anonymize all expired competitions, returns the numbers of the anonymized competitions
+pub async fn anonymize_expired_competitions(
+    storage: &(dyn Storage + Send + Sync),
+    retention_period: Duration,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let now = Utc::now();
+    let competitions = storage.get_competitions().await?;
+
+    let mut anonymized = vec![];
+    for mut competition in competitions {
+        if !is_expired(&competition, retention_period, now) {
+            continue;
+        }
+        let competition_id = CompetitionID { number: competition.number.clone() };
+        storage.anonymize_competition(&competition_id).await?;
+
+        competition.anonymized = true;
+        anonymized.push(competition.number.clone());
+        storage.store_competition(competition).await?;
+    }
+    Ok(anonymized)
+}
+
+/// New (anonymized) athlete ids for all athletes which still carry personal data.
+/// Pseudonyms are numbered by starting number after the highest pseudonym already in use, so no id is reused.
+pub fn assign_pseudonyms(athletes: &[Athlete]) -> HashMap<AthleteID, AthleteID> {
+    let (anonymized, mut personal): (Vec<&Athlete>, Vec<&Athlete>) = athletes
+        .iter()
+        .partition(|athlete| athlete.name() == ANONYMOUS_NAME);
+
+    let mut next_number = anonymized
+        .iter()
+        .filter_map(|athlete| athlete.surname().parse::<u32>().ok())
+        .max()
+        .unwrap_or(0) + 1;
+
+    personal.sort_by_key(|athlete| (athlete.starting_number().unwrap_or(u16::MAX), athlete.athlete_id()));
+    personal
+        .into_iter()
+        .map(|athlete| {
+            let pseudonym = AthleteID::new(ANONYMOUS_NAME, format!("{:04}", next_number).as_str());
+            next_number += 1;
+            (AthleteID::from_athlete(athlete), pseudonym)
+        })
+        .collect()
+}
+
+/// Pseudonym of the athlete. Athletes which are no longer registered in the competition share one pseudonym
+/// (e.g. "Anonym 0000"), already anonymized athletes keep theirs.
+pub fn pseudonym(pseudonyms: &HashMap<AthleteID, AthleteID>, athlete_id: &AthleteID) -> AthleteID {
+    match pseudonyms.get(athlete_id) {
+        Some(pseudonym) => pseudonym.clone(),
+        None if athlete_id.name() == ANONYMOUS_NAME => athlete_id.clone(),
+        None => AthleteID::new(ANONYMOUS_NAME, UNREGISTERED_NUMBER),
+    }
+}
+
+/// Replace the names of the athletes in a free text (e.g. a note) by pseudonyms. Full names ("Max Muster") are
+/// replaced by the pseudonym of the athlete, single first names or surnames by the anonymous name.
+/// Only whole words are replaced.
+pub fn pseudonymize_text(text: &str, pseudonyms: &HashMap<AthleteID, AthleteID>) -> String {
+    let mut text = text.to_string();
+    for (athlete_id, pseudonym) in pseudonyms {
+        text = text.replace(&format!("{} {}", athlete_id.name(), athlete_id.surname()),
+                            &format!("{} {}", pseudonym.name(), pseudonym.surname()));
+    }
+
+    let names: HashSet<String> = pseudonyms
+        .keys()
+        .flat_map(|athlete_id| [athlete_id.name(), athlete_id.surname()])
+        .collect();
+    let mut pseudonymized = String::with_capacity(text.len());
+    let mut word = String::new();
+    for character in text.chars() {
+        if character.is_alphanumeric() {
+            word.push(character);
+            continue;
+        }
+        pseudonymized.push_str(if names.contains(&word) { ANONYMOUS_NAME } else { &word });
+        pseudonymized.push(character);
+        word.clear();
+    }
+    pseudonymized.push_str(if names.contains(&word) { ANONYMOUS_NAME } else { &word });
+    pseudonymized
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use chrono::{Duration, TimeZone, Utc};
+    use crate::certificate_generation::{Athlete, AthleteID, CompetitionType};
+    use crate::competitions::Competition;
+    use super::{assign_pseudonyms, is_expired, pseudonym, pseudonymize_text};
+
+    fn get_athlete(name: &str, surname: &str, starting_number: Option<u16>) -> Athlete {
+        Athlete::new(name, surname, Utc.with_ymd_and_hms(1985, 6, 15, 0, 0, 0).single(), "M",
+                     HashMap::new(), CompetitionType::Decathlon, starting_number, None, Some("L".to_string()))
+    }
+
+    #[test]
+    fn pseudonyms() {
+        let athletes = vec![
+            get_athlete("Max", "Mustermann", Some(12)),
+            get_athlete("Anonym", "0003", Some(7)),
+            get_athlete("Erika", "Musterfrau", Some(3)),
+            get_athlete("Hans", "Huber", None),
+        ];
+        let pseudonyms = assign_pseudonyms(&athletes);
+
+        assert_eq!(pseudonyms.len(), 3);
+        assert_eq!(pseudonyms[&AthleteID::new("Erika", "Musterfrau")], AthleteID::new("Anonym", "0004"));
+        assert_eq!(pseudonyms[&AthleteID::new("Max", "Mustermann")], AthleteID::new("Anonym", "0005"));
+        assert_eq!(pseudonyms[&AthleteID::new("Hans", "Huber")], AthleteID::new("Anonym", "0006"));
+        assert_eq!(pseudonym(&pseudonyms, &AthleteID::new("Anonym", "0003")), AthleteID::new("Anonym", "0003"));
+        assert_eq!(pseudonym(&pseudonyms, &AthleteID::new("Franz", "Gelöscht")), AthleteID::new("Anonym", "0000"));
+        assert_eq!(pseudonymize_text("Max Mustermann hilft Erika, Hubertus übernimmt für Huber", &pseudonyms),
+                   "Anonym 0005 hilft Anonym, Hubertus übernimmt für Anonym");
+
+        let mut athlete = athletes[0].clone();
+        let age_group = athlete.age_group();
+        athlete.anonymize("Anonym", "0005");
+        assert_eq!(athlete.full_name(), "Anonym 0005");
+        assert_eq!(athlete.age_group(), age_group);
+        assert_eq!(athlete.birth_date(), Utc.with_ymd_and_hms(1985, 1, 1, 0, 0, 0).single());
+    }
+
+    #[test]
+    fn expired_competitions() {
+        let mut competition = Competition::from_json(r#"{"number": "28", "name": "Favoritner Jedermann Zehnkampf",
+            "start_date": 1695427200, "end_date": 1695513600}"#).expect("Competition should be valid");
+        let retention_period = Duration::days(365);
+
+        assert!(!is_expired(&competition, retention_period, competition.end_date + Duration::days(100)));
+        assert!(is_expired(&competition, retention_period, competition.end_date + Duration::days(400)));
+
+        competition.anonymized = true;
+        assert!(!is_expired(&competition, retention_period, competition.end_date + Duration::days(400)));
+    }
+}
@@ -0,0 +1,191 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+
+use crate::audit::{AuditAction, AuditRecord};
+use crate::certificate_generation::{Achievement, AchievementID, AthleteID};
+use crate::Storage;
+
+/// Role of the user who changes a result, sent by the client
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct RoleQuery {
+    pub role: Option<String>,
+}
+
+/// Filter of the result history, all given fields have to match
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct ResultHistoryQuery {
+    pub name: Option<String>,
+    pub surname: Option<String>,
+    pub discipline: Option<String>,
+    /// Id of the last event which is replayed, all events if not given
+    pub until: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct RestoreQuery {
+    /// The result is restored to the value after this event
+    pub event_id: String,
+    pub role: Option<String>,
+}
+
+/// Change of a result, taken from the audit log
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ResultEvent {
+    pub id: String,
+    pub timestamp: DateTime<Utc>,
+    pub athlete_id: AthleteID,
+    pub discipline: String,
+    pub role: Option<String>,
+    pub old: Option<Achievement>,
+    pub new: Option<Achievement>,
+}
+
+impl ResultEvent {
+    fn from_record(record: AuditRecord) -> Option<Self> {
+        match record.action {
+            AuditAction::ResultChange { athlete_id, discipline, role, old, new } => Some(ResultEvent {
+                id: record.id,
+                timestamp: record.timestamp,
+                athlete_id,
+                discipline,
+                role,
+                old: old.map(|old| *old),
+                new: new.map(|new| *new),
+            }),
+            _ => None,
+        }
+    }
+
+    fn achievement_id(&self) -> AchievementID {
+        AchievementID {
+            athlete_id: Some(self.athlete_id.clone()),
+            name: self.discipline.clone(),
+            athlete_name: Some(self.athlete_id.full_name()),
+        }
+    }
+}
+
+impl ResultHistoryQuery {
+    fn matches(&self, event: &ResultEvent) -> bool {
+        self.name.as_ref().is_none_or(|name| *name == event.athlete_id.name())
+            && self.surname.as_ref().is_none_or(|surname| *surname == event.athlete_id.surname())
+            && self.discipline.as_ref().is_none_or(|discipline| *discipline == event.discipline)
+    }
+}
+
+/// Result changes of the audit log which match the query, oldest first
+pub fn result_events(records: Vec<AuditRecord>, query: &ResultHistoryQuery) -> Vec<ResultEvent> {
+    let mut events: Vec<ResultEvent> = records
+        .into_iter()
+        .filter_map(ResultEvent::from_record)
+        .filter(|event| query.matches(event))
+        .collect();
+    events.sort_by(|event, other| event.id.cmp(&other.id));
+    events
+}
+
+/// Derive the result of one athlete and discipline from its events (oldest first). Results entered before the
+/// history was recorded are taken from the first event.
+pub fn replay(events: &[ResultEvent], until: Option<&str>) -> Option<Achievement> {
+    let mut result = events.first().and_then(|event| event.old.clone());
+    for event in events {
+        if until.is_some_and(|until| event.id.as_str() > until) {
+            break;
+        }
+        result = event.new.clone();
+    }
+    result
+}
+
+/// Store the new result (None deletes it) together with the event of the change. Both are written in one
+/// transaction, so the stored result is always the result of the last event. Nothing is stored if the result did
+/// not change.
+pub async fn change_result(
+    storage: &(dyn Storage + Send + Sync),
+    achievement_id: &AchievementID,
+    role: Option<String>,
+    old: Option<Achievement>,
+    mut new: Option<Achievement>,
+) -> Result<Option<Achievement>, Box<dyn Error>> {
+    if let Some(new) = &mut new {
+        new.compute_final_result();
+    }
+    if new == old {
+        return Ok(new);
+    }
+    let athlete_id = achievement_id.athlete_id().ok_or("Athlete ID not given")?;
+    let record = AuditRecord::new(AuditAction::ResultChange {
+        athlete_id,
+        discipline: achievement_id.name.clone(),
+        role,
+        old: old.map(Box::new),
+        new: new.clone().map(Box::new),
+    });
+    storage.store_result_change(achievement_id, new.as_ref(), &record).await?;
+    Ok(new)
+}
+
+/// Result of one athlete and discipline replayed from the history
+pub async fn replay_result(storage: &(dyn Storage + Send + Sync), query: &ResultHistoryQuery) -> Result<Option<Achievement>, Box<dyn Error>> {
+    if query.name.is_none() || query.surname.is_none() || query.discipline.is_none() {
+        return Err(Box::from("Name, surname and discipline are needed to replay a result"));
+    }
+    let events = result_events(storage.get_audit_records().await?, query);
+    Ok(replay(&events, query.until.as_deref()))
+}
+
+/// Set the result to its value after the event, the restore is recorded as a new change in the same transaction
+pub async fn restore_result(storage: &(dyn Storage + Send + Sync), query: &RestoreQuery) -> Result<Option<Achievement>, Box<dyn Error>> {
+    let event = result_events(storage.get_audit_records().await?, &ResultHistoryQuery::default())
+        .into_iter()
+        .find(|event| event.id == query.event_id)
+        .ok_or(format!("Result change {} not found", query.event_id))?;
+    let achievement_id = event.achievement_id();
+
+    let old = storage.get_achievement(&achievement_id).await;
+    change_result(storage, &achievement_id, query.role.clone(), old, event.new).await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::audit::{AuditAction, AuditRecord};
+    use crate::certificate_generation::{Achievement, AthleteID};
+    use super::{replay, result_events, ResultHistoryQuery};
+
+    fn get_result(final_result: &str) -> Achievement {
+        let json = format!(r#"{{"Time": {{"name": "100 Meter Lauf", "final_result": {final_result}, "unit": "s"}}}}"#);
+        Achievement::from_json(&json).expect("Achievement should be parsable")
+    }
+
+    fn get_record(id: &str, athlete: &str, old: Option<Achievement>, new: Option<Achievement>) -> AuditRecord {
+        let mut record = AuditRecord::new(AuditAction::ResultChange {
+            athlete_id: AthleteID::new(athlete, "Muster"),
+            discipline: "100 Meter Lauf".to_string(),
+            role: Some("Helper".to_string()),
+            old: old.map(Box::new),
+            new: new.map(Box::new),
+        });
+        record.id = id.to_string();
+        record
+    }
+
+    #[test]
+    fn replay_history() {
+        let records = vec![
+            get_record("3", "Max", Some(get_result("12.5")), None),
+            get_record("1", "Max", Some(get_result("12.1")), Some(get_result("12.3"))),
+            get_record("2", "Max", Some(get_result("12.3")), Some(get_result("12.5"))),
+            get_record("4", "Erika", None, Some(get_result("13.0"))),
+        ];
+        let query = ResultHistoryQuery { name: Some("Max".to_string()), ..Default::default() };
+        let events = result_events(records, &query);
+        assert_eq!(events.iter().map(|event| event.id.as_str()).collect::<Vec<_>>(), vec!["1", "2", "3"]);
+
+        // Current state: the result was deleted
+        assert_eq!(replay(&events, None), None);
+        assert_eq!(replay(&events, Some("2")), Some(get_result("12.5")));
+        // Value entered before the history was recorded
+        assert_eq!(replay(&events, Some("0")), Some(get_result("12.1")));
+    }
+}
@@ -1,5 +1,6 @@
 use async_trait::async_trait;
-use chrono::{DateTime, TimeZone, Utc};
+use chrono::{DateTime, FixedOffset, LocalResult, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
 use env_logger::filter;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
@@ -8,15 +9,40 @@ use std::collections::HashMap;
 use std::error::Error;
 use std::hash::Hash;
 
+mod attempts;
+mod delay_estimation;
+mod discipline_state;
+mod ical_export;
+mod seeding;
+mod time_table;
+mod time_table_generation;
+mod time_table_merge;
+mod time_table_validation;
+mod venue;
+
+pub use attempts::{next_attempt, record_attempt, Attempt};
+pub use delay_estimation::{estimate_start_times, estimate_time_group};
+pub use discipline_state::{missing_results, StateChange};
+pub use ical_export::{ical_feed, LocationQuery};
+pub use seeding::{SeedingInfo, SeedingStrategy};
+pub use time_table::{SchemaError, TimeTable};
+pub use time_table_generation::{generate_time_table_for_storage, ScheduleRequest};
+pub use time_table_merge::{TimeTableChanges, UploadSummary};
+pub use time_table_validation::validate_time_table;
+pub use venue::{station_occupancy, Station};
+
 #[async_trait]
 pub trait TimePlanStorage {
     async fn get_time_group(&self, group_id: &TimeGroupID) -> Option<TimeGroup>;
 
-    async fn store_time_plan(&self, time_table: Value) -> Result<String, Box<dyn Error>>;
+    /// Store the time table, stored time groups are merged with the upload so their progress is kept
+    async fn store_time_plan(&self, time_table: Value) -> Result<TimeTableChanges, Box<dyn Error>>;
 
     async fn store_time_group(&self, group: TimeGroup) -> Result<String, Box<dyn Error>>;
 
     async fn get_all_athlete_states(&self) -> Result<HashMap<String, bool>, Box<dyn Error>>;
+
+    async fn get_time_groups(&self) -> Result<Vec<TimeGroup>, Box<dyn Error>>;
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
@@ -49,9 +75,17 @@ impl Athlete {
             self.surname.clone().unwrap_or("".to_string())
         )
     }
+
+    /// Rename the athlete if its id is contained in the map of new (name, surname) pairs
+    fn rename(&mut self, new_names: &HashMap<String, (String, String)>) {
+        if let Some((name, surname)) = new_names.get(&self.athlete_id()) {
+            self.name = Some(name.clone());
+            self.surname = Some(surname.clone());
+        }
+    }
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 pub enum DisciplineType {
     Height,
     Distance,
@@ -60,7 +94,7 @@ pub enum DisciplineType {
 }
 
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 pub enum TryOrderType {
     Standard,
     Once, // only one try
@@ -80,28 +114,39 @@ pub struct Discipline {
     #[serde(skip_serializing_if = "Option::is_none")]
     time_started: Option<DateTime<Utc>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    time_finished: Option<DateTime<Utc>>
+    time_finished: Option<DateTime<Utc>>,
+    /// Planned duration in minutes, estimated from the discipline type if not given in the time table
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    duration: Option<i64>,
+    /// Start time estimated from the progress of the competition, only set for API responses and never read back
+    #[serde(default, skip_deserializing, skip_serializing_if = "Option::is_none")]
+    estimated_start_time: Option<DateTime<Utc>>,
+    /// Start times in the time zone of the venue, only set for API responses (see `TimeGroup::localize`)
+    #[serde(default, skip_deserializing, skip_serializing_if = "Option::is_none")]
+    local_start_time: Option<DateTime<FixedOffset>>,
+    #[serde(default, skip_deserializing, skip_serializing_if = "Option::is_none")]
+    local_estimated_start_time: Option<DateTime<FixedOffset>>,
+    /// Seeding of the runs of track disciplines, see `Discipline::seeding` for the default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    seeding: Option<SeedingStrategy>,
+    /// Seeding as given in the time table, uploads are compared against it so that a seeding changed through the
+    /// API or the seed of a random draw is kept
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    time_table_seeding: Option<SeedingStrategy>,
+    /// Athletes (by athlete id) who retired from the discipline and are skipped when calling up attempts
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    retired: Vec<String>,
 }
 
 impl Discipline {
-    fn change_state(&mut self, new_state: DisciplineState) {
-        match new_state {
-            DisciplineState::Active => {
-                self.time_started = Some(chrono::offset::Utc::now())
-            },
-            DisciplineState::BeforeStart => {},
-            DisciplineState::Finished => {
-                self.time_finished = Some(chrono::offset::Utc::now())
-            }
-        }
-        self.state = new_state;
-
-    }
-
     pub fn name(&self) -> &str {
         &self.name
     }
 
+    pub fn location(&self) -> &str {
+        &self.location
+    }
+
     pub fn discipline_type(&self) -> &DisciplineType {
         &self.discipline_type
     }
@@ -110,13 +155,29 @@ impl Discipline {
         &self.starting_order
     }
 
+    pub fn start_time(&self) -> DateTime<Utc> {
+        self.start_time
+    }
+
+    /// Planned start time in the given time zone
+    pub fn local_start_time(&self, time_zone: Tz) -> DateTime<Tz> {
+        self.start_time.with_timezone(&time_zone)
+    }
+
     pub fn is_finished(&self) -> bool{
         &self.state == &DisciplineState::Finished
     }
+
+    /// Set the local start times, used in API responses next to the UTC times
+    pub fn localize(&mut self, time_zone: Tz) {
+        let local = |time: &DateTime<Utc>| time.with_timezone(&time_zone).fixed_offset();
+        self.local_start_time = Some(local(&self.start_time));
+        self.local_estimated_start_time = self.estimated_start_time.as_ref().map(local);
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
-enum DisciplineState {
+pub enum DisciplineState {
     BeforeStart,
     Active,
     Finished,
@@ -129,34 +190,139 @@ pub enum StartingOrder {
     NoOrder,
 }
 
+/// Number of lanes if the competition does not define it
+pub const DEFAULT_LANES: usize = 6;
+
+fn default_lanes() -> usize {
+    DEFAULT_LANES
+}
+
+/// Time zone of the venue if the competition does not define it
+pub const DEFAULT_TIME_ZONE: Tz = chrono_tz::Europe::Vienna;
+
+fn default_time_zone() -> Tz {
+    DEFAULT_TIME_ZONE
+}
+
+/// Parse the local date ("dd.mm.YYYY") and time ("HH:MM") of the time table in the time zone of the venue
+fn parse_local_time(day: &str, time: &str, time_zone: Tz) -> Result<DateTime<Utc>, Box<dyn Error>> {
+    let date_str = format!("{} {}", day.trim(), time.trim());
+    let local = NaiveDateTime::parse_from_str(&date_str, "%d.%m.%Y %H:%M")
+        .map_err(|e| format!("Time string ({date_str}) could not be parsed: {e}"))?;
+    match time_zone.from_local_datetime(&local) {
+        LocalResult::Single(time) => Ok(time.with_timezone(&Utc)),
+        // Clocks are set back, the time exists twice
+        LocalResult::Ambiguous(earliest, _) => Ok(earliest.with_timezone(&Utc)),
+        LocalResult::None => Err(Box::from(format!("{date_str} does not exist in {time_zone} (clocks are set forward)"))),
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Run {
     name: String,
-    athletes: (
-        Option<Athlete>,
-        Option<Athlete>,
-        Option<Athlete>,
-        Option<Athlete>,
-        Option<Athlete>,
-        Option<Athlete>,
-    ),
+    /// Athlete per lane, starting with lane 1
+    athletes: Vec<Option<Athlete>>,
 }
 
 impl Run {
-    pub fn athletes(
-        &self,
-    ) -> &(
-        Option<Athlete>,
-        Option<Athlete>,
-        Option<Athlete>,
-        Option<Athlete>,
-        Option<Athlete>,
-        Option<Athlete>,
-    ) {
+    fn rename_athletes(&mut self, new_names: &HashMap<String, (String, String)>) {
+        for athlete in self.athletes.iter_mut().flatten() {
+            athlete.rename(new_names);
+        }
+    }
+
+    /// Remove the athlete from its lane, returns false if the athlete is not part of the run
+    fn remove_athlete(&mut self, athlete: &Athlete) -> bool {
+        match self.athletes.iter_mut().find(|lane| lane.as_ref() == Some(athlete)) {
+            Some(lane) => {
+                *lane = None;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Put the athlete in the first free lane, returns false if all lanes are taken
+    fn add_athlete(&mut self, athlete: &Athlete) -> bool {
+        match self.athletes.iter_mut().find(|lane| lane.is_none()) {
+            Some(lane) => {
+                *lane = Some(athlete.clone());
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn athletes(&self) -> &[Option<Athlete>] {
         &self.athletes
     }
 }
 
+/// Add the athletes to free lanes of the runs, additional runs are added if all lanes are taken
+fn add_to_runs(runs: &mut Vec<Run>, athletes: &[Athlete], lanes: usize) {
+    for athlete in athletes {
+        if runs.iter_mut().any(|run| run.add_athlete(athlete)) {
+            continue;
+        }
+        let mut run_athletes = vec![None; lanes.max(1)];
+        run_athletes[0] = Some(athlete.clone());
+        runs.push(Run { name: format!("Lauf {}", runs.len() + 1), athletes: run_athletes });
+    }
+}
+
+/// Youth groups are named after their age class, the decathlon groups "Gruppe ...". The athletes of youth groups are
+/// sorted by gender.
+fn is_youth_group(group_name: &str) -> bool {
+    !group_name.contains("Gruppe")
+}
+
+/// Age groups which run the hurdles in the lane (lowest hurdles in the first lane, then the inner and outer lanes)
+fn hurdle_lane_age_groups(lane: usize, lanes: usize) -> Vec<&'static str> {
+    if lane == 0 {
+        vec!["M70", "M60", "W70", "W60", "W50", "W40"]
+    } else if lane <= lanes.saturating_sub(1) / 2 {
+        vec!["M50", "M40", "W"]
+    } else {
+        vec!["M", "S-M", "S-W"]
+    }
+}
+
+/// Put the athlete in the first free lane of the runs which fits the hurdle height of the age group
+fn add_to_hurdle_lane(runs: &mut [Run], athlete: &Athlete) -> bool {
+    let age_group = athlete.age_group.clone().unwrap_or("M".to_string());
+    runs.iter_mut().any(|run| {
+        let lanes = run.athletes.len();
+        match run.athletes
+            .iter_mut()
+            .enumerate()
+            .find(|(lane, athlete)| athlete.is_none() && hurdle_lane_age_groups(*lane, lanes).contains(&age_group.as_str())) {
+            Some((_, lane)) => {
+                *lane = Some(athlete.clone());
+                true
+            }
+            None => false,
+        }
+    })
+}
+
+/// Add the athletes to the hurdle runs like the hurdle order: every athlete gets a free lane of the hurdle height of
+/// the age group, an additional run is added if no such lane is free. Other age groups are added like in add_to_runs.
+fn add_to_hurdle_runs(runs: &mut Vec<Run>, athletes: &[Athlete], lanes: usize) {
+    let mut other_athletes = vec![];
+    for athlete in athletes {
+        if add_to_hurdle_lane(runs, athlete) {
+            continue;
+        }
+        let mut run = Run { name: format!("Lauf {}", runs.len() + 1), athletes: vec![None; lanes.max(1)] };
+        if add_to_hurdle_lane(std::slice::from_mut(&mut run), athlete) {
+            runs.push(run);
+        } else {
+            other_athletes.push(athlete.clone());
+        }
+    }
+    add_to_runs(runs, &other_athletes, lanes);
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct TimeGroup {
     name: String,
@@ -164,6 +330,15 @@ pub struct TimeGroup {
     default_run_order: Vec<Run>,
     disciplines: Vec<Discipline>,
     current_discipline: usize,
+    /// Number of lanes used for the track starting orders
+    #[serde(default = "default_lanes")]
+    lanes: usize,
+    /// IANA time zone of the venue, the times of the time table are local times
+    #[serde(default = "default_time_zone")]
+    time_zone: Tz,
+    /// Incremented with every write, used for conditional writes
+    #[serde(default)]
+    version: u64,
 }
 
 impl TimeGroup {
@@ -173,10 +348,12 @@ impl TimeGroup {
         date_info: &HashMap<String, String>,
         discipline_starting_order_type: &HashMap<String, String>,
         athletes: Option<Vec<Athlete>>,
+        lanes: usize,
+        time_zone: Tz,
     ) -> Result<Self, Box<dyn Error>> {
-        let youth_group = !group_name.contains("Gruppe"); // Sort by gender for youth groups
+        let youth_group = is_youth_group(group_name);
         let (default_athlete_order, default_run_order, default_hurdle_order) =
-            create_default_athlete_order(athletes, youth_group);
+            create_default_athlete_order(athletes, youth_group, lanes);
 
         let mut disciplines = vec![];
         match group_info {
@@ -199,19 +376,7 @@ impl TimeGroup {
                                     .get(&day_name)
                                     .ok_or(Box::from("Day not found in dates") as Box<dyn Error>)?;
 
-                                let date_str = format!("{day} {time}:00 +0200");
-
-                                match DateTime::parse_from_str(
-                                    date_str.as_str(),
-                                    "%d.%m.%Y %H:%M:%S %z",
-                                ) {
-                                    Ok(parsed) => Utc.from_utc_datetime(&parsed.naive_utc()),
-                                    Err(e) => {
-                                        return Err(Box::from(format!(
-                                            "Time string ({date_str}) could not be parsed: {e}"
-                                        )))
-                                    }
-                                }
+                                parse_local_time(day, time, time_zone)?
                             }
                             _ => return Err(Box::from("Time information not parsable as String")),
                         },
@@ -240,14 +405,9 @@ impl TimeGroup {
                             }
                         };
 
+                    // Track orders are seeded after the discipline is built
                     let starting_order: StartingOrder = match starting_order_type.trim() {
-                        "Track" => {
-                            if discipline_name.as_str() == "110 Meter Hürden" {
-                                StartingOrder::Track(default_hurdle_order.clone())
-                            } else {
-                                StartingOrder::Track(default_run_order.clone())
-                            }
-                        }
+                        "Track" => StartingOrder::Track(vec![]),
                         "Default" => StartingOrder::Default(default_athlete_order.clone()),
                         "None" => StartingOrder::NoOrder,
                         x => {
@@ -289,7 +449,21 @@ impl TimeGroup {
                         None => TryOrderType::Standard
                     };
 
-                    let discipline = Discipline {
+                    let duration = match discipline_info.get("duration") {
+                        Some(duration_value) => match duration_value.as_i64() {
+                            Some(duration) => Some(duration),
+                            None => return Err(Box::from(format!("Duration of {discipline_name} not parsable as minutes"))),
+                        },
+                        None => None,
+                    };
+
+                    let seeding: Option<SeedingStrategy> = match discipline_info.get("seeding") {
+                        Some(seeding_value) => Some(serde_json::from_value(seeding_value.clone())
+                            .map_err(|e| format!("Seeding of {discipline_name} not parsable: {e}"))?),
+                        None => None,
+                    };
+
+                    let mut discipline = Discipline {
                         name: discipline_name.clone(),
                         location,
                         start_time,
@@ -298,8 +472,20 @@ impl TimeGroup {
                         discipline_type,
                         try_order_type,
                         time_finished: None,
-                        time_started: None
+                        time_started: None,
+                        duration,
+                        estimated_start_time: None,
+                        local_start_time: None,
+                        local_estimated_start_time: None,
+                        time_table_seeding: seeding.clone(),
+                        seeding,
+                        retired: Vec::new(),
                     };
+                    if let StartingOrder::Track(_) = discipline.starting_order {
+                        let runs = discipline.seed_runs(&default_athlete_order, &default_run_order,
+                                                        &default_hurdle_order, lanes, &SeedingInfo::default());
+                        discipline.starting_order = StartingOrder::Track(runs);
+                    }
 
                     disciplines.push(discipline)
                 }
@@ -309,24 +495,61 @@ impl TimeGroup {
 
         disciplines.sort_by(|a, b| a.start_time.cmp(&b.start_time));
 
-        let group = TimeGroup {
+        let mut group = TimeGroup {
             name: group_name.clone(),
-            default_athlete_order,
+            default_athlete_order: default_athlete_order.clone(),
             default_run_order,
             disciplines,
             current_discipline: 0,
+            lanes,
+            time_zone,
+            version: 0,
         };
+        group.seed_field_orders(&default_athlete_order, &SeedingInfo::default(), false);
 
         Ok(group)
     }
     pub fn name(&self) -> &str {
         &self.name
     }
+    pub fn time_zone(&self) -> Tz {
+        self.time_zone
+    }
+    /// Version of the stored time group, 0 if it was never stored
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+    /// Set the local start times of all disciplines, used in API responses next to the UTC times. Time groups are
+    /// stored without the local and estimated start times, they are only set on the copy for the response.
+    pub fn localize(&mut self) {
+        let time_zone = self.time_zone;
+        self.disciplines.iter_mut().for_each(|discipline| discipline.localize(time_zone));
+    }
+    /// Change the number of lanes, used when the track starting orders are created the next time
+    pub fn set_lanes(&mut self, lanes: usize) {
+        self.lanes = lanes;
+    }
+    /// Rename athletes in all starting orders (key: athlete id, value: new name and surname)
+    pub fn rename_athletes(&mut self, new_names: &HashMap<String, (String, String)>) {
+        self.default_athlete_order.iter_mut().for_each(|athlete| athlete.rename(new_names));
+        self.default_run_order.iter_mut().for_each(|run| run.rename_athletes(new_names));
+        for discipline in &mut self.disciplines {
+            match &mut discipline.starting_order {
+                StartingOrder::Default(athletes) => athletes.iter_mut().for_each(|athlete| athlete.rename(new_names)),
+                StartingOrder::Track(runs) => runs.iter_mut().for_each(|run| run.rename_athletes(new_names)),
+                StartingOrder::NoOrder => {}
+            }
+        }
+    }
     pub fn get_disciplines(&self) -> &Vec<Discipline> {
         &self.disciplines
     }
-    pub fn get_next_discipline(&self) -> &Discipline {
-        &self.disciplines[self.current_discipline + 1]
+    /// First discipline after the current one which has not started yet, None after the last discipline
+    pub fn get_next_discipline(&self) -> Option<&Discipline> {
+        let current = self.current_index()?;
+        self.disciplines[current + 1..]
+            .iter()
+            .find(|discipline| discipline.state == DisciplineState::BeforeStart)
     }
     pub fn get_discipline(&self, discipline_name: &str) -> Option<&Discipline> {
         let mut selected_discipline = None;
@@ -337,72 +560,16 @@ impl TimeGroup {
         }
         selected_discipline
     }
-    pub fn get_current_discipline(&mut self) -> &Discipline {
-        while self.current_discipline < self.disciplines.len() -1 {
-            let active_discipline: &Discipline = &self.disciplines[self.current_discipline];
-            match active_discipline.state {
-                DisciplineState::Active => break,
-                DisciplineState::BeforeStart => break,
-                DisciplineState::Finished => {
-                    self.current_discipline += 1;
-                }
-            }
-        }
-        &self.disciplines[self.current_discipline]
-    }
-    pub fn get_mut_current_discipline(&mut self) -> &mut Discipline {
-        match self.disciplines[self.current_discipline].state {
-            DisciplineState::Active => &mut self.disciplines[self.current_discipline],
-            DisciplineState::BeforeStart => &mut self.disciplines[self.current_discipline],
-            DisciplineState::Finished => {
-                if self.current_discipline < self.disciplines.len() - 1 {
-                    self.current_discipline += 1;
-                }
-                &mut self.disciplines[self.current_discipline]
-            }
-        }
+    /// Index of the first discipline which is not finished, the last one if all are finished
+    fn current_index(&self) -> Option<usize> {
+        self.disciplines
+            .iter()
+            .position(|discipline| discipline.state != DisciplineState::Finished)
+            .or(self.disciplines.len().checked_sub(1))
     }
-    pub fn change_discipline_state(&mut self, json_body: &str) -> Result<String, Box<dyn Error>> {
-        let info = serde_json::from_str(json_body)?;
-        let discipline_name: String;
-        let new_state: DisciplineState;
-
-        match info {
-            Value::Object(map) => {
-                discipline_name = map
-                    .get("name")
-                    .ok_or(Box::from("Discipline name not given") as Box<dyn Error>)?
-                    .to_string()
-                    .replace("\"", "");
-                let state_value = map
-                    .get("state")
-                    .ok_or(Box::from("Discipline name not given") as Box<dyn Error>)?;
-                new_state = serde_json::from_value(state_value.clone())?;
-            }
-            _ => return Err(Box::from("Could not parse new state information")),
-        }
-
-        let mut discipline_updated = false;
-        if self.get_current_discipline().name == discipline_name {
-            // most likely the current discipline is changed
-            let discipline = self.get_mut_current_discipline();
-            discipline.change_state(new_state);
-            discipline_updated = true;
-        } else {
-            // Changing state of a different discipline
-            for discipline in &mut self.disciplines {
-                if discipline.name == discipline_name {
-                    discipline.change_state(new_state);
-                    discipline_updated = true;
-                    break;
-                }
-            }
-        }
-        if discipline_updated {
-            Ok(String::from("Discipline state updated"))
-        } else {
-            Err(Box::from("No discipline updated"))
-        }
+    /// Current discipline, None if the group has no disciplines
+    pub fn get_current_discipline(&self) -> Option<&Discipline> {
+        self.disciplines.get(self.current_index()?)
     }
     pub fn get_default_starting_order(&self) -> StartingOrder {
         StartingOrder::Default(self.default_athlete_order.clone())
@@ -445,9 +612,9 @@ impl TimeGroup {
         let mut new_athletes = new_athletes.clone();
         all_athletes.append(&mut new_athletes);
 
-        let youth_group = self.disciplines.len() < 10;
+        let youth_group = is_youth_group(&self.name);
         let (default_athlete_order, default_run_order, default_hurdle_order) =
-            create_default_athlete_order(Some(all_athletes.clone()), youth_group);
+            create_default_athlete_order(Some(all_athletes.clone()), youth_group, self.lanes);
 
         self.default_athlete_order = default_athlete_order.clone();
         self.default_run_order = default_run_order.clone();
@@ -456,18 +623,43 @@ impl TimeGroup {
             discipline.starting_order = match discipline.starting_order {
                 StartingOrder::NoOrder => StartingOrder::NoOrder,
                 StartingOrder::Default(_) => StartingOrder::Default(default_athlete_order.clone()),
-                StartingOrder::Track(_) => {
-                    if discipline.name() == "110 Meter Hürden" {
-                        StartingOrder::Track(default_hurdle_order.clone())
-                    } else {
-                        StartingOrder::Track(default_run_order.clone())
-                    }
-                }
+                StartingOrder::Track(_) => StartingOrder::Track(discipline.seed_runs(
+                    &default_athlete_order, &default_run_order, &default_hurdle_order, self.lanes, &SeedingInfo::default()))
             }
         }
+        self.seed_field_orders(&default_athlete_order, &SeedingInfo::default(), false);
 
         Ok(())
     }
+    /// Add athletes to the end of the existing starting orders of all disciplines which are not finished,
+    /// the orders of the other athletes are kept (e.g. when athletes switch the group)
+    pub fn insert_athletes(&mut self, athletes: &[Athlete]) {
+        let athletes: Vec<Athlete> = athletes
+            .iter()
+            .filter(|athlete| !self.default_athlete_order.contains(athlete))
+            .cloned()
+            .collect();
+        self.default_athlete_order.extend(athletes.iter().cloned());
+        add_to_runs(&mut self.default_run_order, &athletes, self.lanes);
+        for discipline in &mut self.disciplines {
+            if let DisciplineState::Finished = discipline.state {
+                continue;
+            }
+            let seeding = discipline.seeding();
+            match &mut discipline.starting_order {
+                StartingOrder::NoOrder => {}
+                StartingOrder::Default(order) => order.extend(athletes.iter().cloned()),
+                // Runs seeded by age class follow the hurdle order
+                StartingOrder::Track(runs) if seeding == SeedingStrategy::AgeClass => add_to_hurdle_runs(runs, &athletes, self.lanes),
+                StartingOrder::Track(runs) => add_to_runs(runs, &athletes, self.lanes),
+            }
+        }
+    }
+    /// Replace all athletes of the group and rebuild the starting orders (e.g. after a new group allocation)
+    pub fn replace_athletes(&mut self, athletes: Vec<Athlete>) -> Result<(), Box<dyn Error>> {
+        self.default_athlete_order = vec![];
+        self.update_athletes(&athletes)
+    }
     pub fn delete_athlete(&mut self, athlete: Athlete) -> Result<(), Box<dyn Error>> {
         let index = self.default_athlete_order
             .iter()
@@ -475,33 +667,11 @@ impl TimeGroup {
             .ok_or("Athlete not found")?;
         self.default_athlete_order.remove(index);
 
-        let run_index = self.default_run_order.iter().position(|run| {
-            run.athletes.0.as_ref() == Some(&athlete)
-                || run.athletes.1.as_ref() == Some(&athlete)
-                || run.athletes.2.as_ref() == Some(&athlete)
-                || run.athletes.3.as_ref() == Some(&athlete)
-                || run.athletes.4.as_ref() == Some(&athlete)
-                || run.athletes.5.as_ref() == Some(&athlete)
-        });
-        match run_index {
-            Some(run_index) => {
-                let run: &mut Run = self.default_run_order.get_mut(run_index).expect("Run should exist");
-                if run.athletes.0.as_ref() == Some(&athlete) {
-                    run.athletes.0 = None;
-                } else if run.athletes.1.as_ref() == Some(&athlete) {
-                    run.athletes.1 = None;
-                } else if run.athletes.2.as_ref() == Some(&athlete) {
-                    run.athletes.2 = None;
-                } else if run.athletes.3.as_ref() == Some(&athlete) {
-                    run.athletes.3 = None;
-                } else if run.athletes.4.as_ref() == Some(&athlete) {
-                    run.athletes.4 = None;
-                } else if run.athletes.5.as_ref() == Some(&athlete) {
-                    run.athletes.5 = None;
-                }
+        for run in &mut self.default_run_order {
+            if run.remove_athlete(&athlete) {
+                break;
             }
-            None => (),
-        };
+        }
         
         for discipline in &mut self.disciplines {
             discipline.starting_order = match &discipline.starting_order {
@@ -517,33 +687,11 @@ impl TimeGroup {
                     },
                 StartingOrder::Track(current_order) => {
                     let mut new_order = current_order.clone();
-                    let run_index = new_order.iter().position(|run| {
-                        run.athletes.0.as_ref() == Some(&athlete)
-                            || run.athletes.1.as_ref() == Some(&athlete)
-                            || run.athletes.2.as_ref() == Some(&athlete)
-                            || run.athletes.3.as_ref() == Some(&athlete)
-                            || run.athletes.4.as_ref() == Some(&athlete)
-                            || run.athletes.5.as_ref() == Some(&athlete)
-                    });
-                    match run_index {
-                        Some(run_index) => {
-                            let run: &mut Run = new_order.get_mut(run_index).expect("Run should exist");
-                            if run.athletes.0.as_ref() == Some(&athlete) {
-                                run.athletes.0 = None;
-                            } else if run.athletes.1.as_ref() == Some(&athlete) {
-                                run.athletes.1 = None;
-                            } else if run.athletes.2.as_ref() == Some(&athlete) {
-                                run.athletes.2 = None;
-                            } else if run.athletes.3.as_ref() == Some(&athlete) {
-                                run.athletes.3 = None;
-                            } else if run.athletes.4.as_ref() == Some(&athlete) {
-                                run.athletes.4 = None;
-                            } else if run.athletes.5.as_ref() == Some(&athlete) {
-                                run.athletes.5 = None;
-                            }
+                    for run in &mut new_order {
+                        if run.remove_athlete(&athlete) {
+                            break;
                         }
-                        None => (),
-                    };
+                    }
                     StartingOrder::Track(new_order)
                 }
             }
@@ -551,8 +699,55 @@ impl TimeGroup {
 
         Ok(())
     }
-    pub fn reshuffle_athlete_order(&mut self, group_name: String, only_registered_athletes: bool, athlete_states: HashMap<String, bool>) -> Result<String, Box<dyn Error>> {
-        let youth_group = !group_name.contains("Gruppe"); // Sort by gender for youth groups
+    /// Change the seeding strategy of a discipline and regenerate its starting order
+    pub fn change_seeding(&mut self, discipline_name: &str, seeding: SeedingStrategy, info: &SeedingInfo) -> Result<&Discipline, Box<dyn Error>> {
+        let youth_group = is_youth_group(&self.name);
+        let (default_order, run_order, hurdle_order) =
+            create_default_athlete_order(Some(self.default_athlete_order.clone()), youth_group, self.lanes);
+        let lanes = self.lanes;
+
+        let index = self.disciplines
+            .iter()
+            .position(|discipline| discipline.name == discipline_name)
+            .ok_or(format!("Discipline {discipline_name} not found"))?;
+        let discipline = &mut self.disciplines[index];
+        if let DisciplineState::Finished = discipline.state {
+            return Err(Box::from(format!("{discipline_name} is already finished")));
+        }
+        discipline.check_seeding(&seeding)?;
+        discipline.set_seeding(seeding);
+        match discipline.starting_order {
+            StartingOrder::Track(_) => {
+                let runs = discipline.seed_runs(&default_order, &run_order, &hurdle_order, lanes, info);
+                discipline.starting_order = StartingOrder::Track(runs);
+            }
+            _ => self.seed_field_orders(&default_order, info, true),
+        }
+        Ok(&self.disciplines[index])
+    }
+
+    /// Order the field disciplines according to their seeding. The rotation follows the start times.
+    fn seed_field_orders(&mut self, athletes: &[Athlete], info: &SeedingInfo, skip_finished: bool) {
+        let mut field_disciplines: Vec<usize> = (0..self.disciplines.len())
+            .filter(|index| matches!(self.disciplines[*index].starting_order, StartingOrder::Default(_)))
+            .collect();
+        field_disciplines.sort_by_key(|index| self.disciplines[*index].start_time);
+
+        let rotations = field_disciplines.len();
+        for (rotation, index) in field_disciplines.into_iter().enumerate() {
+            let discipline = &mut self.disciplines[index];
+            if skip_finished && matches!(discipline.state, DisciplineState::Finished) {
+                continue;
+            }
+            let order = discipline.seed_order(athletes, rotation, rotations, info);
+            discipline.starting_order = StartingOrder::Default(order);
+        }
+    }
+    /// Regenerate the starting orders of all disciplines which are not finished, track runs are seeded with
+    /// the seeding strategy of the discipline
+    pub fn reshuffle_athlete_order(&mut self, group_name: String, only_registered_athletes: bool, athlete_states: HashMap<String, bool>,
+                                   info: &SeedingInfo) -> Result<String, Box<dyn Error>> {
+        let youth_group = is_youth_group(&group_name);
         let mut athletes = self.default_athlete_order.clone();
         
         if only_registered_athletes {
@@ -566,21 +761,30 @@ impl TimeGroup {
         }
 
         let (default_order, run_order, hurdle_order) =
-            create_default_athlete_order(Some(athletes), youth_group);
+            create_default_athlete_order(Some(athletes), youth_group, self.lanes);
 
-        self.change_starting_order(StartingOrder::Track(run_order), Some(false));
-        self.change_starting_order(StartingOrder::Track(hurdle_order), Some(true));
-        self.change_starting_order(StartingOrder::Default(default_order), None);
+        for discipline in &mut self.disciplines {
+            if let DisciplineState::Finished = discipline.state {
+                continue;
+            }
+            if let StartingOrder::Track(_) = discipline.starting_order {
+                let runs = discipline.seed_runs(&default_order, &run_order, &hurdle_order, self.lanes, info);
+                discipline.starting_order = StartingOrder::Track(runs);
+            }
+        }
+        self.seed_field_orders(&default_order, info, true);
 
         Ok("Updated".to_string())
     }
 }
 
 // Create default order, default run order and default order for hurdles
-// hurdels need in 1. track: M60,W40,W50,W60 and in 2.3. track: AK-W, M40, M50 and in 4.5.6. track: AK-M
+// hurdels need in 1. track: M60,W40,W50,W60 and in the inner tracks: AK-W, M40, M50 and in the outer tracks: AK-M
+// (for six lanes: 1. track, 2.3. track and 4.5.6. track)
 fn create_default_athlete_order(
     athletes: Option<Vec<Athlete>>,
     sort_gender: bool,
+    lanes: usize,
 ) -> (Vec<Athlete>, Vec<Run>, Vec<Run>) {
     let mut default_athlete_order = athletes.unwrap_or_else(|| vec![]);
     if sort_gender {
@@ -601,7 +805,7 @@ fn create_default_athlete_order(
     let mut default_run_order: Vec<Run> = vec![];
     let mut i = 0;
     let mut run_number = 1;
-    let num_tracks = 6;
+    let num_tracks = lanes;
     while i < default_athlete_order.len() {
         let mut athletes: Vec<Athlete>;
 
@@ -628,19 +832,11 @@ fn create_default_athlete_order(
         let mut run_athletes: Vec<Option<Athlete>> = athletes
             .into_iter()
             .map(|athlete| Some(athlete)).collect();
-
-        if run_athletes.len() < 6{
-            let missing_fields = 6 - run_athletes.len();
-            for _ in 0..missing_fields {
-                run_athletes.push(None)
-            }
-        }
-
-        let tuple_athletes = run_athletes.into_iter().collect_tuple().unwrap();
+        run_athletes.resize(num_tracks, None);
 
         let run = Run {
             name: format!("Lauf {}", run_number),
-            athletes: tuple_athletes,
+            athletes: run_athletes,
         };
         default_run_order.push(run);
         run_number += 1;
@@ -650,69 +846,21 @@ fn create_default_athlete_order(
     let mut i = 0;
     let mut run_number = 1;
     while i < default_athlete_order.len() {
-        let mut athletes: (
-            Option<Athlete>,
-            Option<Athlete>,
-            Option<Athlete>,
-            Option<Athlete>,
-            Option<Athlete>,
-            Option<Athlete>,
-        ) = (None, None, None, None, None, None);
-
-        let mut j = 0;
-        while j < 6 {
-            if j == 0 && athletes.0.is_none(){
-                for age_group in vec!["M70", "M60", "W70", "W60", "W50", "W40"] {
-                    if let Some(track_athletes) = track_athletes_map.get_mut(age_group) {
-                        if track_athletes.len() > 0 {
-                            athletes.0 = track_athletes.pop();
-                            i += 1;
-                            break;
-                        }
-                    }
-                }
-            } else if (j == 1 || j == 2) && (athletes.1.is_none() || athletes.2.is_none()) {
-                for age_group in vec!["M50", "M40", "W"] {
-                    if let Some(track_athletes) = track_athletes_map.get_mut(age_group) {
-                        if track_athletes.len() > 0 {
-                            if athletes.1.is_none() {
-                                athletes.1 = track_athletes.pop();
-                                i += 1;
-                                break;
-                            } else if athletes.2.is_none() {
-                                athletes.2 = track_athletes.pop();
-                                i += 1;
-                                break;
-                            }
-                        }
-                    }
-                }
-            } else if j == 3 || j == 4 || j == 5 {
-                for age_group in vec!["M", "S-M", "S-W"] {
-                    if let Some(track_athletes) = track_athletes_map.get_mut(age_group) {
-                        if track_athletes.len() > 0 {
-                            if athletes.3.is_none() {
-                                athletes.3 = track_athletes.pop();
-                                i += 1;
-                                break;
-                            } else if athletes.4.is_none() {
-                                athletes.4 = track_athletes.pop();
-                                i += 1;
-                                break;
-                            }else if athletes.5.is_none() {
-                                athletes.5 = track_athletes.pop();
-                                i+= 1;
-                                break;
-                            }
-                        }
+        let mut athletes: Vec<Option<Athlete>> = vec![None; lanes];
+
+        for (lane, athlete) in athletes.iter_mut().enumerate() {
+            for age_group in hurdle_lane_age_groups(lane, lanes) {
+                if let Some(track_athletes) = track_athletes_map.get_mut(age_group) {
+                    if track_athletes.len() > 0 {
+                        *athlete = track_athletes.pop();
+                        i += 1;
+                        break;
                     }
                 }
             }
-
-            j += 1;
         }
 
-        if athletes == (None, None, None, None, None, None){
+        if athletes.iter().all(Option::is_none) {
             // Break out of loop if no athletes could be assigned to a track
             break
         }
@@ -733,6 +881,38 @@ fn create_default_athlete_order(
 }
 
 
+/// Build the time groups of an uploaded time table without athletes (e.g. to validate it before storing)
+pub fn build_time_groups(time_table: &Value, time_zone: Tz) -> Result<Vec<TimeGroup>, Box<dyn Error>> {
+    let string_map = |key: &str| -> Result<HashMap<String, String>, Box<dyn Error>> {
+        match time_table.get(key) {
+            Some(Value::Object(map)) => Ok(map
+                .iter()
+                .map(|(k, v)| (k.clone(), v.to_string().replace("\"", "")))
+                .collect()),
+            Some(_) => Err(Box::from(format!("{key} information in invalid format"))),
+            None => Err(Box::from(format!("{key} information not found"))),
+        }
+    };
+    let date_info = string_map("Dates")?;
+    let discipline_info = string_map("DisciplineTypes")?;
+
+    match time_table.get("Groups") {
+        Some(Value::Object(group_map)) => group_map
+            .iter()
+            .map(|(group_name, times)| TimeGroup::build(group_name, times, &date_info, &discipline_info, None, DEFAULT_LANES, time_zone))
+            .collect(),
+        Some(_) => Err(Box::from("Group information in invalid format")),
+        None => Err(Box::from("Group information not found")),
+    }
+}
+
+/// Time group and optionally one of its disciplines (e.g. for the starting order of a discipline)
+#[derive(Deserialize, Debug, Clone)]
+pub struct StartingOrderQuery {
+    pub name: Option<String>,
+    pub discipline_name: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, Hash, PartialEq)]
 pub struct TimeGroupID {
     pub name: Option<String>,
@@ -770,3 +950,82 @@ impl DisciplineID {
         self.discipline_name.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{add_to_hurdle_runs, add_to_runs, create_default_athlete_order, parse_local_time, Athlete, Run, DEFAULT_TIME_ZONE};
+
+    fn get_athletes(age_group: &str, count: usize) -> Vec<Athlete> {
+        (0..count)
+            .map(|index| Athlete::new(format!("{age_group} {index}"), "Muster".to_string(), Some(age_group.to_string())))
+            .collect()
+    }
+
+    #[test]
+    fn lane_count() {
+        let mut athletes = get_athletes("M", 12);
+        athletes.extend(get_athletes("M40", 4));
+        athletes.extend(get_athletes("W60", 2));
+
+        let (_, runs, hurdle_runs) = create_default_athlete_order(Some(athletes.clone()), false, 8);
+        assert_eq!(runs.len(), 3);
+        assert!(runs.iter().all(|run| run.athletes().len() == 8));
+        assert_eq!(runs[2].athletes().iter().flatten().count(), 2);
+
+        // Lane 1 for the oldest, lanes 2-4 for the masters, lanes 5-8 for the main class
+        let lanes: Vec<Option<String>> = hurdle_runs[0].athletes()
+            .iter()
+            .map(|athlete| athlete.as_ref().and_then(|athlete| athlete.age_group.clone()))
+            .collect();
+        assert_eq!(lanes[0].as_deref(), Some("W60"));
+        assert!(lanes[1..4].iter().all(|lane| lane.as_deref() == Some("M40")));
+        assert!(lanes[4..].iter().all(|lane| lane.as_deref() == Some("M")));
+        assert_eq!(hurdle_runs.iter().flat_map(|run| run.athletes().iter().flatten()).count(), 18);
+
+        let (_, runs, hurdle_runs) = create_default_athlete_order(Some(athletes), false, 4);
+        assert_eq!(runs.len(), 5);
+        assert!(hurdle_runs.iter().all(|run| run.athletes().len() == 4));
+    }
+
+    #[test]
+    fn daylight_saving_time() {
+        let summer = parse_local_time("20.09.2025", "08:30", DEFAULT_TIME_ZONE).expect("Summer time should be parsable");
+        assert_eq!(summer.to_rfc3339(), "2025-09-20T06:30:00+00:00");
+        let winter = parse_local_time("08.11.2025", "08:30", DEFAULT_TIME_ZONE).expect("Winter time should be parsable");
+        assert_eq!(winter.to_rfc3339(), "2025-11-08T07:30:00+00:00");
+
+        // 02:30 is skipped when the clocks are set forward, 02:30 exists twice when they are set back
+        assert!(parse_local_time("30.03.2025", "02:30", DEFAULT_TIME_ZONE).is_err());
+        let ambiguous = parse_local_time("26.10.2025", "02:30", DEFAULT_TIME_ZONE).expect("Ambiguous time should be parsable");
+        assert_eq!(ambiguous.to_rfc3339(), "2025-10-26T00:30:00+00:00");
+    }
+
+    #[test]
+    fn add_athletes_to_runs() {
+        let (_, mut runs, _) = create_default_athlete_order(Some(get_athletes("M", 7)), false, 4);
+        runs.reverse();
+        let custom_order: Vec<Run> = runs.clone();
+        let new_athletes = get_athletes("W", 3);
+
+        add_to_runs(&mut runs, &new_athletes, 4);
+        assert_eq!(runs.len(), 3);
+        // The custom order of the runs is kept, the free lane is used first
+        assert_eq!(runs[0].athletes()[..3], custom_order[0].athletes()[..3]);
+        assert_eq!(runs[0].athletes()[3].as_ref(), Some(&new_athletes[0]));
+        assert_eq!(runs[1].athletes(), custom_order[1].athletes());
+        assert_eq!(runs[2].athletes().iter().flatten().count(), 2);
+    }
+
+    #[test]
+    fn add_athletes_to_hurdle_runs() {
+        let (_, _, mut hurdle_runs) = create_default_athlete_order(Some(get_athletes("M", 4)), false, 8);
+        let w60 = get_athletes("W60", 2);
+
+        add_to_hurdle_runs(&mut hurdle_runs, &w60, 8);
+        // Only lane 1 has the hurdle height of W60, the second athlete needs a new run
+        assert_eq!(hurdle_runs.len(), 2);
+        assert_eq!(hurdle_runs[0].athletes()[0].as_ref(), Some(&w60[0]));
+        assert_eq!(hurdle_runs[1].athletes()[0].as_ref(), Some(&w60[1]));
+        assert_eq!(hurdle_runs[0].athletes().iter().flatten().count(), 5);
+    }
+}
@@ -0,0 +1,469 @@
+use std::collections::HashMap;
+use std::error::Error;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use super::{Athlete, Discipline, DisciplineType, StartingOrder, TimeGroup, TimeGroupID, TryOrderType};
+use crate::certificate_generation::achievements::HeightResult;
+use crate::certificate_generation::{Achievement, AchievementID, AthleteID};
+use crate::result_history::change_result;
+use crate::Storage;
+
+/// Attempts per athlete in distance disciplines, unless only one try is allowed
+const DISTANCE_ATTEMPTS: usize = 3;
+const DISTANCE_FIELDS: [&str; DISTANCE_ATTEMPTS] = ["first_try", "second_try", "third_try"];
+
+/// Attempts per athlete and height, three failed attempts eliminate the athlete
+const HEIGHT_ATTEMPTS: usize = 3;
+
+/// Result of a single attempt entered by a field judge
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum AttemptResult {
+    /// Valid attempt, distance in meters
+    Distance(f64),
+    /// Invalid attempt
+    Foul,
+    /// Attempt at the given height in centimeters
+    Height { height: i32, cleared: bool },
+    /// Skip the attempt, at heights the remaining attempts of the current height.
+    /// A passed distance attempt is recorded like an invalid one.
+    Pass,
+    /// Athlete does not take part in the rest of the discipline
+    Retire,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Attempt {
+    pub name: String,
+    pub surname: String,
+    /// Attempt number starting at 1 (per height for height disciplines), the next open attempt of the athlete if not given
+    #[serde(default)]
+    pub attempt: Option<usize>,
+    pub result: AttemptResult,
+}
+
+/// Athlete who is up next
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NextAttempt {
+    pub name: String,
+    pub surname: String,
+    pub attempt: usize,
+    /// Bar height in centimeters for height disciplines
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<i32>,
+}
+
+impl NextAttempt {
+    fn new(athlete: &Athlete, attempt: usize, height: Option<i32>) -> Self {
+        NextAttempt {
+            name: athlete.name.clone().unwrap_or_default(),
+            surname: athlete.surname.clone().unwrap_or_default(),
+            attempt,
+            height,
+        }
+    }
+}
+
+/// Achievement of the athlete after an attempt and who is up next (None if the discipline is done)
+#[derive(Serialize, Debug, Clone)]
+pub struct AttemptUpdate {
+    pub achievement: Option<Achievement>,
+    pub next: Option<NextAttempt>,
+}
+
+/// Height increase if the athlete has no achievement yet, same as in the judging UI
+fn default_height_increase(discipline_name: &str) -> i32 {
+    if discipline_name == "Hochsprung" { 4 } else { 20 }
+}
+
+/// Progress of an athlete in a height discipline. The tries contain a block of three marks per height
+/// ("O" cleared, "X" failed, "/" passed), e.g. "///-O//-XO/-XXX".
+struct HeightProgress {
+    start_height: i32,
+    height_increase: i32,
+    heights: Vec<String>,
+}
+
+impl HeightProgress {
+    fn from_result(result: &HeightResult) -> Self {
+        let tries = result.tries();
+        HeightProgress {
+            start_height: result.start_height(),
+            height_increase: result.height_increase(),
+            heights: if tries.is_empty() { vec![] } else { tries.split('-').map(String::from).collect() },
+        }
+    }
+
+    fn is_eliminated(&self) -> bool {
+        self.heights.iter().any(|height| height.chars().nth(HEIGHT_ATTEMPTS - 1) == Some('X'))
+    }
+
+    /// Index of the lowest height the athlete still has attempts at
+    fn open_index(&self) -> usize {
+        self.heights
+            .iter()
+            .position(|height| !height.contains('O') && height.len() < HEIGHT_ATTEMPTS)
+            .unwrap_or(self.heights.len())
+    }
+
+    fn next_height(&self) -> i32 {
+        self.start_height + self.open_index() as i32 * self.height_increase
+    }
+
+    /// Attempts used at the height, passed attempts carried over from the previous height included
+    fn used(&self, index: usize) -> usize {
+        self.heights.get(index).map(String::len).unwrap_or(0)
+    }
+
+    fn index_of(&self, height: i32) -> Result<usize, Box<dyn Error>> {
+        let offset = height - self.start_height;
+        if self.height_increase <= 0 || offset < 0 || offset % self.height_increase != 0 {
+            return Err(Box::from(format!("{height} cm is not a height of the athlete (start {} cm, increase {} cm)",
+                                         self.start_height, self.height_increase)));
+        }
+        Ok((offset / self.height_increase) as usize)
+    }
+
+    fn mark(&mut self, index: usize, attempt: usize, mark: char) {
+        while self.heights.len() <= index {
+            let passed = if self.heights.len() < index { "/".repeat(HEIGHT_ATTEMPTS) } else { String::new() };
+            self.heights.push(passed);
+        }
+        let height = &mut self.heights[index];
+        while height.len() < attempt - 1 {
+            height.push('/');
+        }
+        height.truncate(attempt - 1);
+        height.push(mark);
+        // The remaining attempts of a cleared or passed height are not used
+        if mark != 'X' {
+            while height.len() < HEIGHT_ATTEMPTS {
+                height.push('/');
+            }
+        }
+    }
+
+    /// Pass the remaining attempts of the current height, the used attempts are carried over to the next one
+    fn pass(&mut self) {
+        let index = self.open_index();
+        let used = self.used(index);
+        self.mark(index, HEIGHT_ATTEMPTS, '/');
+        if used > 0 {
+            self.heights.push("/".repeat(used));
+        }
+    }
+
+    fn tries(&self) -> String {
+        self.heights.join("-")
+    }
+}
+
+impl Discipline {
+    /// Athletes of a field discipline in starting order, without the retired ones
+    fn active_athletes(&self) -> Result<Vec<&Athlete>, Box<dyn Error>> {
+        match (&self.discipline_type, &self.starting_order) {
+            (DisciplineType::Distance | DisciplineType::Height, StartingOrder::Default(athletes)) => Ok(athletes
+                .iter()
+                .filter(|athlete| !self.retired.contains(&athlete.athlete_id()))
+                .collect()),
+            _ => Err(Box::from(format!("{} has no attempts to judge", self.name))),
+        }
+    }
+
+    fn distance_attempts(&self) -> usize {
+        match self.try_order_type {
+            TryOrderType::Once => 1,
+            _ => DISTANCE_ATTEMPTS,
+        }
+    }
+
+    /// Athlete who is up next, achievements by athlete id. Distance attempts follow the try order type, height
+    /// attempts go round by round at the lowest open height. Athletes without a start height are not called up
+    /// at heights.
+    pub fn next_attempt(&self, achievements: &HashMap<String, Achievement>) -> Result<Option<NextAttempt>, Box<dyn Error>> {
+        let athletes = self.active_athletes()?;
+        Ok(match self.discipline_type {
+            DisciplineType::Height => next_height_attempt(&athletes, achievements),
+            _ => self.next_distance_attempt(&athletes, achievements),
+        })
+    }
+
+    fn next_distance_attempt(&self, athletes: &[&Athlete], achievements: &HashMap<String, Achievement>) -> Option<NextAttempt> {
+        let is_open = |athlete: &Athlete, attempt: usize| match achievements.get(&athlete.athlete_id()) {
+            Some(Achievement::Distance(result)) => result.tries()[attempt - 1].is_none(),
+            _ => true,
+        };
+        let attempts = self.distance_attempts();
+        let mut order: Vec<(&Athlete, usize)> = match self.try_order_type {
+            TryOrderType::Subsequent => athletes
+                .iter()
+                .flat_map(|athlete| (1..=attempts).map(move |attempt| (*athlete, attempt)))
+                .collect(),
+            _ => (1..=attempts)
+                .flat_map(|attempt| athletes.iter().map(move |athlete| (*athlete, attempt)))
+                .collect(),
+        };
+        order.retain(|(athlete, attempt)| is_open(athlete, *attempt));
+        order.first().map(|(athlete, attempt)| NextAttempt::new(athlete, *attempt, None))
+    }
+
+    /// Empty achievement for the first attempt of an athlete, the start height is the height of the attempt
+    fn empty_achievement(&self, result: &AttemptResult) -> Result<Achievement, Box<dyn Error>> {
+        let achievement = match (&self.discipline_type, result) {
+            (DisciplineType::Height, AttemptResult::Height { height, .. }) => json!({"Height": {
+                "name": self.name,
+                "start_height": height,
+                "height_increase": default_height_increase(&self.name),
+                "tries": "",
+                "unit": "cm",
+            }}),
+            (DisciplineType::Height, _) => return Err(Box::from("Start height unknown, the first attempt needs a height")),
+            _ => json!({"Distance": {"name": self.name, "unit": "m"}}),
+        };
+        Ok(Achievement::from_json(&achievement.to_string())?)
+    }
+
+    /// Changed values of the achievement (see `Achievement::update_values`) after the attempt
+    fn attempt_update(&self, attempt: &Attempt, achievement: &Achievement) -> Result<Value, Box<dyn Error>> {
+        match (achievement, &attempt.result) {
+            (Achievement::Distance(result), AttemptResult::Distance(_) | AttemptResult::Foul | AttemptResult::Pass) => {
+                let number = attempt.attempt
+                    .or_else(|| result.tries().iter().position(|value| value.is_none()).map(|index| index + 1))
+                    .ok_or("All attempts are taken")?;
+                if number == 0 || number > self.distance_attempts() {
+                    return Err(Box::from(format!("{} has no attempt {number}", self.name)));
+                }
+                let value = match attempt.result {
+                    AttemptResult::Distance(distance) if distance > 0. => distance,
+                    AttemptResult::Distance(_) => return Err(Box::from("Distance has to be positive")),
+                    _ => -1.,
+                };
+                let mut update = Map::new();
+                update.insert(DISTANCE_FIELDS[number - 1].to_string(), json!(value));
+                Ok(Value::Object(update))
+            }
+            (Achievement::Height(result), AttemptResult::Height { height, cleared }) => {
+                let mut progress = HeightProgress::from_result(result);
+                let index = progress.index_of(*height)?;
+                let number = attempt.attempt.unwrap_or(progress.used(index) + 1);
+                if number == 0 || number > HEIGHT_ATTEMPTS {
+                    return Err(Box::from(format!("There is no attempt {number} at a height")));
+                }
+                progress.mark(index, number, if *cleared { 'O' } else { 'X' });
+                Ok(json!({"tries": progress.tries()}))
+            }
+            (Achievement::Height(result), AttemptResult::Pass) => {
+                let mut progress = HeightProgress::from_result(result);
+                progress.pass();
+                Ok(json!({"tries": progress.tries()}))
+            }
+            _ => Err(Box::from(format!("{:?} is not an attempt of {}", attempt.result, self.name))),
+        }
+    }
+}
+
+fn next_height_attempt(athletes: &[&Athlete], achievements: &HashMap<String, Achievement>) -> Option<NextAttempt> {
+    let competing: Vec<(&Athlete, HeightProgress)> = athletes
+        .iter()
+        .filter_map(|athlete| match achievements.get(&athlete.athlete_id()) {
+            Some(Achievement::Height(result)) => Some((*athlete, HeightProgress::from_result(result))),
+            _ => None,
+        })
+        .filter(|(_, progress)| !progress.is_eliminated())
+        .collect();
+    let height = competing.iter().map(|(_, progress)| progress.next_height()).min()?;
+
+    (0..HEIGHT_ATTEMPTS).find_map(|used| {
+        competing
+            .iter()
+            .find(|(_, progress)| progress.next_height() == height && progress.used(progress.open_index()) == used)
+            .map(|(athlete, _)| NextAttempt::new(athlete, used + 1, Some(height)))
+    })
+}
+
+impl TimeGroup {
+    fn retire_athlete(&mut self, discipline_name: &str, athlete_id: String) -> Result<(), Box<dyn Error>> {
+        let discipline = self.disciplines
+            .iter_mut()
+            .find(|discipline| discipline.name == discipline_name)
+            .ok_or(format!("Discipline {discipline_name} not found"))?;
+        if !discipline.retired.contains(&athlete_id) {
+            discipline.retired.push(athlete_id);
+        }
+        Ok(())
+    }
+}
+
+fn achievement_id(athlete: &Athlete, discipline_name: &str) -> AchievementID {
+    let athlete_id = AthleteID::new(&athlete.name.clone().unwrap_or_default(), &athlete.surname.clone().unwrap_or_default());
+    AchievementID {
+        athlete_name: Some(athlete_id.full_name()),
+        athlete_id: Some(athlete_id),
+        name: discipline_name.to_string(),
+    }
+}
+
+async fn load_discipline(storage: &(dyn Storage + Send + Sync), group_name: &str, discipline_name: &str) -> Result<(TimeGroup, Discipline), Box<dyn Error>> {
+    let time_group = storage.get_time_group(&TimeGroupID::new(group_name.to_string()))
+        .await
+        .ok_or(format!("Time group {group_name} not found"))?;
+    let discipline = time_group.get_discipline(discipline_name)
+        .cloned()
+        .ok_or(format!("Discipline {discipline_name} not found in {group_name}"))?;
+    Ok((time_group, discipline))
+}
+
+async fn next_attempt_of(storage: &(dyn Storage + Send + Sync), discipline: &Discipline) -> Result<Option<NextAttempt>, Box<dyn Error>> {
+    let mut achievements = HashMap::new();
+    for athlete in discipline.active_athletes()? {
+        if let Some(achievement) = storage.get_achievement(&achievement_id(athlete, &discipline.name)).await {
+            achievements.insert(athlete.athlete_id(), achievement);
+        }
+    }
+    discipline.next_attempt(&achievements)
+}
+
+/// Athlete who is up next in the field discipline of the time group, None if all attempts are done
+pub async fn next_attempt(storage: &(dyn Storage + Send + Sync), group_name: &str, discipline_name: &str) -> Result<Option<NextAttempt>, Box<dyn Error>> {
+    let (_, discipline) = load_discipline(storage, group_name, discipline_name).await?;
+    next_attempt_of(storage, &discipline).await
+}
+
+/// Record a single attempt, the achievement of the athlete is created with the first attempt and updated afterwards.
+/// The change is stored together with its event in the result history.
+pub async fn record_attempt(storage: &(dyn Storage + Send + Sync), group_name: &str, discipline_name: &str, attempt: Attempt, role: Option<String>) -> Result<AttemptUpdate, Box<dyn Error>> {
+    let (mut time_group, discipline) = load_discipline(storage, group_name, discipline_name).await?;
+    let athlete = discipline.active_athletes()?
+        .into_iter()
+        .find(|athlete| athlete.name.as_deref() == Some(attempt.name.as_str()) && athlete.surname.as_deref() == Some(attempt.surname.as_str()))
+        .cloned()
+        .ok_or(format!("{} {} is not competing in {discipline_name}", attempt.name, attempt.surname))?;
+    let achievement_id = achievement_id(&athlete, discipline_name);
+
+    if attempt.result == AttemptResult::Retire {
+        time_group.retire_athlete(discipline_name, athlete.athlete_id())?;
+        let discipline = time_group.get_discipline(discipline_name).cloned().ok_or("Discipline not found")?;
+        storage.store_time_group(time_group).await?;
+        return Ok(AttemptUpdate {
+            achievement: storage.get_achievement(&achievement_id).await,
+            next: next_attempt_of(storage, &discipline).await?,
+        });
+    }
+
+    let old = storage.get_achievement(&achievement_id).await;
+    let mut achievement = match &old {
+        Some(achievement) => achievement.clone(),
+        None => discipline.empty_achievement(&attempt.result)?,
+    };
+    let update = discipline.attempt_update(&attempt, &achievement)?;
+    achievement.update_values(&update.to_string())?;
+    let achievement = change_result(storage, &achievement_id, role, old, Some(achievement)).await?;
+
+    Ok(AttemptUpdate {
+        achievement,
+        next: next_attempt_of(storage, &discipline).await?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use serde_json::json;
+    use crate::certificate_generation::Achievement;
+    use crate::time_planner::{Athlete, Discipline, DisciplineState, DisciplineType, StartingOrder, TryOrderType};
+    use super::{Attempt, AttemptResult, NextAttempt};
+
+    fn get_discipline(discipline_type: DisciplineType, try_order_type: TryOrderType) -> Discipline {
+        let athletes = ["Anna", "Berta", "Clara"]
+            .iter()
+            .map(|name| Athlete::new(name.to_string(), "Muster".to_string(), None))
+            .collect();
+        Discipline {
+            name: String::from(match discipline_type { DisciplineType::Height => "Hochsprung", _ => "Weitsprung" }),
+            location: String::from("Weit 1"),
+            start_time: chrono::Utc::now(),
+            state: DisciplineState::Active,
+            starting_order: StartingOrder::Default(athletes),
+            discipline_type,
+            try_order_type,
+            time_started: None,
+            time_finished: None,
+            duration: None,
+            estimated_start_time: None,
+            local_start_time: None,
+            local_estimated_start_time: None,
+            seeding: None,
+            time_table_seeding: None,
+            retired: vec![],
+        }
+    }
+
+    fn judge(discipline: &Discipline, achievements: &mut HashMap<String, Achievement>, name: &str, result: AttemptResult) {
+        let attempt = Attempt { name: name.to_string(), surname: "Muster".to_string(), attempt: None, result };
+        let id = format!("{name}_Muster");
+        let mut achievement = match achievements.get(&id) {
+            Some(achievement) => achievement.clone(),
+            None => discipline.empty_achievement(&attempt.result).expect("Achievement should be creatable"),
+        };
+        let update = discipline.attempt_update(&attempt, &achievement).expect("Attempt should be valid");
+        achievement.update_values(&update.to_string()).expect("Update should be applicable");
+        achievements.insert(id, achievement);
+    }
+
+    fn next(discipline: &Discipline, achievements: &HashMap<String, Achievement>) -> Option<(String, usize, Option<i32>)> {
+        discipline.next_attempt(achievements)
+            .expect("Field discipline should have attempts")
+            .map(|NextAttempt { name, attempt, height, .. }| (name, attempt, height))
+    }
+
+    #[test]
+    fn distance_order() {
+        let mut discipline = get_discipline(DisciplineType::Distance, TryOrderType::Standard);
+        discipline.retired.push(String::from("Clara_Muster"));
+        let mut achievements = HashMap::new();
+
+        judge(&discipline, &mut achievements, "Anna", AttemptResult::Distance(5.12));
+        assert_eq!(next(&discipline, &achievements), Some(("Berta".to_string(), 1, None)));
+        judge(&discipline, &mut achievements, "Berta", AttemptResult::Foul);
+        // Retired athletes are skipped, the next round starts
+        assert_eq!(next(&discipline, &achievements), Some(("Anna".to_string(), 2, None)));
+        judge(&discipline, &mut achievements, "Anna", AttemptResult::Distance(5.4));
+        assert_eq!(achievements["Anna_Muster"].final_result(), "5,40");
+
+        let mut subsequent = get_discipline(DisciplineType::Distance, TryOrderType::Subsequent);
+        subsequent.retired.push(String::from("Clara_Muster"));
+        assert_eq!(next(&subsequent, &achievements), Some(("Anna".to_string(), 3, None)));
+
+        let once = get_discipline(DisciplineType::Distance, TryOrderType::Once);
+        judge(&once, &mut achievements, "Clara", AttemptResult::Distance(4.));
+        assert_eq!(next(&once, &achievements), None);
+    }
+
+    #[test]
+    fn height_order() {
+        let discipline = get_discipline(DisciplineType::Height, TryOrderType::Standard);
+        let mut achievements = HashMap::new();
+        let height = |cleared| AttemptResult::Height { height: 120, cleared };
+
+        judge(&discipline, &mut achievements, "Anna", height(false));
+        judge(&discipline, &mut achievements, "Berta", height(true));
+        // Clara starts at a later height
+        judge(&discipline, &mut achievements, "Clara", AttemptResult::Height { height: 128, cleared: true });
+        assert_eq!(next(&discipline, &achievements), Some(("Anna".to_string(), 2, Some(120))));
+
+        // Anna passes her remaining attempts and takes the failed one to 1.24
+        judge(&discipline, &mut achievements, "Anna", AttemptResult::Pass);
+        assert_eq!(next(&discipline, &achievements), Some(("Berta".to_string(), 1, Some(124))));
+        judge(&discipline, &mut achievements, "Berta", AttemptResult::Height { height: 124, cleared: false });
+        assert_eq!(next(&discipline, &achievements), Some(("Anna".to_string(), 2, Some(124))));
+        judge(&discipline, &mut achievements, "Anna", AttemptResult::Height { height: 124, cleared: false });
+        judge(&discipline, &mut achievements, "Berta", AttemptResult::Height { height: 124, cleared: false });
+        judge(&discipline, &mut achievements, "Anna", AttemptResult::Height { height: 124, cleared: false });
+        judge(&discipline, &mut achievements, "Berta", AttemptResult::Height { height: 124, cleared: false });
+
+        // Anna and Berta are eliminated, only Clara is left
+        assert_eq!(achievements["Anna_Muster"].final_result(), "0,00");
+        assert_eq!(next(&discipline, &achievements), Some(("Clara".to_string(), 1, Some(132))));
+        assert!(json!(achievements["Berta_Muster"]).to_string().contains("O//-XXX"));
+    }
+}
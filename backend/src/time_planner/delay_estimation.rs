@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+use chrono::{DateTime, Duration, Utc};
+
+use super::{Discipline, DisciplineState, TimeGroup};
+use crate::Storage;
+
+/// Typical duration per discipline name: mean of the actual durations of finished disciplines
+fn typical_durations(time_groups: &[TimeGroup]) -> HashMap<String, Duration> {
+    let mut actual: HashMap<String, Vec<Duration>> = HashMap::new();
+    for discipline in time_groups.iter().flat_map(|group| group.disciplines.iter()) {
+        if let (Some(started), Some(finished)) = (discipline.time_started, discipline.time_finished) {
+            if finished > started {
+                actual.entry(discipline.name.clone()).or_default().push(finished - started);
+            }
+        }
+    }
+    actual
+        .into_iter()
+        .map(|(name, durations)| {
+            let total: Duration = durations.iter().sum();
+            (name, total / durations.len() as i32)
+        })
+        .collect()
+}
+
+/// Location queue: planned start and start bound of the last session and its estimated end
+struct LocationSession {
+    planned_start: DateTime<Utc>,
+    bound: DateTime<Utc>,
+    end: DateTime<Utc>,
+}
+
+/// Recompute the estimated start times of all disciplines from the actual progress.
+/// Delays are passed on to the following disciplines of the group and to the groups which use the same
+/// location afterwards. Groups starting together at a location share the session.
+pub fn estimate_start_times(time_groups: &mut [TimeGroup], now: DateTime<Utc>) {
+    let typical = typical_durations(time_groups);
+    let duration_of = |discipline: &Discipline| -> Duration {
+        typical.get(&discipline.name).cloned().unwrap_or(discipline.planned_duration())
+    };
+
+    let mut order: Vec<(usize, usize)> = time_groups
+        .iter()
+        .enumerate()
+        .flat_map(|(group_index, group)| (0..group.disciplines.len()).map(move |index| (group_index, index)))
+        .collect();
+    order.sort_by_key(|(group_index, index)| time_groups[*group_index].disciplines[*index].start_time);
+
+    let mut group_end: HashMap<usize, DateTime<Utc>> = HashMap::new();
+    let mut locations: HashMap<String, LocationSession> = HashMap::new();
+
+    for (group_index, index) in order {
+        let discipline = &time_groups[group_index].disciplines[index];
+        let duration = duration_of(discipline);
+
+        let location_bound = match locations.get(&discipline.location) {
+            Some(session) if session.planned_start == discipline.start_time => Some(session.bound),
+            Some(session) => Some(session.end),
+            None => None,
+        };
+
+        let (start, end) = match discipline.state {
+            DisciplineState::Finished => {
+                let start = discipline.time_started.unwrap_or(discipline.start_time);
+                (start, discipline.time_finished.unwrap_or(start + duration))
+            }
+            DisciplineState::Active => {
+                let start = discipline.time_started.unwrap_or(discipline.start_time);
+                (start, (start + duration).max(now))
+            }
+            DisciplineState::BeforeStart => {
+                let start = [Some(discipline.start_time), group_end.get(&group_index).cloned(), location_bound, Some(now)]
+                    .into_iter()
+                    .flatten()
+                    .max()
+                    .expect("Planned start time is always given");
+                (start, start + duration)
+            }
+        };
+
+        let session = locations.entry(discipline.location.clone()).or_insert(LocationSession {
+            planned_start: discipline.start_time,
+            bound: start,
+            end,
+        });
+        if session.planned_start == discipline.start_time {
+            session.end = session.end.max(end);
+        } else {
+            *session = LocationSession { planned_start: discipline.start_time, bound: start, end };
+        }
+        group_end.insert(group_index, end);
+
+        time_groups[group_index].disciplines[index].estimated_start_time = Some(start);
+    }
+    time_groups.iter_mut().for_each(TimeGroup::localize);
+}
+
+/// Time group with estimated start times, the estimates consider all stored time groups
+pub async fn estimate_time_group(storage: &(dyn Storage + Send + Sync), group_name: &str) -> Option<TimeGroup> {
+    let mut time_groups = storage.get_time_groups().await.ok()?;
+    estimate_start_times(&mut time_groups, Utc::now());
+    time_groups.into_iter().find(|time_group| time_group.name() == group_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+    use serde_json::json;
+    use crate::time_planner::{build_time_groups, DEFAULT_TIME_ZONE, DisciplineState, TimeGroup};
+    use super::estimate_start_times;
+
+    #[test]
+    fn propagate_delay() {
+        let time_table = json!({
+            "Dates": {"Samstag": "20.09.2025"},
+            "DisciplineTypes": {"100 Meter Lauf": "Track", "Weitsprung": "Default"},
+            "Groups": {
+                "Gruppe 1": {
+                    "100 Meter Lauf": {"location": "Start 100m", "time": "08:30, Samstag", "duration": 15},
+                    "Weitsprung": {"location": "Weit 1", "time": "09:00, Samstag", "duration": 60},
+                },
+                "Gruppe 2": {
+                    "Weitsprung": {"location": "Weit 1", "time": "10:00, Samstag", "duration": 60},
+                    "100 Meter Lauf": {"location": "Start 100m", "time": "11:00, Samstag", "duration": 15},
+                },
+            },
+        });
+        let mut time_groups = build_time_groups(&time_table, DEFAULT_TIME_ZONE).expect("Time groups should be buildable");
+        let first = time_groups.iter().position(|group| group.name() == "Gruppe 1").expect("Group should exist");
+
+        // Gruppe 1 started the 100 Meter Lauf 40 minutes late (08:30 CEST = 06:30 UTC)
+        let planned = Utc.with_ymd_and_hms(2025, 9, 20, 6, 30, 0).unwrap();
+        let run = &mut time_groups[first].disciplines[0];
+        run.state = DisciplineState::Active;
+        run.time_started = Some(planned + Duration::minutes(40));
+        let now = planned + Duration::minutes(45);
+
+        estimate_start_times(&mut time_groups, now);
+        let estimate = |group: &str, discipline: &str| time_groups
+            .iter()
+            .find(|time_group| time_group.name() == group)
+            .and_then(|time_group| time_group.get_discipline(discipline))
+            .and_then(|discipline| discipline.estimated_start_time)
+            .expect("Estimate should be set");
+
+        assert_eq!(estimate("Gruppe 1", "100 Meter Lauf"), planned + Duration::minutes(40));
+        assert_eq!(estimate("Gruppe 1", "Weitsprung"), planned + Duration::minutes(55));
+        // Weit 1 is occupied by Gruppe 1 until 10:25
+        assert_eq!(estimate("Gruppe 2", "Weitsprung"), planned + Duration::minutes(115));
+        assert_eq!(estimate("Gruppe 2", "100 Meter Lauf"), planned + Duration::minutes(175));
+    }
+
+    #[test]
+    fn estimates_are_not_stored() {
+        let time_table = json!({
+            "Dates": {"Samstag": "20.09.2025"},
+            "DisciplineTypes": {"Weitsprung": "Default"},
+            "Groups": {"Gruppe 1": {"Weitsprung": {"location": "Weit 1", "time": "09:00, Samstag"}}},
+        });
+        let mut time_groups = build_time_groups(&time_table, DEFAULT_TIME_ZONE).expect("Time groups should be buildable");
+        estimate_start_times(&mut time_groups, Utc::now());
+        let response = serde_json::to_value(&time_groups[0]).expect("Time group should be serializable");
+        assert!(response["disciplines"][0].get("local_start_time").is_some());
+
+        let stored: TimeGroup = serde_json::from_value(response).expect("Time group should be deserializable");
+        let discipline = &stored.get_disciplines()[0];
+        assert_eq!((discipline.estimated_start_time, discipline.local_start_time), (None, None));
+    }
+}
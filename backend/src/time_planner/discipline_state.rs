@@ -0,0 +1,147 @@
+use std::error::Error;
+use chrono::Utc;
+use serde::Deserialize;
+
+use super::{Athlete, Discipline, DisciplineState, DisciplineType, StartingOrder, TimeGroup};
+use crate::certificate_generation::AthleteID;
+use crate::Storage;
+
+/// Requested state change of a discipline
+#[derive(Deserialize, Debug, Clone)]
+pub struct StateChange {
+    pub name: String,
+    pub state: DisciplineState,
+    /// Override the guards: finish with missing results or go back to an earlier state
+    #[serde(default)]
+    pub force: bool,
+}
+
+impl Discipline {
+    /// Change the state, without override only BeforeStart → Active → Finished is allowed and a discipline is only
+    /// finished if all athletes have a result. Going back resets the start and end times.
+    fn change_state(&mut self, new_state: DisciplineState, missing_results: &[String], force: bool) -> Result<(), Box<dyn Error>> {
+        match (&self.state, &new_state) {
+            (current, new) if current == new => {
+                return Err(Box::from(format!("{} is already {:?}", self.name, new)));
+            }
+            (DisciplineState::BeforeStart, DisciplineState::Active) => {}
+            (DisciplineState::Active, DisciplineState::Finished) if missing_results.is_empty() || force => {}
+            (DisciplineState::Active, DisciplineState::Finished) => {
+                return Err(Box::from(format!("{} can not be finished, results missing for {}", self.name,
+                                             missing_results.join(", "))));
+            }
+            _ if force => {}
+            (current, new) => {
+                return Err(Box::from(format!("{} can not change from {:?} to {:?} without override", self.name,
+                                             current, new)));
+            }
+        }
+
+        match new_state {
+            DisciplineState::BeforeStart => {
+                self.time_started = None;
+                self.time_finished = None;
+            }
+            DisciplineState::Active => {
+                // A reopened discipline keeps its start time
+                self.time_started = self.time_started.or(Some(Utc::now()));
+                self.time_finished = None;
+            }
+            DisciplineState::Finished => self.time_finished = Some(Utc::now()),
+        }
+        self.state = new_state;
+        Ok(())
+    }
+}
+
+impl TimeGroup {
+    /// Athletes who take part in the discipline
+    fn discipline_athletes<'a>(&'a self, discipline: &'a Discipline) -> Vec<&'a Athlete> {
+        let athletes: Vec<&Athlete> = match &discipline.starting_order {
+            StartingOrder::Default(athletes) => athletes.iter().collect(),
+            StartingOrder::Track(runs) => runs.iter().flat_map(|run| run.athletes().iter().flatten()).collect(),
+            StartingOrder::NoOrder => self.default_athlete_order.iter().collect(),
+        };
+        athletes
+            .into_iter()
+            .filter(|athlete| !discipline.retired.contains(&athlete.athlete_id()))
+            .collect()
+    }
+
+    /// Change the state of a discipline, the missing results are needed to finish it
+    pub fn change_discipline_state(&mut self, change: &StateChange, missing_results: &[String]) -> Result<String, Box<dyn Error>> {
+        let discipline = self.disciplines
+            .iter_mut()
+            .find(|discipline| discipline.name == change.name)
+            .ok_or(format!("Discipline {} not found", change.name))?;
+        discipline.change_state(change.state.clone(), missing_results, change.force)?;
+        self.current_discipline = self.current_index().unwrap_or(0);
+        Ok(String::from("Discipline state updated"))
+    }
+}
+
+/// Athletes of the discipline (full name) without a result, retired athletes are not included. Track and time
+/// disciplines are not checked, their times are entered by the timekeeping after the group has finished.
+pub async fn missing_results(storage: &(dyn Storage + Send + Sync), time_group: &TimeGroup, discipline_name: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    let discipline = time_group.get_discipline(discipline_name)
+        .ok_or(format!("Discipline {discipline_name} not found"))?;
+    if matches!(discipline.discipline_type, DisciplineType::Track | DisciplineType::Time) {
+        return Ok(vec![]);
+    }
+    let mut missing = vec![];
+    for athlete in time_group.discipline_athletes(discipline) {
+        let athlete_id = AthleteID::new(&athlete.name.clone().unwrap_or_default(), &athlete.surname.clone().unwrap_or_default());
+        let has_result = storage.get_athlete(&athlete_id)
+            .await
+            .is_some_and(|athlete| athlete.get_achievement(discipline_name).is_some());
+        if !has_result {
+            missing.push(athlete.full_name());
+        }
+    }
+    Ok(missing)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::time_planner::{build_time_groups, DEFAULT_TIME_ZONE, DisciplineState};
+    use super::StateChange;
+
+    #[test]
+    fn guarded_transitions() {
+        let time_table = json!({
+            "Dates": {"Samstag": "20.09.2025"},
+            "DisciplineTypes": {"100 Meter Lauf": "Track", "Weitsprung": "Default"},
+            "Groups": {
+                "Gruppe 1": {
+                    "100 Meter Lauf": {"location": "Start 100m", "time": "08:30, Samstag"},
+                    "Weitsprung": {"location": "Weit 1", "time": "09:00, Samstag"},
+                },
+            },
+        });
+        let mut time_group = build_time_groups(&time_table, DEFAULT_TIME_ZONE).expect("Time groups should be buildable").remove(0);
+        let first = time_group.get_current_discipline().expect("Group has disciplines").name().to_string();
+        let change = |state: DisciplineState, force: bool| StateChange { name: first.clone(), state, force };
+
+        assert!(time_group.change_discipline_state(&change(DisciplineState::Finished, false), &[]).is_err());
+        time_group.change_discipline_state(&change(DisciplineState::Active, false), &[]).expect("Start should be allowed");
+        let missing = vec![String::from("Max Muster")];
+        assert!(time_group.change_discipline_state(&change(DisciplineState::Finished, false), &missing).is_err());
+        time_group.change_discipline_state(&change(DisciplineState::Finished, true), &missing).expect("Override should finish");
+
+        // Navigation after the last discipline
+        let second = time_group.get_current_discipline().expect("Second discipline is current").name().to_string();
+        assert_ne!(first, second);
+        assert!(time_group.get_next_discipline().is_none());
+        let finish_second = |state| StateChange { name: second.clone(), state, force: true };
+        time_group.change_discipline_state(&finish_second(DisciplineState::Active), &[]).expect("Start should be allowed");
+        time_group.change_discipline_state(&finish_second(DisciplineState::Finished), &[]).expect("Finish should be allowed");
+        assert_eq!(time_group.get_current_discipline().map(|discipline| discipline.name()), Some(second.as_str()));
+        assert!(time_group.get_next_discipline().is_none());
+
+        // Finished disciplines only go back with override
+        assert!(time_group.change_discipline_state(&change(DisciplineState::BeforeStart, false), &[]).is_err());
+        time_group.change_discipline_state(&change(DisciplineState::BeforeStart, true), &[]).expect("Reset should be allowed");
+        assert_eq!(time_group.get_current_discipline().map(|discipline| discipline.name()), Some(first.as_str()));
+    }
+}
@@ -0,0 +1,157 @@
+use std::error::Error;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use super::{estimate_start_times, Discipline, TimeGroup};
+use crate::Storage;
+
+const PRODUCT_ID: &str = "-//Decathlon Support System//Time Plan//DE";
+
+/// Maximum length of a content line in octets, longer lines are folded
+const MAX_LINE_OCTETS: usize = 75;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct LocationQuery {
+    pub location: String,
+}
+
+fn format_time(time: &DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Escape a text value (RFC 5545, section 3.3.11)
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Fold a content line into lines of at most 75 octets without splitting characters
+fn fold_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut octets = 0;
+    for character in line.chars() {
+        if octets + character.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            octets = 1;
+        }
+        folded.push(character);
+        octets += character.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+/// Stable identifier, so subscribed calendars update the event when the time changes
+fn event_uid(time_group: &TimeGroup, discipline: &Discipline) -> String {
+    let uid: String = format!("{}-{}", time_group.name, discipline.name)
+        .chars()
+        .map(|character| if character.is_ascii_alphanumeric() { character.to_ascii_lowercase() } else { '-' })
+        .collect();
+    format!("{uid}@decathlon-support-system")
+}
+
+fn event(time_group: &TimeGroup, discipline: &Discipline, now: &DateTime<Utc>) -> Vec<String> {
+    let start = discipline.estimated_start_time.unwrap_or(discipline.start_time);
+    let delay = (start - discipline.start_time).num_minutes();
+    let mut description = time_group.name.clone();
+    if delay > 0 {
+        description.push_str(&format!(", {delay} Minuten später als geplant"));
+    }
+
+    vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}", event_uid(time_group, discipline)),
+        format!("DTSTAMP:{}", format_time(now)),
+        format!("DTSTART:{}", format_time(&start)),
+        format!("DTEND:{}", format_time(&(start + discipline.planned_duration()))),
+        format!("SUMMARY:{}", escape_text(&format!("{} ({})", discipline.name, time_group.name))),
+        format!("LOCATION:{}", escape_text(&discipline.location)),
+        format!("DESCRIPTION:{}", escape_text(&description)),
+        "END:VEVENT".to_string(),
+    ]
+}
+
+/// iCalendar (RFC 5545) with an event for every discipline of the time groups which matches the filter.
+/// The estimated start time is used if available.
+pub fn to_ical(
+    calendar_name: &str,
+    time_groups: &[TimeGroup],
+    filter: impl Fn(&TimeGroup, &Discipline) -> bool,
+    now: DateTime<Utc>,
+) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{PRODUCT_ID}"),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!("X-WR-CALNAME:{}", escape_text(calendar_name)),
+        // Ask subscribed clients to refresh regularly, so delays are picked up
+        "REFRESH-INTERVAL;VALUE=DURATION:PT5M".to_string(),
+        "X-PUBLISHED-TTL:PT5M".to_string(),
+    ];
+    for time_group in time_groups {
+        let mut disciplines: Vec<&Discipline> = time_group.disciplines
+            .iter()
+            .filter(|discipline| filter(time_group, discipline))
+            .collect();
+        disciplines.sort_by_key(|discipline| discipline.start_time);
+        for discipline in disciplines {
+            lines.extend(event(time_group, discipline, &now));
+        }
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold_line(line)).collect()
+}
+
+/// Calendar feed of the stored time plan including the current delays
+pub async fn ical_feed(
+    storage: &(dyn Storage + Send + Sync),
+    calendar_name: &str,
+    filter: impl Fn(&TimeGroup, &Discipline) -> bool,
+) -> Result<String, Box<dyn Error>> {
+    let mut time_groups = storage.get_time_groups().await?;
+    time_groups.sort_by(|group, other| group.name.cmp(&other.name));
+    let now = Utc::now();
+    estimate_start_times(&mut time_groups, now);
+    Ok(to_ical(calendar_name, &time_groups, filter, now))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use serde_json::json;
+    use crate::time_planner::{build_time_groups, DEFAULT_TIME_ZONE};
+    use super::to_ical;
+
+    #[test]
+    fn location_calendar() {
+        let time_table = json!({
+            "Dates": {"Samstag": "20.09.2025"},
+            "DisciplineTypes": {"100 Meter Lauf": "Track", "Weitsprung": "Default"},
+            "Groups": {
+                "Gruppe 1": {
+                    "100 Meter Lauf": {"location": "Start 100m", "time": "08:30, Samstag"},
+                    "Weitsprung": {"location": "Weit 1", "time": "09:00, Samstag", "duration": 60},
+                },
+                "Gruppe 2": {
+                    "Weitsprung": {"location": "Weit 1", "time": "10:00, Samstag", "duration": 60},
+                },
+            },
+        });
+        let time_groups = build_time_groups(&time_table, DEFAULT_TIME_ZONE).expect("Time groups should be buildable");
+        let now = Utc.with_ymd_and_hms(2025, 9, 1, 12, 0, 0).unwrap();
+
+        let calendar = to_ical("Weit 1", &time_groups, |_, discipline| discipline.location == "Weit 1", now);
+        assert!(calendar.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(calendar.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(calendar.matches("BEGIN:VEVENT").count(), 2);
+        assert!(calendar.contains("DTSTART:20250920T070000Z\r\nDTEND:20250920T080000Z\r\n"));
+        assert!(calendar.contains("UID:gruppe-2-weitsprung@decathlon-support-system\r\n"));
+        assert!(!calendar.contains("100 Meter Lauf"));
+        assert!(calendar.split("\r\n").all(|line| line.len() <= 75));
+    }
+}
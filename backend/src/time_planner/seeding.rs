@@ -0,0 +1,287 @@
+use std::collections::HashMap;
+use std::error::Error;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use super::{Athlete, Discipline, Run, StartingOrder};
+use crate::certificate_generation::{Group, GroupID};
+use crate::Storage;
+
+/// How the athletes of a track discipline are distributed over the runs and lanes, or the athletes of a field
+/// discipline are ordered
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SeedingStrategy {
+    /// Runs in the order of the athlete list (youth groups by age group)
+    ListOrder,
+    /// Lanes by age class: first lane for the oldest, inner lanes for the masters, outer lanes for the main class
+    AgeClass,
+    /// By current total points, the leaders run in the last run (e.g. the final 1500 Meter Lauf)
+    TotalPoints,
+    /// By declared best time, the fastest athletes run in the last run
+    BestTime,
+    /// Random draw, the seed is recorded when the order is generated so the draw can be reproduced
+    Random {
+        #[serde(default)]
+        seed: Option<u64>,
+    },
+    /// Field disciplines: every discipline starts one block further down the athlete list
+    Rotation,
+}
+
+/// Data of the athletes used for seeding, keyed by athlete id
+#[derive(Clone, Debug, Default)]
+pub struct SeedingInfo {
+    pub total_points: HashMap<String, u32>,
+    /// Declared best times in milliseconds per discipline
+    pub best_times: HashMap<String, HashMap<String, u32>>,
+}
+
+impl SeedingInfo {
+    pub fn from_group(group: &Group) -> Self {
+        let mut info = SeedingInfo::default();
+        for athlete in group.athletes() {
+            info.total_points.insert(athlete.athlete_id(), athlete.total_point());
+            info.best_times.insert(athlete.athlete_id(), athlete.declared_best_times().clone());
+        }
+        info
+    }
+
+    /// Seeding data of the stored group, empty if the group does not exist
+    pub async fn load(storage: &(dyn Storage + Send + Sync), group_name: &str) -> Self {
+        match storage.get_group(&GroupID::new(group_name)).await {
+            Some(group) => SeedingInfo::from_group(&group),
+            None => SeedingInfo::default(),
+        }
+    }
+
+    fn total_points(&self, athlete: &Athlete) -> u32 {
+        self.total_points.get(&athlete.athlete_id()).cloned().unwrap_or(0)
+    }
+
+    /// Declared best time, athletes without one are seeded last
+    fn best_time(&self, athlete: &Athlete, discipline: &str) -> u32 {
+        self.best_times
+            .get(&athlete.athlete_id())
+            .and_then(|best_times| best_times.get(discipline))
+            .cloned()
+            .unwrap_or(u32::MAX)
+    }
+}
+
+/// Split the ranking (strongest first) into runs, the strongest athletes run in the last, full run and the
+/// partial run of the weakest athletes starts first
+fn runs_from_ranking(ranking: &[Athlete], lanes: usize) -> Vec<Run> {
+    let mut chunks: Vec<&[Athlete]> = ranking.chunks(lanes.max(1)).collect();
+    chunks.reverse();
+    into_runs(chunks, lanes)
+}
+
+fn into_runs(chunks: Vec<&[Athlete]>, lanes: usize) -> Vec<Run> {
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(index, chunk)| {
+            let mut athletes: Vec<Option<Athlete>> = chunk.iter().cloned().map(Some).collect();
+            athletes.resize(lanes, None);
+            Run { name: format!("Lauf {}", index + 1), athletes }
+        })
+        .collect()
+}
+
+/// Shuffle with a small deterministic generator (splitmix64), so the same seed gives the same draw
+fn shuffle(athletes: &mut [Athlete], seed: u64) {
+    let mut state = seed;
+    let mut next = || {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    };
+    for index in (1..athletes.len()).rev() {
+        let other = (next() % (index as u64 + 1)) as usize;
+        athletes.swap(index, other);
+    }
+}
+
+impl Discipline {
+    /// Seeding strategy of the discipline, hurdles are seeded by age class unless configured otherwise
+    pub fn seeding(&self) -> SeedingStrategy {
+        match &self.seeding {
+            Some(seeding) => seeding.clone(),
+            None if self.name == "110 Meter Hürden" => SeedingStrategy::AgeClass,
+            None if matches!(self.starting_order, StartingOrder::Default(_)) => SeedingStrategy::Rotation,
+            None => SeedingStrategy::ListOrder,
+        }
+    }
+
+    /// Description of the order printed on the protocol
+    pub fn order_description(&self) -> String {
+        match self.seeding() {
+            SeedingStrategy::ListOrder => String::from("Reihenfolge laut Liste"),
+            SeedingStrategy::AgeClass => String::from("Bahnen nach Altersklasse"),
+            SeedingStrategy::TotalPoints => String::from("Reihenfolge nach Gesamtpunkten"),
+            SeedingStrategy::BestTime => String::from("Reihenfolge nach Bestzeit"),
+            SeedingStrategy::Random { seed: Some(seed) } => format!("Reihenfolge gelost (Seed {seed})"),
+            SeedingStrategy::Random { seed: None } => String::from("Reihenfolge gelost"),
+            SeedingStrategy::Rotation => String::from("Reihenfolge rotiert"),
+        }
+    }
+
+    /// Check whether the strategy can be used for the starting order of the discipline
+    pub(super) fn check_seeding(&self, seeding: &SeedingStrategy) -> Result<(), Box<dyn Error>> {
+        let valid = match self.starting_order {
+            StartingOrder::Track(_) => !matches!(seeding, SeedingStrategy::Rotation),
+            StartingOrder::Default(_) => !matches!(seeding, SeedingStrategy::AgeClass | SeedingStrategy::BestTime),
+            StartingOrder::NoOrder => false,
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(Box::from(format!("Seeding {seeding:?} not available for {}", self.name)))
+        }
+    }
+
+    pub fn set_seeding(&mut self, seeding: SeedingStrategy) {
+        self.seeding = Some(seeding);
+    }
+
+    /// Track starting order according to the seeding strategy. The athletes, run and hurdle order are the
+    /// default orders of the group. The seed of a random draw is recorded in the discipline.
+    pub(super) fn seed_runs(
+        &mut self,
+        athletes: &[Athlete],
+        run_order: &[Run],
+        hurdle_order: &[Run],
+        lanes: usize,
+        info: &SeedingInfo,
+    ) -> Vec<Run> {
+        match self.seeding() {
+            SeedingStrategy::ListOrder | SeedingStrategy::Rotation => run_order.to_vec(),
+            SeedingStrategy::AgeClass => hurdle_order.to_vec(),
+            SeedingStrategy::TotalPoints => {
+                let mut ranking = athletes.to_vec();
+                ranking.sort_by_key(|athlete| std::cmp::Reverse(info.total_points(athlete)));
+                runs_from_ranking(&ranking, lanes)
+            }
+            SeedingStrategy::BestTime => {
+                let mut ranking = athletes.to_vec();
+                ranking.sort_by_key(|athlete| info.best_time(athlete, &self.name));
+                runs_from_ranking(&ranking, lanes)
+            }
+            SeedingStrategy::Random { seed } => {
+                let draw = self.draw(athletes, seed);
+                into_runs(draw.chunks(lanes.max(1)).collect(), lanes)
+            }
+        }
+    }
+
+    /// Order of a field discipline according to the seeding strategy. The discipline is the `rotation`-th of
+    /// `rotations` field disciplines of the group, the seed of a random draw is recorded in the discipline.
+    pub(super) fn seed_order(&mut self, athletes: &[Athlete], rotation: usize, rotations: usize, info: &SeedingInfo) -> Vec<Athlete> {
+        let mut order = athletes.to_vec();
+        match self.seeding() {
+            SeedingStrategy::Rotation if !order.is_empty() => {
+                let block = order.len().div_ceil(rotations.max(1));
+                let offset = (rotation * block) % order.len();
+                order.rotate_left(offset);
+            }
+            // The leaders go last
+            SeedingStrategy::TotalPoints => order.sort_by_key(|athlete| info.total_points(athlete)),
+            SeedingStrategy::Random { seed } => order = self.draw(athletes, seed),
+            _ => {}
+        }
+        order
+    }
+
+    fn draw(&mut self, athletes: &[Athlete], seed: Option<u64>) -> Vec<Athlete> {
+        let seed = seed.unwrap_or_else(|| Utc::now().timestamp_nanos_opt().unwrap_or_default() as u64);
+        self.seeding = Some(SeedingStrategy::Random { seed: Some(seed) });
+        let mut draw = athletes.to_vec();
+        shuffle(&mut draw, seed);
+        draw
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use serde_json::json;
+    use crate::time_planner::{build_time_groups, DEFAULT_TIME_ZONE, Athlete, StartingOrder, TimeGroup, DEFAULT_LANES};
+    use super::{SeedingInfo, SeedingStrategy};
+
+    fn get_athletes(count: usize) -> Vec<Athlete> {
+        (0..count)
+            .map(|index| Athlete::new(format!("A{index}"), "Muster".to_string(), Some("M".to_string())))
+            .collect()
+    }
+
+    fn names(athletes: &[Option<Athlete>]) -> Vec<String> {
+        athletes.iter().flatten().map(Athlete::athlete_id).collect()
+    }
+
+    #[test]
+    fn seeding_strategies() {
+        let time_table = json!({
+            "Dates": {"Sonntag": "21.09.2025"},
+            "DisciplineTypes": {"1500 Meter Lauf": "Track"},
+            "Groups": {"Gruppe 1": {"1500 Meter Lauf": {"location": "Start 1500m", "time": "16:00, Sonntag"}}},
+        });
+        let mut time_groups = build_time_groups(&time_table, DEFAULT_TIME_ZONE).expect("Time groups should be buildable");
+        let discipline = &mut time_groups[0].disciplines[0];
+        let athletes = get_athletes(8);
+        let info = SeedingInfo {
+            total_points: athletes.iter().enumerate().map(|(index, athlete)| (athlete.athlete_id(), index as u32 * 100)).collect(),
+            best_times: HashMap::from([(String::from("A3_Muster"), HashMap::from([(String::from("1500 Meter Lauf"), 250_000)]))]),
+        };
+
+        // Leaders run in the last, full run
+        discipline.set_seeding(SeedingStrategy::TotalPoints);
+        let runs = discipline.seed_runs(&athletes, &[], &[], 6, &info);
+        assert_eq!(runs.len(), 2);
+        assert_eq!(names(runs[0].athletes()), vec!["A1_Muster", "A0_Muster"]);
+        assert_eq!(names(runs[1].athletes())[0], "A7_Muster");
+        assert_eq!(runs[1].name, "Lauf 2");
+
+        discipline.set_seeding(SeedingStrategy::BestTime);
+        let runs = discipline.seed_runs(&athletes, &[], &[], 6, &info);
+        assert_eq!(names(runs[1].athletes())[0], "A3_Muster");
+
+        // The seed is recorded and gives the same draw again
+        discipline.set_seeding(SeedingStrategy::Random { seed: None });
+        let runs = discipline.seed_runs(&athletes, &[], &[], 4, &info);
+        let seed = match discipline.seeding() {
+            SeedingStrategy::Random { seed: Some(seed) } => seed,
+            seeding => panic!("Seed should be recorded: {seeding:?}"),
+        };
+        discipline.set_seeding(SeedingStrategy::Random { seed: Some(seed) });
+        let repeated = discipline.seed_runs(&athletes, &[], &[], 4, &info);
+        assert_eq!(runs.iter().map(|run| names(run.athletes())).collect::<Vec<_>>(),
+                   repeated.iter().map(|run| names(run.athletes())).collect::<Vec<_>>());
+        assert_eq!(runs.iter().map(|run| run.athletes().iter().flatten().count()).sum::<usize>(), 8);
+    }
+
+    #[test]
+    fn rotate_field_orders() {
+        let group_info = json!({
+            "Weitsprung": {"location": "Weit 1", "time": "09:00, Samstag"},
+            "Kugelstoß": {"location": "Kugel 1", "time": "10:00, Samstag"},
+            "Hochsprung": {"location": "Hoch 1", "time": "11:00, Samstag"},
+        });
+        let dates = HashMap::from([(String::from("Samstag"), String::from("20.09.2025"))]);
+        let types = ["Weitsprung", "Kugelstoß", "Hochsprung"]
+            .map(|discipline| (discipline.to_string(), String::from("Default")))
+            .into_iter()
+            .collect();
+        let time_group = TimeGroup::build(&String::from("Gruppe 1"), &group_info, &dates, &types,
+                                          Some(get_athletes(6)), DEFAULT_LANES, DEFAULT_TIME_ZONE).expect("Time group should be buildable");
+
+        let first_athlete = |discipline: &str| match time_group.get_discipline(discipline).map(|discipline| discipline.starting_order()) {
+            Some(StartingOrder::Default(order)) => order[0].athlete_id(),
+            _ => panic!("{discipline} should have a default order"),
+        };
+        assert_eq!(first_athlete("Weitsprung"), "A0_Muster");
+        assert_eq!(first_athlete("Kugelstoß"), "A2_Muster");
+        assert_eq!(first_athlete("Hochsprung"), "A4_Muster");
+    }
+}
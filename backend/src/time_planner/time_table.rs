@@ -0,0 +1,418 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use chrono::{Datelike, NaiveDate, NaiveTime};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::{DisciplineType, SeedingStrategy, TimeGroup, TryOrderType};
+
+const STARTING_ORDER_TYPES: [&str; 3] = ["Track", "Default", "None"];
+const WEEKDAYS: [&str; 7] = ["Montag", "Dienstag", "Mittwoch", "Donnerstag", "Freitag", "Samstag", "Sonntag"];
+
+/// Time table in the upload format, e.g.
+/// `{"Dates": {"Samstag": "20.09.2025"}, "DisciplineTypes": {"Weitsprung": "Default"},
+///   "Groups": {"Gruppe 1": {"Weitsprung": {"location": "Weit 1", "time": "09:00, Samstag"}}}}`
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct TimeTable {
+    /// Day name → date (dd.mm.YYYY)
+    #[serde(rename = "Dates")]
+    pub dates: BTreeMap<String, String>,
+    /// Discipline name → starting order type (Track, Default or None)
+    #[serde(rename = "DisciplineTypes")]
+    pub discipline_types: BTreeMap<String, String>,
+    #[serde(rename = "Groups")]
+    pub groups: BTreeMap<String, BTreeMap<String, TimeTableEntry>>,
+    /// Password → login of the group helpers, never exported
+    #[serde(rename = "Passwords", default)]
+    pub passwords: BTreeMap<String, GroupLogin>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TimeTableEntry {
+    pub location: String,
+    /// Local start time and day name, e.g. "09:00, Samstag"
+    pub time: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub try_order_type: Option<TryOrderType>,
+    /// Planned duration in minutes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seeding: Option<SeedingStrategy>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GroupLogin {
+    pub role: String,
+    pub group: String,
+}
+
+/// Violation of the time table schema, the path points to the invalid value (e.g. "Groups.Gruppe 1.Weitsprung.time")
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SchemaError {
+    pub path: String,
+    pub message: String,
+}
+
+impl SchemaError {
+    fn new(path: &str, message: impl Into<String>) -> Self {
+        SchemaError { path: path.to_string(), message: message.into() }
+    }
+}
+
+/// One line of the CSV export, the dates and discipline types are repeated on every line
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct CsvRow {
+    group: String,
+    discipline: String,
+    discipline_type: String,
+    day: String,
+    date: String,
+    time: String,
+    location: String,
+    #[serde(default)]
+    try_order_type: String,
+    #[serde(default)]
+    duration: String,
+    #[serde(default)]
+    seeding: String,
+}
+
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{path}.{key}")
+    }
+}
+
+fn get_object<'a>(map: &'a Map<String, Value>, key: &str, path: &str, errors: &mut Vec<SchemaError>) -> Option<&'a Map<String, Value>> {
+    match map.get(key) {
+        Some(Value::Object(object)) => Some(object),
+        Some(_) => {
+            errors.push(SchemaError::new(&join(path, key), "Expected an object"));
+            None
+        }
+        None => {
+            errors.push(SchemaError::new(&join(path, key), "Missing"));
+            None
+        }
+    }
+}
+
+fn get_string<'a>(map: &'a Map<String, Value>, key: &str, path: &str, errors: &mut Vec<SchemaError>) -> Option<&'a str> {
+    match map.get(key) {
+        Some(Value::String(text)) => Some(text),
+        Some(_) => {
+            errors.push(SchemaError::new(&join(path, key), "Expected a string"));
+            None
+        }
+        None => {
+            errors.push(SchemaError::new(&join(path, key), "Missing"));
+            None
+        }
+    }
+}
+
+/// Check an optional value which is deserialized by serde, e.g. the seeding strategy
+fn check_optional<T: for<'de> Deserialize<'de>>(map: &Map<String, Value>, key: &str, path: &str, errors: &mut Vec<SchemaError>) {
+    if let Some(value) = map.get(key) {
+        if let Err(e) = serde_json::from_value::<T>(value.clone()) {
+            errors.push(SchemaError::new(&join(path, key), format!("Invalid value {value}: {e}")));
+        }
+    }
+}
+
+/// Text of a CSV cell, unit variants are written without quotes
+fn to_cell<T: Serialize>(value: &Option<T>) -> String {
+    match value.as_ref().map(serde_json::to_value) {
+        Some(Ok(Value::String(text))) => text,
+        Some(Ok(value)) => value.to_string(),
+        _ => String::new(),
+    }
+}
+
+fn from_cell(cell: &str) -> Option<Value> {
+    let cell = cell.trim();
+    if cell.is_empty() {
+        None
+    } else if cell.starts_with('{') {
+        Some(serde_json::from_str(cell).unwrap_or(Value::String(cell.to_string())))
+    } else if let Ok(number) = cell.parse::<i64>() {
+        Some(Value::from(number))
+    } else {
+        Some(Value::String(cell.to_string()))
+    }
+}
+
+impl DisciplineType {
+    fn starting_order_type(&self) -> &'static str {
+        match self {
+            DisciplineType::Track => "Track",
+            DisciplineType::Distance | DisciplineType::Height => "Default",
+            DisciplineType::Time => "None",
+        }
+    }
+}
+
+impl TimeTable {
+    /// Check the uploaded time table against the schema, all violations are reported with their path
+    pub fn from_value(value: &Value) -> Result<TimeTable, Vec<SchemaError>> {
+        let mut errors = vec![];
+        let Value::Object(root) = value else {
+            return Err(vec![SchemaError::new("", "Expected an object with Dates, DisciplineTypes and Groups")]);
+        };
+
+        let dates = get_object(root, "Dates", "", &mut errors);
+        for (day, date) in dates.into_iter().flatten() {
+            let path = join("Dates", day);
+            match date.as_str() {
+                Some(date) if NaiveDate::parse_from_str(date.trim(), "%d.%m.%Y").is_ok() => {}
+                _ => errors.push(SchemaError::new(&path, format!("Expected a date (dd.mm.YYYY), found {date}"))),
+            }
+        }
+
+        let discipline_types = get_object(root, "DisciplineTypes", "", &mut errors);
+        for (discipline, starting_order_type) in discipline_types.into_iter().flatten() {
+            if !starting_order_type.as_str().is_some_and(|order_type| STARTING_ORDER_TYPES.contains(&order_type.trim())) {
+                errors.push(SchemaError::new(&join("DisciplineTypes", discipline),
+                                             format!("Expected one of {}, found {starting_order_type}", STARTING_ORDER_TYPES.join(", "))));
+            }
+        }
+
+        check_optional::<BTreeMap<String, GroupLogin>>(root, "Passwords", "", &mut errors);
+
+        let groups = get_object(root, "Groups", "", &mut errors);
+        for (group_name, group) in groups.into_iter().flatten() {
+            let group_path = join("Groups", group_name);
+            let Value::Object(group) = group else {
+                errors.push(SchemaError::new(&group_path, "Expected an object of disciplines"));
+                continue;
+            };
+            for (discipline_name, entry) in group {
+                let path = join(&group_path, discipline_name);
+                let Value::Object(entry) = entry else {
+                    errors.push(SchemaError::new(&path, "Expected an object with location and time"));
+                    continue;
+                };
+                if discipline_types.is_some_and(|types| !types.contains_key(discipline_name)) {
+                    errors.push(SchemaError::new(&path, format!("{discipline_name} is missing in DisciplineTypes")));
+                }
+                get_string(entry, "location", &path, &mut errors);
+                if let Some(time) = get_string(entry, "time", &path, &mut errors) {
+                    let time_path = join(&path, "time");
+                    match time.split(',').collect::<Vec<_>>()[..] {
+                        [time, day] => {
+                            if NaiveTime::parse_from_str(time.trim(), "%H:%M").is_err() {
+                                errors.push(SchemaError::new(&time_path, format!("Expected a time (HH:MM), found {}", time.trim())));
+                            }
+                            if dates.is_some_and(|dates| !dates.contains_key(day.trim())) {
+                                errors.push(SchemaError::new(&time_path, format!("Day {} is missing in Dates", day.trim())));
+                            }
+                        }
+                        _ => errors.push(SchemaError::new(&time_path, format!("Expected \"HH:MM, Day\", found {time}"))),
+                    }
+                }
+                check_optional::<TryOrderType>(entry, "try_order_type", &path, &mut errors);
+                check_optional::<SeedingStrategy>(entry, "seeding", &path, &mut errors);
+                if let Some(duration) = entry.get("duration") {
+                    if duration.as_i64().is_none_or(|duration| duration <= 0) {
+                        errors.push(SchemaError::new(&join(&path, "duration"), format!("Expected minutes (> 0), found {duration}")));
+                    }
+                }
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        serde_json::from_value(value.clone()).map_err(|e| vec![SchemaError::new("", e.to_string())])
+    }
+
+    pub fn to_value(&self) -> Value {
+        serde_json::to_value(self).expect("Time table should be serializable")
+    }
+
+    /// Time table of the stored time groups in the upload format, changes made after the upload are included.
+    /// Passwords are not exported.
+    pub fn from_time_groups(time_groups: &[TimeGroup]) -> TimeTable {
+        let mut time_table = TimeTable::default();
+        for group in time_groups {
+            for discipline in &group.disciplines {
+                let local = discipline.local_start_time(group.time_zone);
+                let day = time_table.day_name(local.date_naive());
+                time_table.discipline_types.insert(discipline.name.clone(),
+                                                   discipline.discipline_type.starting_order_type().to_string());
+                time_table.groups.entry(group.name.clone()).or_default().insert(discipline.name.clone(), TimeTableEntry {
+                    location: discipline.location.clone(),
+                    time: format!("{}, {}", local.format("%H:%M"), day),
+                    try_order_type: match discipline.try_order_type {
+                        TryOrderType::Standard => None,
+                        ref try_order_type => Some(try_order_type.clone()),
+                    },
+                    duration: discipline.duration,
+                    seeding: discipline.seeding.clone(),
+                });
+            }
+        }
+        time_table
+    }
+
+    /// Name of the day in the time table: the weekday, the date is added if the weekday is used twice
+    fn day_name(&mut self, date: NaiveDate) -> String {
+        let date_str = date.format("%d.%m.%Y").to_string();
+        if let Some((name, _)) = self.dates.iter().find(|(_, day_date)| **day_date == date_str) {
+            return name.clone();
+        }
+        let weekday = WEEKDAYS[date.weekday().num_days_from_monday() as usize];
+        let name = if self.dates.contains_key(weekday) {
+            format!("{weekday} {}", date.format("%d.%m."))
+        } else {
+            weekday.to_string()
+        };
+        self.dates.insert(name.clone(), date_str);
+        name
+    }
+
+    /// Semicolon separated CSV with one line per group and discipline, ordered by start time
+    pub fn to_csv(&self) -> Result<String, Box<dyn Error>> {
+        let mut writer = csv::WriterBuilder::new().delimiter(b';').from_writer(vec![]);
+        for (group_name, group) in &self.groups {
+            let mut rows: Vec<CsvRow> = group
+                .iter()
+                .map(|(discipline_name, entry)| {
+                    let (time, day) = entry.time.split_once(',').unwrap_or((&entry.time, ""));
+                    CsvRow {
+                        group: group_name.clone(),
+                        discipline: discipline_name.clone(),
+                        discipline_type: self.discipline_types.get(discipline_name).cloned().unwrap_or_default(),
+                        day: day.trim().to_string(),
+                        date: self.dates.get(day.trim()).cloned().unwrap_or_default(),
+                        time: time.trim().to_string(),
+                        location: entry.location.clone(),
+                        try_order_type: to_cell(&entry.try_order_type),
+                        duration: to_cell(&entry.duration),
+                        seeding: to_cell(&entry.seeding),
+                    }
+                })
+                .collect();
+            let sort_key = |row: &CsvRow| (NaiveDate::parse_from_str(&row.date, "%d.%m.%Y").ok(), row.time.clone());
+            rows.sort_by_key(sort_key);
+            for row in rows {
+                writer.serialize(row)?;
+            }
+        }
+        Ok(String::from_utf8(writer.into_inner()?)?)
+    }
+
+    /// Read the CSV export, the resulting time table is checked like an uploaded one
+    pub fn from_csv(csv: &str) -> Result<TimeTable, Vec<SchemaError>> {
+        let mut errors = vec![];
+        let mut dates = Map::new();
+        let mut discipline_types = Map::new();
+        let mut groups: Map<String, Value> = Map::new();
+        let mut reader = csv::ReaderBuilder::new().delimiter(b';').trim(csv::Trim::All).from_reader(csv.as_bytes());
+
+        for (index, row) in reader.deserialize::<CsvRow>().enumerate() {
+            // Line 1 is the header
+            let path = format!("line {}", index + 2);
+            let row = match row {
+                Ok(row) => row,
+                Err(e) => {
+                    errors.push(SchemaError::new(&path, e.to_string()));
+                    continue;
+                }
+            };
+            match dates.get(&row.day) {
+                Some(date) if *date != Value::String(row.date.clone()) => {
+                    errors.push(SchemaError::new(&join(&path, "date"), format!("{} is already used for {date}", row.day)));
+                }
+                _ => { dates.insert(row.day.clone(), Value::String(row.date.clone())); }
+            }
+            match discipline_types.get(&row.discipline) {
+                Some(discipline_type) if *discipline_type != Value::String(row.discipline_type.clone()) => {
+                    errors.push(SchemaError::new(&join(&path, "discipline_type"),
+                                                 format!("{} is already of type {discipline_type}", row.discipline)));
+                }
+                _ => { discipline_types.insert(row.discipline.clone(), Value::String(row.discipline_type.clone())); }
+            }
+
+            let mut entry = Map::new();
+            entry.insert(String::from("location"), Value::String(row.location));
+            entry.insert(String::from("time"), Value::String(format!("{}, {}", row.time, row.day)));
+            for (key, cell) in [("try_order_type", &row.try_order_type), ("duration", &row.duration), ("seeding", &row.seeding)] {
+                if let Some(value) = from_cell(cell) {
+                    entry.insert(key.to_string(), value);
+                }
+            }
+            let group = groups.entry(row.group).or_insert(Value::Object(Map::new()));
+            group[&row.discipline] = Value::Object(entry);
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        let mut time_table = Map::new();
+        time_table.insert(String::from("Dates"), Value::Object(dates));
+        time_table.insert(String::from("DisciplineTypes"), Value::Object(discipline_types));
+        time_table.insert(String::from("Groups"), Value::Object(groups));
+        TimeTable::from_value(&Value::Object(time_table))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::time_planner::{build_time_groups, DEFAULT_TIME_ZONE};
+    use super::TimeTable;
+
+    #[test]
+    fn schema_errors() {
+        let time_table = json!({
+            "Dates": {"Samstag": "20.09.2025", "Sonntag": "21.9."},
+            "DisciplineTypes": {"100 Meter Lauf": "Track", "Weitsprung": "Jump"},
+            "Groups": {
+                "Gruppe 1": {
+                    "100 Meter Lauf": {"location": "Start 100m", "time": "08:30, Montag"},
+                    "Weitsprung": {"location": "Weit 1", "time": "09:00 Samstag", "duration": -5},
+                    "Kugelstoß": {"time": "25:00, Samstag", "try_order_type": "Twice"},
+                },
+            },
+        });
+        let errors = TimeTable::from_value(&time_table).expect_err("Time table should be invalid");
+        let paths: Vec<&str> = errors.iter().map(|error| error.path.as_str()).collect();
+        assert_eq!(paths, vec![
+            "Dates.Sonntag",
+            "DisciplineTypes.Weitsprung",
+            "Groups.Gruppe 1.100 Meter Lauf.time",
+            "Groups.Gruppe 1.Kugelstoß",
+            "Groups.Gruppe 1.Kugelstoß.location",
+            "Groups.Gruppe 1.Kugelstoß.time",
+            "Groups.Gruppe 1.Kugelstoß.try_order_type",
+            "Groups.Gruppe 1.Weitsprung.time",
+            "Groups.Gruppe 1.Weitsprung.duration",
+        ]);
+    }
+
+    #[test]
+    fn round_trip() {
+        let time_table = json!({
+            "Dates": {"Samstag": "20.09.2025", "Sonntag": "21.09.2025"},
+            "DisciplineTypes": {"100 Meter Lauf": "Track", "Hochsprung": "Default", "1500 Meter Lauf": "None"},
+            "Groups": {
+                "Gruppe 1": {
+                    "100 Meter Lauf": {"location": "Start 100m", "time": "08:30, Samstag", "seeding": {"Random": {"seed": 7}}},
+                    "Hochsprung": {"location": "Hoch", "time": "09:00, Samstag", "duration": 90},
+                    "1500 Meter Lauf": {"location": "Start 1500m", "time": "17:00, Sonntag", "try_order_type": "Once"},
+                },
+            },
+        });
+        let time_table = TimeTable::from_value(&time_table).expect("Time table should be valid");
+        let time_groups = build_time_groups(&time_table.to_value(), DEFAULT_TIME_ZONE).expect("Time groups should be buildable");
+        assert_eq!(TimeTable::from_time_groups(&time_groups), time_table);
+
+        let csv = time_table.to_csv().expect("Time table should be writable as CSV");
+        assert!(csv.starts_with("group;discipline;discipline_type;day;date;time;location"));
+        assert_eq!(TimeTable::from_csv(&csv).expect("CSV should be valid"), time_table);
+    }
+}
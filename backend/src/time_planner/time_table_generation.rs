@@ -0,0 +1,300 @@
+use std::collections::HashMap;
+use std::error::Error;
+use chrono::NaiveTime;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use super::time_table_validation::MIN_TRACK_RECOVERY_MINUTES;
+use crate::certificate_generation::{competition_order, CompetitionType, GroupID};
+use crate::Storage;
+
+/// All start times are multiples of this slot length (in minutes)
+const SLOT_MINUTES: i64 = 15;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScheduleDay {
+    /// Name used in the time table (e.g. "Samstag")
+    pub name: String,
+    /// Date in the format of the time table (e.g. "20.09.2025")
+    pub date: String,
+    /// First possible start (e.g. "08:30")
+    pub start: String,
+    /// Latest end of the last discipline (e.g. "18:00")
+    pub end: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScheduleGroup {
+    pub name: String,
+    pub competition_type: CompetitionType,
+    pub athletes: usize,
+    /// Days on which the group competes, all days if empty. The disciplines are split evenly over the days.
+    #[serde(default)]
+    pub days: Vec<String>,
+}
+
+/// Input of the time table generation
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScheduleRequest {
+    pub days: Vec<ScheduleDay>,
+    /// Groups to schedule, the stored groups are used if empty
+    #[serde(default)]
+    pub groups: Vec<ScheduleGroup>,
+    /// Available locations per discipline
+    pub locations: HashMap<String, Vec<String>>,
+    /// Estimated minutes per athlete and discipline
+    pub durations: HashMap<String, f64>,
+    /// Break of a group between two disciplines in minutes
+    #[serde(default = "default_break_minutes")]
+    pub break_minutes: i64,
+    /// Starting order type per discipline ("Track", "Default" or "None"), derived from the name if not given
+    #[serde(default)]
+    pub discipline_types: HashMap<String, String>,
+}
+
+fn default_break_minutes() -> i64 {
+    15
+}
+
+/// Starting order type of a discipline as used in the time table: runs up to 400 meters are run in lanes,
+/// longer runs without order, all other disciplines in the default order
+fn default_discipline_type(discipline: &str) -> &'static str {
+    if !discipline.contains("Meter") {
+        return "Default";
+    }
+    let distance = discipline
+        .split_whitespace()
+        .next()
+        .and_then(|distance| distance.parse::<u32>().ok());
+    match distance {
+        Some(distance) if distance <= 400 => "Track",
+        Some(_) => "None",
+        None => "Default",
+    }
+}
+
+fn parse_minutes(time: &str) -> Result<i64, Box<dyn Error>> {
+    let time = NaiveTime::parse_from_str(time.trim(), "%H:%M")
+        .map_err(|e| format!("Time {time} could not be parsed: {e}"))?;
+    Ok(time.signed_duration_since(NaiveTime::MIN).num_minutes())
+}
+
+fn round_up_to_slot(minutes: i64) -> i64 {
+    (minutes + SLOT_MINUTES - 1) / SLOT_MINUTES * SLOT_MINUTES
+}
+
+struct PlannedDiscipline {
+    name: String,
+    day: usize,
+    duration: i64,
+    track: bool,
+}
+
+/// Generate a conflict free time table in the upload format of the time table (see `store_time_plan`).
+/// Disciplines are placed greedily at the earliest free slot, the groups take turns discipline by discipline.
+pub fn generate_time_table(request: &ScheduleRequest) -> Result<Value, Box<dyn Error>> {
+    if request.days.is_empty() {
+        return Err(Box::from("No days given"));
+    }
+    let day_bounds = request.days
+        .iter()
+        .map(|day| Ok((parse_minutes(&day.start)?, parse_minutes(&day.end)?)))
+        .collect::<Result<Vec<(i64, i64)>, Box<dyn Error>>>()?;
+    let discipline_type = |discipline: &str| -> String {
+        request.discipline_types
+            .get(discipline)
+            .cloned()
+            .unwrap_or(default_discipline_type(discipline).to_string())
+    };
+
+    // Plan the day and duration of every discipline of every group
+    let mut plans: Vec<Vec<PlannedDiscipline>> = vec![];
+    for group in &request.groups {
+        let days: Vec<usize> = if group.days.is_empty() {
+            (0..request.days.len()).collect()
+        } else {
+            group.days
+                .iter()
+                .map(|name| request.days.iter().position(|day| day.name == *name)
+                    .ok_or(format!("Day {name} of group {} not found", group.name)))
+                .collect::<Result<_, _>>()?
+        };
+        let disciplines = competition_order(&group.competition_type);
+        let per_day = disciplines.len().div_ceil(days.len());
+
+        let mut plan = vec![];
+        for (index, discipline) in disciplines.into_iter().enumerate() {
+            let minutes_per_athlete = request.durations
+                .get(discipline)
+                .ok_or(format!("No duration given for {discipline}"))?;
+            let duration = round_up_to_slot((minutes_per_athlete * group.athletes.max(1) as f64).ceil() as i64)
+                .max(SLOT_MINUTES);
+            plan.push(PlannedDiscipline {
+                name: discipline.to_string(),
+                day: days[index / per_day],
+                duration,
+                track: discipline_type(discipline) == "Track",
+            });
+        }
+        plans.push(plan);
+    }
+
+    // Booked (day, start, end) per location, (day, end) of the last discipline and last track event per group
+    let mut bookings: HashMap<&str, Vec<(usize, i64, i64)>> = HashMap::new();
+    let mut last_discipline: Vec<Option<(usize, i64)>> = vec![None; request.groups.len()];
+    let mut last_track_event: Vec<Option<(usize, i64)>> = vec![None; request.groups.len()];
+    let mut groups: Map<String, Value> = Map::new();
+
+    let max_disciplines = plans.iter().map(Vec::len).max().unwrap_or(0);
+    for index in 0..max_disciplines {
+        for (group_index, group) in request.groups.iter().enumerate() {
+            let discipline = match plans[group_index].get(index) {
+                Some(discipline) => discipline,
+                None => continue,
+            };
+            let (day_start, day_end) = day_bounds[discipline.day];
+            let mut earliest = day_start;
+            if let Some((day, end)) = last_discipline[group_index] {
+                if day > discipline.day {
+                    return Err(Box::from(format!("Disciplines of group {} are not in day order", group.name)));
+                } else if day == discipline.day {
+                    earliest = earliest.max(end + request.break_minutes);
+                }
+            }
+            if let Some((day, end)) = last_track_event[group_index] {
+                if day == discipline.day && discipline.track {
+                    earliest = earliest.max(end + MIN_TRACK_RECOVERY_MINUTES);
+                }
+            }
+
+            let locations = request.locations
+                .get(&discipline.name)
+                .filter(|locations| !locations.is_empty())
+                .ok_or(format!("No location given for {}", discipline.name))?;
+
+            let mut start = day_start + round_up_to_slot(earliest - day_start);
+            let (start, location) = loop {
+                let end = start + discipline.duration;
+                if end > day_end {
+                    return Err(Box::from(format!("Not enough time on {} for {} of group {}",
+                                                 request.days[discipline.day].name, discipline.name, group.name)));
+                }
+                let free_location = locations.iter().find(|location| {
+                    bookings.get(location.as_str()).is_none_or(|booked| booked
+                        .iter()
+                        .all(|(day, booked_start, booked_end)| *day != discipline.day || end <= *booked_start || *booked_end <= start))
+                });
+                match free_location {
+                    Some(location) => break (start, location),
+                    None => start += SLOT_MINUTES,
+                }
+            };
+
+            bookings.entry(location.as_str()).or_default().push((discipline.day, start, start + discipline.duration));
+            last_discipline[group_index] = Some((discipline.day, start + discipline.duration));
+            if discipline.track {
+                last_track_event[group_index] = Some((discipline.day, start + discipline.duration));
+            }
+
+            let group_entry = groups
+                .entry(group.name.clone())
+                .or_insert(Value::Object(Map::new()));
+            group_entry[&discipline.name] = json!({
+                "location": location,
+                "time": format!("{:02}:{:02}, {}", start / 60, start % 60, request.days[discipline.day].name),
+                "duration": discipline.duration,
+            });
+        }
+    }
+
+    let dates: Map<String, Value> = request.days
+        .iter()
+        .map(|day| (day.name.clone(), Value::String(day.date.clone())))
+        .collect();
+    let discipline_types: Map<String, Value> = plans
+        .iter()
+        .flatten()
+        .map(|discipline| (discipline.name.clone(), Value::String(discipline_type(&discipline.name))))
+        .collect();
+
+    Ok(json!({
+        "Groups": groups,
+        "Dates": dates,
+        "DisciplineTypes": discipline_types,
+        "Passwords": {},
+    }))
+}
+
+/// Generate the time table for the stored groups if the request does not contain any groups
+pub async fn generate_time_table_for_storage(
+    storage: &(dyn Storage + Send + Sync),
+    mut request: ScheduleRequest,
+) -> Result<Value, Box<dyn Error>> {
+    if request.groups.is_empty() {
+        let mut group_names: Vec<String> = storage.get_athletes().await.into_keys().collect();
+        group_names.sort();
+        for group_name in group_names {
+            if let Some(group) = storage.get_group(&GroupID::new(&group_name)).await {
+                request.groups.push(ScheduleGroup {
+                    name: group_name,
+                    competition_type: group.competition_type(),
+                    athletes: group.athletes().iter().filter(|athlete| !athlete.is_deregistered()).count(),
+                    days: vec![],
+                });
+            }
+        }
+    }
+    generate_time_table(&request)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::certificate_generation::{competition_order, CompetitionType};
+    use crate::time_planner::{build_time_groups, DEFAULT_TIME_ZONE};
+    use crate::time_planner::time_table_validation::check_time_table;
+    use super::{generate_time_table, ScheduleDay, ScheduleGroup, ScheduleRequest};
+
+    fn get_day(name: &str, date: &str) -> ScheduleDay {
+        ScheduleDay { name: name.to_string(), date: date.to_string(), start: "08:30".to_string(), end: "18:00".to_string() }
+    }
+
+    #[test]
+    fn generate_conflict_free_time_table() {
+        let decathlon = competition_order(&CompetitionType::Decathlon);
+        let triathlon = competition_order(&CompetitionType::Triathlon);
+        let locations = decathlon.iter().chain(triathlon.iter())
+            .map(|discipline| (discipline.to_string(), vec![format!("{discipline} 1")]))
+            .collect();
+        let durations = decathlon.iter().chain(triathlon.iter())
+            .map(|discipline| (discipline.to_string(), 4.))
+            .collect();
+        let request = ScheduleRequest {
+            days: vec![get_day("Samstag", "20.09.2025"), get_day("Sonntag", "21.09.2025")],
+            groups: vec![
+                ScheduleGroup { name: "Gruppe 1".to_string(), competition_type: CompetitionType::Decathlon, athletes: 12, days: vec![] },
+                ScheduleGroup { name: "Gruppe 2".to_string(), competition_type: CompetitionType::Decathlon, athletes: 10, days: vec![] },
+                ScheduleGroup { name: "U10".to_string(), competition_type: CompetitionType::Triathlon, athletes: 8,
+                    days: vec!["Samstag".to_string()] },
+            ],
+            locations,
+            durations,
+            break_minutes: 15,
+            discipline_types: HashMap::new(),
+        };
+
+        let time_table = generate_time_table(&request).expect("Time table should be generated");
+        assert_eq!(time_table["Groups"]["Gruppe 1"]["100 Meter Lauf"]["time"], "08:30, Samstag");
+        assert_eq!(time_table["Groups"]["Gruppe 1"]["110 Meter Hürden"]["time"], "08:30, Sonntag");
+        assert_eq!(time_table["DisciplineTypes"]["1500 Meter Lauf"], "None");
+
+        let time_groups = build_time_groups(&time_table, DEFAULT_TIME_ZONE).expect("Generated time table should be buildable");
+        let competition_types = request.groups
+            .iter()
+            .map(|group| (group.name.clone(), group.competition_type.clone()))
+            .collect();
+        let report = check_time_table(&time_groups, &competition_types, &[]);
+        assert!(report.is_valid(), "{:?}", report.errors);
+        assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+    }
+}
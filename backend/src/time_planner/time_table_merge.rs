@@ -0,0 +1,259 @@
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use super::{Discipline, DisciplineState, TimeGroup};
+use super::time_table_validation::TimeTableReport;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum ChangeKind {
+    GroupAdded,
+    /// Stored group which is not part of the uploaded time table
+    GroupMissing,
+    DisciplineAdded,
+    DisciplineRemoved,
+    DisciplineChanged,
+}
+
+/// Change of a stored time group caused by the upload of a time table
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TimeTableChange {
+    pub kind: ChangeKind,
+    pub group: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub discipline: Option<String>,
+    /// Changed values, e.g. "location: Weit 1 → Weit 2"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<String>,
+}
+
+/// Summary of a time table upload, groups without any change are listed as unchanged
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct TimeTableChanges {
+    pub changes: Vec<TimeTableChange>,
+    pub unchanged_groups: Vec<String>,
+}
+
+/// Response of a time table upload: the validation report and the changes of the stored time groups
+#[derive(Serialize, Debug, Clone)]
+pub struct UploadSummary {
+    #[serde(flatten)]
+    pub report: TimeTableReport,
+    #[serde(flatten)]
+    pub changes: TimeTableChanges,
+}
+
+impl Discipline {
+    /// Take over the schedule of the uploaded discipline. The progress, retired athletes and the starting order are
+    /// kept, the starting order is only replaced if the type or the seeding in the time table of a discipline which
+    /// has not started changed. A seeding changed through the API and the seed of a random draw are kept otherwise.
+    fn merge_upload(&mut self, uploaded: Discipline, time_zone: Tz) -> Vec<String> {
+        let mut details = vec![];
+        if self.location != uploaded.location {
+            details.push(format!("location: {} → {}", self.location, uploaded.location));
+        }
+        if self.start_time != uploaded.start_time {
+            let local = |discipline: &Discipline| discipline.local_start_time(time_zone).format("%d.%m.%Y %H:%M");
+            details.push(format!("time: {} → {}", local(self), local(&uploaded)));
+        }
+        if self.duration != uploaded.duration {
+            details.push(format!("duration: {:?} → {:?}", self.duration, uploaded.duration));
+        }
+        if self.try_order_type != uploaded.try_order_type {
+            details.push(format!("try_order_type: {:?} → {:?}", self.try_order_type, uploaded.try_order_type));
+        }
+        let seeding_changed = self.time_table_seeding != uploaded.time_table_seeding;
+        if seeding_changed {
+            details.push(format!("seeding: {:?} → {:?}", self.time_table_seeding, uploaded.time_table_seeding));
+        }
+        if self.discipline_type != uploaded.discipline_type {
+            details.push(format!("discipline_type: {:?} → {:?}", self.discipline_type, uploaded.discipline_type));
+        }
+        let order_changed = seeding_changed || self.discipline_type != uploaded.discipline_type;
+        if order_changed && self.state == DisciplineState::BeforeStart {
+            self.starting_order = uploaded.starting_order;
+            self.discipline_type = uploaded.discipline_type;
+            self.seeding = uploaded.seeding;
+            details.push(String::from("starting order regenerated"));
+        } else if order_changed {
+            details.push(String::from("starting order kept, the discipline has already started"));
+        }
+
+        self.location = uploaded.location;
+        self.start_time = uploaded.start_time;
+        self.duration = uploaded.duration;
+        self.try_order_type = uploaded.try_order_type;
+        self.time_table_seeding = uploaded.time_table_seeding;
+        details
+    }
+}
+
+impl TimeTableChanges {
+    /// Merge the uploaded time group into the stored one and record the changes
+    pub fn merge(&mut self, stored: Option<TimeGroup>, uploaded: TimeGroup) -> TimeGroup {
+        let Some(mut group) = stored else {
+            self.changes.push(TimeTableChange {
+                kind: ChangeKind::GroupAdded,
+                group: uploaded.name.clone(),
+                discipline: None,
+                details: vec![],
+            });
+            return uploaded;
+        };
+        let changes_before = self.changes.len();
+        let change = |kind: ChangeKind, discipline: &Discipline, details: Vec<String>| TimeTableChange {
+            kind,
+            group: group.name.clone(),
+            discipline: Some(discipline.name.clone()),
+            details,
+        };
+
+        let mut disciplines = vec![];
+        for discipline in group.disciplines.iter().filter(|discipline| uploaded.get_discipline(&discipline.name).is_none()) {
+            let details = match discipline.state {
+                DisciplineState::BeforeStart => vec![],
+                _ => vec![format!("discipline was {:?}", discipline.state)],
+            };
+            self.changes.push(change(ChangeKind::DisciplineRemoved, discipline, details));
+        }
+        for uploaded_discipline in uploaded.disciplines {
+            match group.disciplines.iter().find(|discipline| discipline.name == uploaded_discipline.name) {
+                Some(stored_discipline) => {
+                    let mut discipline = stored_discipline.clone();
+                    let details = discipline.merge_upload(uploaded_discipline, uploaded.time_zone);
+                    if !details.is_empty() {
+                        self.changes.push(change(ChangeKind::DisciplineChanged, &discipline, details));
+                    }
+                    disciplines.push(discipline);
+                }
+                None => {
+                    self.changes.push(change(ChangeKind::DisciplineAdded, &uploaded_discipline, vec![]));
+                    disciplines.push(uploaded_discipline);
+                }
+            }
+        }
+
+        disciplines.sort_by_key(|discipline| discipline.start_time);
+        group.disciplines = disciplines;
+        group.current_discipline = group.current_index().unwrap_or(0);
+        group.lanes = uploaded.lanes;
+        group.time_zone = uploaded.time_zone;
+
+        if self.changes.len() == changes_before {
+            self.unchanged_groups.push(group.name.clone());
+        }
+        group
+    }
+
+    /// Record the stored time groups which are not part of the uploaded time table, they are kept unchanged
+    pub fn missing_groups(&mut self, stored: &[TimeGroup], uploaded: &[String]) {
+        let mut missing: Vec<&TimeGroup> = stored
+            .iter()
+            .filter(|group| !uploaded.contains(&group.name))
+            .collect();
+        missing.sort_by(|a, b| a.name.cmp(&b.name));
+        for group in missing {
+            let started = group.disciplines.iter().any(|discipline| discipline.state != DisciplineState::BeforeStart);
+            let details = if started {
+                vec![String::from("group is kept, it has already started")]
+            } else {
+                vec![String::from("group is kept")]
+            };
+            self.changes.push(TimeTableChange {
+                kind: ChangeKind::GroupMissing,
+                group: group.name.clone(),
+                discipline: None,
+                details,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use crate::time_planner::{build_time_groups, DisciplineState, SeedingInfo, SeedingStrategy, StateChange, TimeGroup, DEFAULT_TIME_ZONE};
+    use super::{ChangeKind, TimeTableChanges};
+
+    fn get_time_group(weitsprung_location: &str, kugelstoss: bool) -> TimeGroup {
+        let mut group: Value = json!({
+            "100 Meter Lauf": {"location": "Start 100m", "time": "08:30, Samstag"},
+            "Weitsprung": {"location": weitsprung_location, "time": "09:00, Samstag"},
+        });
+        if kugelstoss {
+            group["Kugelstoß"] = json!({"location": "Kugel", "time": "10:00, Samstag"});
+        }
+        let time_table = json!({
+            "Dates": {"Samstag": "20.09.2025"},
+            "DisciplineTypes": {"100 Meter Lauf": "Track", "Weitsprung": "Default", "Kugelstoß": "Default"},
+            "Groups": {"Gruppe 1": group},
+        });
+        build_time_groups(&time_table, DEFAULT_TIME_ZONE).expect("Time groups should be buildable").remove(0)
+    }
+
+    #[test]
+    fn keep_progress() {
+        let mut stored = get_time_group("Weit 1", true);
+        let start = StateChange { name: String::from("100 Meter Lauf"), state: DisciplineState::Active, force: false };
+        stored.change_discipline_state(&start, &[]).expect("Start should be allowed");
+        let finish = StateChange { state: DisciplineState::Finished, ..start };
+        stored.change_discipline_state(&finish, &[]).expect("Finish should be allowed");
+
+        let mut changes = TimeTableChanges::default();
+        let merged = changes.merge(Some(stored), get_time_group("Weit 2", false));
+        let kinds: Vec<(ChangeKind, Option<&str>)> = changes.changes
+            .iter()
+            .map(|change| (change.kind.clone(), change.discipline.as_deref()))
+            .collect();
+        assert_eq!(kinds, vec![(ChangeKind::DisciplineRemoved, Some("Kugelstoß")), (ChangeKind::DisciplineChanged, Some("Weitsprung"))]);
+        assert_eq!(changes.changes[1].details, vec!["location: Weit 1 → Weit 2"]);
+
+        // The finished discipline stays finished, the group continues with Weitsprung
+        let sprint = merged.get_discipline("100 Meter Lauf").expect("Discipline should be kept");
+        assert!(sprint.is_finished());
+        assert_eq!(merged.get_current_discipline().map(|discipline| discipline.name()), Some("Weitsprung"));
+
+        let mut changes = TimeTableChanges::default();
+        changes.merge(Some(merged), get_time_group("Weit 2", false));
+        assert!(changes.changes.is_empty());
+        assert_eq!(changes.unchanged_groups, vec!["Gruppe 1"]);
+    }
+
+    fn get_seeded_time_group(seeding: Value) -> TimeGroup {
+        let time_table = json!({
+            "Dates": {"Samstag": "20.09.2025"},
+            "DisciplineTypes": {"100 Meter Lauf": "Track", "Weitsprung": "Default"},
+            "Groups": {"Gruppe 1": {
+                "100 Meter Lauf": {"location": "Start 100m", "time": "08:30, Samstag", "seeding": seeding},
+                "Weitsprung": {"location": "Weit 1", "time": "09:00, Samstag"},
+            }},
+        });
+        build_time_groups(&time_table, DEFAULT_TIME_ZONE).expect("Time groups should be buildable").remove(0)
+    }
+
+    #[test]
+    fn keep_seeding() {
+        let mut stored = get_seeded_time_group(json!({"Random": {}}));
+        let seeding = stored.get_discipline("100 Meter Lauf").expect("Discipline should exist").seeding();
+        assert!(matches!(seeding, SeedingStrategy::Random { seed: Some(_) }));
+        stored.change_seeding("Weitsprung", SeedingStrategy::TotalPoints, &SeedingInfo::default())
+            .expect("Seeding should be changeable");
+
+        // The upload of the same file draws a new seed, the recorded seed and the seeding changed through the API are kept
+        let mut changes = TimeTableChanges::default();
+        let merged = changes.merge(Some(stored), get_seeded_time_group(json!({"Random": {}})));
+        assert!(changes.changes.is_empty(), "{:?}", changes.changes);
+        assert_eq!(merged.get_discipline("100 Meter Lauf").map(|discipline| discipline.seeding()), Some(seeding));
+        assert_eq!(merged.get_discipline("Weitsprung").map(|discipline| discipline.seeding()), Some(SeedingStrategy::TotalPoints));
+
+        let mut changes = TimeTableChanges::default();
+        let merged = changes.merge(Some(merged), get_seeded_time_group(json!("TotalPoints")));
+        assert_eq!(changes.changes[0].details, vec!["seeding: Some(Random { seed: None }) → Some(TotalPoints)", "starting order regenerated"]);
+        assert_eq!(merged.get_discipline("100 Meter Lauf").map(|discipline| discipline.seeding()), Some(SeedingStrategy::TotalPoints));
+
+        // Stored groups missing in the upload are reported
+        let mut changes = TimeTableChanges::default();
+        changes.missing_groups(&[merged], &[String::from("Gruppe 2")]);
+        assert_eq!(changes.changes[0].kind, ChangeKind::GroupMissing);
+        assert_eq!(changes.changes[0].group, "Gruppe 1");
+    }
+}
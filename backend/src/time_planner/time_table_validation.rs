@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{Discipline, DisciplineType, TimeGroup};
+use super::venue::{capacity, check_venue, Station};
+use crate::certificate_generation::{competition_order, AgeGroupSelector, CompetitionType, GroupID};
+use crate::Storage;
+
+/// Minimum break between the end of a track event and the start of the next one of the same group
+pub(super) const MIN_TRACK_RECOVERY_MINUTES: i64 = 30;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum IssueKind {
+    /// Two groups use the same location at overlapping times
+    LocationConflict,
+    /// A discipline of a group starts before the previous one has ended
+    DisciplineOverlap,
+    /// Too little time between two track events of a group
+    ShortRecovery,
+    /// Discipline of the competition type is not part of the time table
+    MissingDiscipline,
+    /// Competition type of the group is unknown, missing disciplines can not be checked
+    UnknownCompetitionType,
+    /// Location is not a station of the venue
+    UnknownStation,
+    /// Station does not support the discipline
+    UnsupportedDiscipline,
+    /// More groups use a station at the same time than it can hold
+    StationCapacity,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TimeTableIssue {
+    pub kind: IssueKind,
+    pub groups: Vec<String>,
+    pub disciplines: Vec<String>,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct TimeTableReport {
+    pub errors: Vec<TimeTableIssue>,
+    pub warnings: Vec<TimeTableIssue>,
+}
+
+impl TimeTableReport {
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Overlaps are errors if they follow from the durations given in the time table. Overlaps based on the
+    /// estimated durations are only warnings, e.g. youth groups often share a station with short offsets.
+    pub(super) fn push_overlap(&mut self, mut issue: TimeTableIssue, certain: bool) {
+        if certain {
+            self.errors.push(issue);
+        } else {
+            issue.message.push_str(" (estimated duration)");
+            self.warnings.push(issue);
+        }
+    }
+}
+
+impl Discipline {
+    /// Planned duration, estimated from the discipline type if the time table does not define it
+    pub(super) fn planned_duration(&self) -> Duration {
+        let minutes = self.duration.unwrap_or(match self.discipline_type {
+            DisciplineType::Track => 15,
+            DisciplineType::Time => 15,
+            DisciplineType::Distance => 45,
+            DisciplineType::Height => 75,
+        });
+        Duration::minutes(minutes)
+    }
+
+    /// Planned end of the discipline
+    pub fn end_time(&self) -> DateTime<Utc> {
+        self.start_time + self.planned_duration()
+    }
+
+    fn overlaps(&self, other: &Discipline) -> bool {
+        self.start_time < other.end_time() && other.start_time < self.end_time()
+    }
+
+    /// The overlap with the other discipline does not depend on an estimated duration, i.e. the discipline which
+    /// starts first has a duration in the time table
+    pub(super) fn overlap_is_certain(&self, other: &Discipline) -> bool {
+        match self.start_time.cmp(&other.start_time) {
+            std::cmp::Ordering::Less => self.duration.is_some(),
+            std::cmp::Ordering::Equal => true,
+            std::cmp::Ordering::Greater => other.duration.is_some(),
+        }
+    }
+}
+
+/// Check the time groups for conflicts. The competition types (by group name) are used to find missing
+/// disciplines, the stations of the venue to check the locations.
+pub fn check_time_table(time_groups: &[TimeGroup], competition_types: &HashMap<String, CompetitionType>,
+                        stations: &[Station]) -> TimeTableReport {
+    let mut report = TimeTableReport::default();
+
+    for (index, time_group) in time_groups.iter().enumerate() {
+        let mut disciplines: Vec<&Discipline> = time_group.disciplines.iter().collect();
+        disciplines.sort_by_key(|discipline| discipline.start_time);
+
+        for pair in disciplines.windows(2) {
+            let (previous, next) = (pair[0], pair[1]);
+            if previous.overlaps(next) {
+                report.push_overlap(TimeTableIssue {
+                    kind: IssueKind::DisciplineOverlap,
+                    groups: vec![time_group.name.clone()],
+                    disciplines: vec![previous.name.clone(), next.name.clone()],
+                    message: format!("{}: {} starts before {} has ended", time_group.name, next.name, previous.name),
+                }, previous.overlap_is_certain(next));
+            }
+        }
+
+        let track_events: Vec<&&Discipline> = disciplines
+            .iter()
+            .filter(|discipline| matches!(discipline.discipline_type, DisciplineType::Track))
+            .collect();
+        for pair in track_events.windows(2) {
+            let (previous, next) = (pair[0], pair[1]);
+            let recovery = next.start_time - previous.end_time();
+            if recovery >= Duration::zero() && recovery < Duration::minutes(MIN_TRACK_RECOVERY_MINUTES) {
+                report.warnings.push(TimeTableIssue {
+                    kind: IssueKind::ShortRecovery,
+                    groups: vec![time_group.name.clone()],
+                    disciplines: vec![previous.name.clone(), next.name.clone()],
+                    message: format!("{}: only {} minutes between {} and {}", time_group.name,
+                                     recovery.num_minutes(), previous.name, next.name),
+                });
+            }
+        }
+
+        for other_group in &time_groups[index + 1..] {
+            for discipline in &time_group.disciplines {
+                for other in &other_group.disciplines {
+                    // Groups starting together at the same location share the session (e.g. joint runs)
+                    let shared_session = discipline.start_time == other.start_time;
+                    // Stations for several groups are checked against their capacity
+                    let single_station = capacity(stations, &discipline.location) == 1;
+                    if discipline.location == other.location && discipline.overlaps(other) && !shared_session && single_station {
+                        report.push_overlap(TimeTableIssue {
+                            kind: IssueKind::LocationConflict,
+                            groups: vec![time_group.name.clone(), other_group.name.clone()],
+                            disciplines: vec![discipline.name.clone(), other.name.clone()],
+                            message: format!("{} ({}) and {} ({}) use {} at the same time", time_group.name,
+                                             discipline.name, other_group.name, other.name, discipline.location),
+                        }, discipline.overlap_is_certain(other));
+                    }
+                }
+            }
+        }
+
+        match competition_types.get(&time_group.name) {
+            Some(competition_type) => {
+                for discipline_name in competition_order(competition_type) {
+                    if time_group.get_discipline(discipline_name).is_none() {
+                        report.warnings.push(TimeTableIssue {
+                            kind: IssueKind::MissingDiscipline,
+                            groups: vec![time_group.name.clone()],
+                            disciplines: vec![discipline_name.to_string()],
+                            message: format!("{}: {} is missing in the time table", time_group.name, discipline_name),
+                        });
+                    }
+                }
+            }
+            None => report.warnings.push(TimeTableIssue {
+                kind: IssueKind::UnknownCompetitionType,
+                groups: vec![time_group.name.clone()],
+                disciplines: vec![],
+                message: format!("{}: competition type unknown, disciplines not checked", time_group.name),
+            }),
+        }
+    }
+
+    check_venue(time_groups, stations, &mut report);
+    report
+}
+
+/// Competition type derived from the group name ("Gruppe ..." for decathlon groups, age classes like "U12" for youth
+/// groups)
+fn competition_type_from_name(group_name: &str) -> Option<CompetitionType> {
+    if group_name.contains("Gruppe") {
+        Some(CompetitionType::Decathlon)
+    } else if group_name.starts_with('U') {
+        AgeGroupSelector::build(group_name).ok().map(|selector| selector.competition_type)
+    } else {
+        None
+    }
+}
+
+/// Validate the time groups, the competition types are taken from the stored groups or derived from the
+/// group name ("Gruppe ..." for decathlon groups, age classes like "U12" for youth groups). The stations are
+/// taken from the active competition.
+pub async fn validate_time_table(storage: &(dyn Storage + Send + Sync), time_groups: &[TimeGroup]) -> TimeTableReport {
+    let mut competition_types = HashMap::new();
+    for time_group in time_groups {
+        let competition_type = match storage.get_group(&GroupID::new(&time_group.name)).await {
+            Some(group) => Some(group.competition_type()),
+            None => competition_type_from_name(&time_group.name),
+        };
+        if let Some(competition_type) = competition_type {
+            competition_types.insert(time_group.name.clone(), competition_type);
+        }
+    }
+    let stations = storage
+        .get_active_competition()
+        .await
+        .map(|competition| competition.config.stations)
+        .unwrap_or_default();
+    check_time_table(time_groups, &competition_types, &stations)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use serde_json::{json, Value};
+    use crate::certificate_generation::CompetitionType;
+    use crate::time_planner::{build_time_groups, DEFAULT_TIME_ZONE};
+    use super::{check_time_table, competition_type_from_name, IssueKind};
+
+    fn get_time_table(groups: Value) -> Value {
+        json!({
+            "Dates": {"Samstag": "20.09.2025", "Sonntag": "21.09.2025"},
+            "DisciplineTypes": {"60 Meter Lauf": "Track", "Weitsprung": "Default", "Schlagball": "Default"},
+            "Groups": groups,
+        })
+    }
+
+    #[test]
+    fn old_decathlon_time_table_is_valid() {
+        let content = std::fs::read_to_string("tests/old_data_tests/timetable.json").expect("Time table should exist");
+        let time_table: Value = serde_json::from_str(&content).expect("Time table should be valid JSON");
+        let time_groups = build_time_groups(&time_table, DEFAULT_TIME_ZONE).expect("Time groups should be buildable");
+
+        let competition_types = time_groups
+            .iter()
+            .filter_map(|group| Some((group.name().to_string(), competition_type_from_name(group.name())?)))
+            .collect();
+        let report = check_time_table(&time_groups, &competition_types, &[]);
+        assert!(report.is_valid(), "{:?}", report.errors);
+        // Youth groups share the long jump pits with short offsets, the overlaps are based on estimated durations
+        assert!(report.warnings.iter().any(|warning| warning.kind == IssueKind::LocationConflict
+            && warning.groups.contains(&String::from("U8"))));
+        assert!(report.warnings.iter().all(|warning| !warning.groups.iter().any(|group| group.contains("Gruppe"))
+            || warning.kind == IssueKind::ShortRecovery), "{:?}", report.warnings);
+    }
+
+    #[test]
+    fn detect_conflicts() {
+        let time_table = get_time_table(json!({
+            "U10": {
+                "60 Meter Lauf": {"location": "Start 100m", "time": "09:00, Samstag", "duration": 15},
+                "Weitsprung": {"location": "Weit 1", "time": "09:20, Samstag"},
+            },
+            "U12": {
+                "60 Meter Lauf": {"location": "Start 100m", "time": "09:10, Samstag"},
+                "Weitsprung": {"location": "Weit 1", "time": "09:30, Samstag", "duration": 20},
+                "Schlagball": {"location": "Wurf", "time": "09:45, Samstag"},
+            },
+        }));
+        let time_groups = build_time_groups(&time_table, DEFAULT_TIME_ZONE).expect("Time groups should be buildable");
+        let competition_types = HashMap::from([
+            (String::from("U10"), CompetitionType::Triathlon),
+            (String::from("U12"), CompetitionType::Triathlon),
+        ]);
+
+        let report = check_time_table(&time_groups, &competition_types, &[]);
+        let kinds: Vec<&IssueKind> = report.errors.iter().map(|issue| &issue.kind).collect();
+        assert_eq!(kinds, vec![&IssueKind::LocationConflict, &IssueKind::DisciplineOverlap]);
+        assert_eq!(report.errors[0].disciplines, vec!["60 Meter Lauf", "60 Meter Lauf"]);
+        // U10 long jump has no duration, the conflict with U12 is only a warning
+        assert!(report.warnings.iter().any(|warning| warning.kind == IssueKind::LocationConflict
+            && warning.disciplines == vec!["Weitsprung", "Weitsprung"]));
+        assert!(report.warnings.iter().any(|warning| warning.kind == IssueKind::MissingDiscipline
+            && warning.groups == vec!["U10"] && warning.disciplines == vec!["Schlagball"]));
+        assert!(!report.is_valid());
+    }
+}
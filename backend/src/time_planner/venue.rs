@@ -0,0 +1,199 @@
+use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
+
+use super::time_table_validation::{IssueKind, TimeTableIssue, TimeTableReport};
+use super::{Discipline, DisciplineState, TimeGroup};
+
+/// Station of the venue (e.g. "Weit 1"), the time table locations refer to the station names
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Station {
+    pub name: String,
+    /// Disciplines which can be held at the station, any discipline if empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disciplines: Vec<String>,
+    /// Number of groups which can use the station at the same time
+    #[serde(default = "default_capacity")]
+    pub capacity: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub equipment: Option<String>,
+}
+
+fn default_capacity() -> usize {
+    1
+}
+
+impl Station {
+    fn supports(&self, discipline: &str) -> bool {
+        self.disciplines.is_empty() || self.disciplines.iter().any(|name| name == discipline)
+    }
+}
+
+/// Capacity of the station at the location, 1 for locations which are not part of the venue
+pub(super) fn capacity(stations: &[Station], location: &str) -> usize {
+    stations
+        .iter()
+        .find(|station| station.name == location)
+        .map_or(1, |station| station.capacity)
+}
+
+/// Check the locations of the time table against the stations of the venue. Nothing is checked if the venue
+/// has no stations. Stations with a capacity of 1 are checked by the location conflicts of the time table.
+pub(super) fn check_venue(time_groups: &[TimeGroup], stations: &[Station], report: &mut TimeTableReport) {
+    if stations.is_empty() {
+        return;
+    }
+    for time_group in time_groups {
+        for discipline in &time_group.disciplines {
+            match stations.iter().find(|station| station.name == discipline.location) {
+                None => report.errors.push(TimeTableIssue {
+                    kind: IssueKind::UnknownStation,
+                    groups: vec![time_group.name.clone()],
+                    disciplines: vec![discipline.name.clone()],
+                    message: format!("{}: {} is not a station of the venue", time_group.name, discipline.location),
+                }),
+                Some(station) if !station.supports(&discipline.name) => report.errors.push(TimeTableIssue {
+                    kind: IssueKind::UnsupportedDiscipline,
+                    groups: vec![time_group.name.clone()],
+                    disciplines: vec![discipline.name.clone()],
+                    message: format!("{}: {} can not be held at {}", time_group.name, discipline.name, station.name),
+                }),
+                Some(_) => {}
+            }
+        }
+    }
+
+    for station in stations.iter().filter(|station| station.capacity > 1) {
+        let sessions: Vec<(&TimeGroup, &Discipline)> = time_groups
+            .iter()
+            .flat_map(|time_group| time_group.disciplines.iter().map(move |discipline| (time_group, discipline)))
+            .filter(|(_, discipline)| discipline.location == station.name)
+            .collect();
+        let mut reported = HashSet::new();
+        for (_, discipline) in &sessions {
+            // Groups starting together share the session, e.g. joint runs
+            let concurrent: Vec<&(&TimeGroup, &Discipline)> = sessions
+                .iter()
+                .filter(|(_, other)| other.start_time <= discipline.start_time && other.end_time() > discipline.start_time)
+                .collect();
+            let session_count = concurrent.iter().map(|(_, other)| other.start_time).collect::<HashSet<_>>().len();
+            if session_count > station.capacity && reported.insert(discipline.start_time) {
+                let certain = concurrent.iter().all(|(_, other)| other.overlap_is_certain(discipline));
+                report.push_overlap(TimeTableIssue {
+                    kind: IssueKind::StationCapacity,
+                    groups: concurrent.iter().map(|(time_group, _)| time_group.name.clone()).collect(),
+                    disciplines: concurrent.iter().map(|(_, other)| other.name.clone()).collect(),
+                    message: format!("{} is used by {} groups at the same time, capacity is {}", station.name,
+                                     session_count, station.capacity),
+                }, certain);
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StationGroup {
+    pub group: String,
+    pub discipline: String,
+}
+
+/// Groups which are currently at the station
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StationOccupancy {
+    pub station: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub equipment: Option<String>,
+    pub capacity: usize,
+    /// The location is used in the time table but not a station of the venue
+    #[serde(default)]
+    pub unknown_station: bool,
+    pub groups: Vec<StationGroup>,
+}
+
+/// Occupancy of all stations from the active disciplines of the time groups
+pub fn station_occupancy(time_groups: &[TimeGroup], stations: &[Station]) -> Vec<StationOccupancy> {
+    let mut occupancy: Vec<StationOccupancy> = stations
+        .iter()
+        .map(|station| StationOccupancy {
+            station: station.name.clone(),
+            equipment: station.equipment.clone(),
+            capacity: station.capacity,
+            unknown_station: false,
+            groups: vec![],
+        })
+        .collect();
+
+    for time_group in time_groups {
+        for discipline in time_group.disciplines.iter().filter(|discipline| discipline.state == DisciplineState::Active) {
+            let index = match occupancy.iter().position(|station| station.station == discipline.location) {
+                Some(index) => index,
+                None => {
+                    occupancy.push(StationOccupancy {
+                        station: discipline.location.clone(),
+                        equipment: None,
+                        capacity: 1,
+                        unknown_station: !stations.is_empty(),
+                        groups: vec![],
+                    });
+                    occupancy.len() - 1
+                }
+            };
+            occupancy[index].groups.push(StationGroup {
+                group: time_group.name.clone(),
+                discipline: discipline.name.clone(),
+            });
+        }
+    }
+    occupancy
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use serde_json::json;
+    use crate::time_planner::{build_time_groups, DisciplineState, StateChange, DEFAULT_TIME_ZONE};
+    use crate::time_planner::time_table_validation::{check_time_table, IssueKind};
+    use super::{station_occupancy, Station};
+
+    fn get_station(name: &str, disciplines: &[&str], capacity: usize) -> Station {
+        Station {
+            name: name.to_string(),
+            disciplines: disciplines.iter().map(|discipline| discipline.to_string()).collect(),
+            capacity,
+            equipment: None,
+        }
+    }
+
+    #[test]
+    fn stations() {
+        let time_table = json!({
+            "Dates": {"Samstag": "20.09.2025"},
+            "DisciplineTypes": {"Weitsprung": "Default", "Kugelstoß": "Default"},
+            "Groups": {
+                "Gruppe 1": {
+                    "Weitsprung": {"location": "Sprunggrube", "time": "09:00, Samstag", "duration": 45},
+                    "Kugelstoß": {"location": "Kugel", "time": "10:00, Samstag"},
+                },
+                "Gruppe 2": {"Weitsprung": {"location": "Sprunggrube", "time": "09:15, Samstag", "duration": 45}},
+                "Gruppe 3": {
+                    "Weitsprung": {"location": "Sprunggrube", "time": "09:20, Samstag"},
+                    "Kugelstoß": {"location": "Weit 2", "time": "10:30, Samstag"},
+                },
+            },
+        });
+        let mut time_groups = build_time_groups(&time_table, DEFAULT_TIME_ZONE).expect("Time groups should be buildable");
+        let stations = vec![get_station("Sprunggrube", &["Weitsprung"], 2), get_station("Weit 2", &["Weitsprung"], 1)];
+
+        let report = check_time_table(&time_groups, &HashMap::new(), &stations);
+        let kinds: Vec<&IssueKind> = report.errors.iter().map(|issue| &issue.kind).collect();
+        // Two groups fit into the pit, the third one does not
+        assert_eq!(kinds, vec![&IssueKind::UnknownStation, &IssueKind::UnsupportedDiscipline, &IssueKind::StationCapacity]);
+        assert_eq!(report.errors[2].groups.len(), 3);
+
+        let start = StateChange { name: String::from("Weitsprung"), state: DisciplineState::Active, force: false };
+        time_groups[1].change_discipline_state(&start, &[]).expect("Start should be allowed");
+        let occupancy = station_occupancy(&time_groups, &stations);
+        assert_eq!(occupancy[0].station, "Sprunggrube");
+        assert_eq!(occupancy[0].groups.iter().map(|group| group.group.as_str()).collect::<Vec<_>>(), vec!["Gruppe 2"]);
+        assert!(occupancy[1].groups.is_empty());
+    }
+}
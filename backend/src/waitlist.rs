@@ -0,0 +1,205 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use chrono::serde::ts_seconds;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+
+use crate::certificate_generation::{AthleteID, CompetitionType, GroupID};
+use crate::Storage;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct WaitlistEntry {
+    pub athlete_id: AthleteID,
+    /// Group the athlete registered for
+    pub group_name: String,
+    #[serde(with = "ts_seconds")]
+    pub registered_at: DateTime<Utc>,
+}
+
+/// Athlete moved from the waitlist into a group, shown to the registration desk
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Promotion {
+    pub athlete_id: AthleteID,
+    pub group_name: String,
+    #[serde(with = "ts_seconds")]
+    pub promoted_at: DateTime<Utc>,
+}
+
+/// Athletes waiting for a free place in a full group, one waitlist per competition type
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Waitlist {
+    pub competition_type: CompetitionType,
+    pub entries: Vec<WaitlistEntry>,
+    #[serde(default)]
+    pub promotions: Vec<Promotion>,
+}
+
+impl Waitlist {
+    pub fn new(competition_type: CompetitionType) -> Self {
+        Waitlist {
+            competition_type,
+            entries: vec![],
+            promotions: vec![],
+        }
+    }
+
+    /// Add the athlete to the end of the waitlist, returns the position (starting with 1)
+    pub fn add(&mut self, athlete_id: AthleteID, group_name: &str) -> usize {
+        if let Some(position) = self.entries.iter().position(|entry| entry.athlete_id == athlete_id) {
+            return position + 1;
+        }
+        self.entries.push(WaitlistEntry {
+            athlete_id,
+            group_name: group_name.to_string(),
+            registered_at: Utc::now(),
+        });
+        self.entries.len()
+    }
+
+    pub fn remove(&mut self, athlete_id: &AthleteID) -> Option<WaitlistEntry> {
+        let position = self.entries.iter().position(|entry| entry.athlete_id == *athlete_id)?;
+        Some(self.entries.remove(position))
+    }
+
+    /// Replace the athlete ids of the entries and promotions, e.g. by pseudonyms
+    pub fn rename_athletes(&mut self, rename: impl Fn(&AthleteID) -> AthleteID) {
+        self.entries.iter_mut().for_each(|entry| entry.athlete_id = rename(&entry.athlete_id));
+        self.promotions.iter_mut().for_each(|promotion| promotion.athlete_id = rename(&promotion.athlete_id));
+    }
+
+    /// Take the next athlete of the waitlist and record the promotion into the given group. Athletes registered for
+    /// the group come first, then athletes which fit into the group (e.g. same age class), then the first athlete.
+    pub fn promote_next(&mut self, group_name: &str, fits: impl Fn(&WaitlistEntry) -> bool) -> Option<Promotion> {
+        let position = self.entries.iter().position(|entry| entry.group_name == group_name)
+            .or_else(|| self.entries.iter().position(fits))
+            .or_else(|| (!self.entries.is_empty()).then_some(0))?;
+        let entry = self.entries.remove(position);
+        let promotion = Promotion {
+            athlete_id: entry.athlete_id,
+            group_name: group_name.to_string(),
+            promoted_at: Utc::now(),
+        };
+        self.promotions.push(promotion.clone());
+        Some(promotion)
+    }
+}
+
+#[async_trait]
+pub trait WaitlistStorage {
+    async fn get_waitlist(&self, competition_type: &CompetitionType) -> Result<Waitlist, Box<dyn Error>>;
+    async fn store_waitlist(&self, waitlist: &Waitlist) -> Result<String, Box<dyn Error>>;
+}
+
+/// Fill a free place in the group with the next athlete of the waitlist (e.g. after a deregistration)
+pub async fn promote_from_waitlist(
+    storage: &(dyn Storage + Send + Sync),
+    group_id: &GroupID,
+) -> Result<Option<Promotion>, Box<dyn Error>> {
+    let group = storage.get_group(group_id).await.ok_or("Group not found")?;
+    if group.is_full() {
+        return Ok(None);
+    }
+    let mut waitlist = storage.get_waitlist(&group.competition_type()).await?;
+    let previous = waitlist.clone();
+
+    // Age classes of the group, e.g. a free place in a U12 group is given to a U12 athlete before a U8 athlete
+    let age_classes: HashSet<&str> = group.athletes()
+        .iter()
+        .filter(|athlete| !athlete.is_deregistered())
+        .filter_map(|athlete| athlete.age_class())
+        .map(|age_class| age_class.name.as_str())
+        .collect();
+    let mut entry_age_classes = HashMap::new();
+    for entry in &waitlist.entries {
+        if let Some(age_class) = storage.get_athlete(&entry.athlete_id).await.and_then(|athlete| athlete.age_class()) {
+            entry_age_classes.insert(entry.athlete_id.clone(), age_class.name.as_str());
+        }
+    }
+    let fits = |entry: &WaitlistEntry| entry_age_classes.get(&entry.athlete_id).is_some_and(|age_class| age_classes.contains(age_class));
+    let promotion = match waitlist.promote_next(group.name(), fits) {
+        Some(promotion) => promotion,
+        None => return Ok(None),
+    };
+
+    // The athlete is taken off the waitlist first, so a concurrent promotion can not take the same athlete.
+    // If the group can not be updated, the athlete is put back.
+    storage.store_waitlist(&waitlist).await?;
+    let json_string = serde_json::json!({"athlete_ids": [promotion.athlete_id]}).to_string();
+    let update = match group.athlete_ids().contains(&promotion.athlete_id) {
+        true => register_again(storage, group_id, &promotion.athlete_id, &json_string).await,
+        false => storage.update_group(group_id.clone(), &json_string, false).await,
+    };
+    if let Err(e) = update {
+        storage.store_waitlist(&previous).await?;
+        return Err(e);
+    }
+
+    info!("Athlete {} promoted from waitlist to {}", promotion.athlete_id.full_name(), promotion.group_name);
+    Ok(Some(promotion))
+}
+
+/// Athletes which deregistered and registered again are still part of the group, only their registration is restored
+async fn register_again(
+    storage: &(dyn Storage + Send + Sync),
+    group_id: &GroupID,
+    athlete_id: &AthleteID,
+    json_string: &str,
+) -> Result<String, Box<dyn Error>> {
+    storage.update_athlete(athlete_id.clone(), r#"{"deregistered": false}"#).await?;
+    storage.update_group(group_id.clone(), json_string, true).await
+}
+
+/// Put a deregistered athlete which registers again on the waitlist if the group is full in the meantime.
+/// Returns the position on the waitlist, None if the athlete can register (free place or in no group).
+pub async fn waitlist_if_full(
+    storage: &(dyn Storage + Send + Sync),
+    athlete_id: &AthleteID,
+) -> Result<Option<usize>, Box<dyn Error>> {
+    let group_id = match storage.get_athlete_group(athlete_id).await {
+        Some(group_id) => group_id,
+        None => return Ok(None),
+    };
+    let group = storage.get_group(&group_id).await.ok_or("Group not found")?;
+    if !group.is_full() {
+        return Ok(None);
+    }
+    let mut waitlist = storage.get_waitlist(&group.competition_type()).await?;
+    let position = waitlist.add(athlete_id.clone(), group.name());
+    storage.store_waitlist(&waitlist).await?;
+
+    info!("Group {} is full, athlete {} added to the waitlist", group.name(), athlete_id.full_name());
+    Ok(Some(position))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::certificate_generation::{AthleteID, CompetitionType};
+    use super::Waitlist;
+
+    #[test]
+    fn waitlist_order() {
+        let mut waitlist = Waitlist::new(CompetitionType::Decathlon);
+        assert_eq!(waitlist.add(AthleteID::new("Max", "Mustermann"), "Gruppe 1"), 1);
+        assert_eq!(waitlist.add(AthleteID::new("Erika", "Musterfrau"), "Gruppe 2"), 2);
+        assert_eq!(waitlist.add(AthleteID::new("Max", "Mustermann"), "Gruppe 1"), 1);
+
+        assert_eq!(waitlist.add(AthleteID::new("Hans", "Huber"), "Gruppe 3"), 3);
+
+        // Athletes registered for the group first, then athletes which fit into the group
+        let promotion = waitlist.promote_next("Gruppe 3", |_| false).expect("Waitlist should not be empty");
+        assert_eq!(promotion.athlete_id, AthleteID::new("Hans", "Huber"));
+        assert_eq!(promotion.group_name, "Gruppe 3");
+        let promotion = waitlist.promote_next("Gruppe 3", |entry| entry.group_name == "Gruppe 2").expect("Waitlist should not be empty");
+        assert_eq!(promotion.athlete_id, AthleteID::new("Erika", "Musterfrau"));
+        let promotion = waitlist.promote_next("Gruppe 3", |_| false).expect("Waitlist should not be empty");
+        assert_eq!(promotion.athlete_id, AthleteID::new("Max", "Mustermann"));
+        assert!(waitlist.entries.is_empty());
+        assert_eq!(waitlist.promotions.len(), 3);
+
+        waitlist.add(AthleteID::new("Erika", "Musterfrau"), "Gruppe 2");
+        assert!(waitlist.remove(&AthleteID::new("Erika", "Musterfrau")).is_some());
+        assert!(waitlist.promote_next("Gruppe 3", |_| true).is_none());
+    }
+}
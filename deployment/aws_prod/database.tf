@@ -21,7 +21,8 @@ resource "aws_iam_role_policy_attachment" "dynamodb_autoscale_policy_attach" {
 
 # ---------- DynamoDB Tables ----------
 
-# athlete_store (15/15 -> autoscale to 50/50)
+# athlete_store: single-competition schema, kept read-only until the cut-over to athlete_store_v2 is verified
+# (see deployment/MIGRATION.md)
 resource "aws_dynamodb_table" "athlete_store" {
   name           = "athlete_store"
   billing_mode   = "PROVISIONED"
@@ -41,12 +42,44 @@ resource "aws_dynamodb_table" "athlete_store" {
   point_in_time_recovery {
     enabled = true
   }
+
+  lifecycle {
+    prevent_destroy = true
+  }
+}
+
+# athlete_store_v2 (15/15 -> autoscale to 50/50)
+resource "aws_dynamodb_table" "athlete_store_v2" {
+  name           = "athlete_store_v2"
+  billing_mode   = "PROVISIONED"
+  hash_key       = "competition"
+  range_key      = "athlete_id"
+  read_capacity  = 15
+  write_capacity = 15
+
+  attribute {
+    name = "competition"
+    type = "S"
+  }
+
+  attribute {
+    name = "athlete_id"
+    type = "S"
+  }
+
+  tags = {
+    Name = "athlete_store_v2"
+  }
+
+  point_in_time_recovery {
+    enabled = true
+  }
 }
 
 resource "aws_appautoscaling_target" "athlete_store_read" {
   max_capacity       = 50
   min_capacity       = 15
-  resource_id        = "table/${aws_dynamodb_table.athlete_store.name}"
+  resource_id        = "table/${aws_dynamodb_table.athlete_store_v2.name}"
   scalable_dimension = "dynamodb:table:ReadCapacityUnits"
   service_namespace  = "dynamodb"
   role_arn           = aws_iam_role.dynamodb_autoscale_role.arn
@@ -72,7 +105,7 @@ resource "aws_appautoscaling_policy" "athlete_store_read_policy" {
 resource "aws_appautoscaling_target" "athlete_store_write" {
   max_capacity       = 50
   min_capacity       = 15
-  resource_id        = "table/${aws_dynamodb_table.athlete_store.name}"
+  resource_id        = "table/${aws_dynamodb_table.athlete_store_v2.name}"
   scalable_dimension = "dynamodb:table:WriteCapacityUnits"
   service_namespace  = "dynamodb"
   role_arn           = aws_iam_role.dynamodb_autoscale_role.arn
@@ -95,7 +128,8 @@ resource "aws_appautoscaling_policy" "athlete_store_write_policy" {
   }
 }
 
-# group_store (4/4 -> autoscale to 10/10)
+# group_store: single-competition schema, kept read-only until the cut-over to group_store_v2 is verified
+# (see deployment/MIGRATION.md)
 resource "aws_dynamodb_table" "group_store" {
   name           = "group_store"
   billing_mode   = "PROVISIONED"
@@ -107,12 +141,36 @@ resource "aws_dynamodb_table" "group_store" {
     name = "name"
     type = "S"
   }
+
+  lifecycle {
+    prevent_destroy = true
+  }
+}
+
+# group_store_v2 (4/4 -> autoscale to 10/10)
+resource "aws_dynamodb_table" "group_store_v2" {
+  name           = "group_store_v2"
+  billing_mode   = "PROVISIONED"
+  hash_key       = "competition"
+  range_key      = "name"
+  read_capacity  = 4
+  write_capacity = 4
+
+  attribute {
+    name = "competition"
+    type = "S"
+  }
+
+  attribute {
+    name = "name"
+    type = "S"
+  }
 }
 
 resource "aws_appautoscaling_target" "group_store_read" {
   max_capacity       = 10
   min_capacity       = 4
-  resource_id        = "table/${aws_dynamodb_table.group_store.name}"
+  resource_id        = "table/${aws_dynamodb_table.group_store_v2.name}"
   scalable_dimension = "dynamodb:table:ReadCapacityUnits"
   service_namespace  = "dynamodb"
   role_arn           = aws_iam_role.dynamodb_autoscale_role.arn
@@ -136,7 +194,7 @@ resource "aws_appautoscaling_policy" "group_store_read_policy" {
 resource "aws_appautoscaling_target" "group_store_write" {
   max_capacity       = 10
   min_capacity       = 4
-  resource_id        = "table/${aws_dynamodb_table.group_store.name}"
+  resource_id        = "table/${aws_dynamodb_table.group_store_v2.name}"
   scalable_dimension = "dynamodb:table:WriteCapacityUnits"
   service_namespace  = "dynamodb"
   role_arn           = aws_iam_role.dynamodb_autoscale_role.arn
@@ -157,7 +215,8 @@ resource "aws_appautoscaling_policy" "group_store_write_policy" {
   }
 }
 
-# time_group_store (3/3 -> autoscale to 10/10)
+# time_group_store: single-competition schema, kept read-only until the cut-over to time_group_store_v2 is verified
+# (see deployment/MIGRATION.md)
 resource "aws_dynamodb_table" "time_group_store" {
   name           = "time_group_store"
   billing_mode   = "PROVISIONED"
@@ -169,12 +228,36 @@ resource "aws_dynamodb_table" "time_group_store" {
     name = "name"
     type = "S"
   }
+
+  lifecycle {
+    prevent_destroy = true
+  }
+}
+
+# time_group_store_v2 (3/3 -> autoscale to 10/10)
+resource "aws_dynamodb_table" "time_group_store_v2" {
+  name           = "time_group_store_v2"
+  billing_mode   = "PROVISIONED"
+  hash_key       = "competition"
+  range_key      = "name"
+  read_capacity  = 3
+  write_capacity = 3
+
+  attribute {
+    name = "competition"
+    type = "S"
+  }
+
+  attribute {
+    name = "name"
+    type = "S"
+  }
 }
 
 resource "aws_appautoscaling_target" "time_group_store_read" {
   max_capacity       = 10
   min_capacity       = 3
-  resource_id        = "table/${aws_dynamodb_table.time_group_store.name}"
+  resource_id        = "table/${aws_dynamodb_table.time_group_store_v2.name}"
   scalable_dimension = "dynamodb:table:ReadCapacityUnits"
   service_namespace  = "dynamodb"
   role_arn           = aws_iam_role.dynamodb_autoscale_role.arn
@@ -198,7 +281,7 @@ resource "aws_appautoscaling_policy" "time_group_store_read_policy" {
 resource "aws_appautoscaling_target" "time_group_store_write" {
   max_capacity       = 10
   min_capacity       = 3
-  resource_id        = "table/${aws_dynamodb_table.time_group_store.name}"
+  resource_id        = "table/${aws_dynamodb_table.time_group_store_v2.name}"
   scalable_dimension = "dynamodb:table:WriteCapacityUnits"
   service_namespace  = "dynamodb"
   role_arn           = aws_iam_role.dynamodb_autoscale_role.arn
@@ -219,7 +302,8 @@ resource "aws_appautoscaling_policy" "time_group_store_write_policy" {
   }
 }
 
-# team_store (2/2, no autoscaling)
+# team_store: single-competition schema, kept read-only until the cut-over to team_store_v2 is verified
+# (see deployment/MIGRATION.md)
 resource "aws_dynamodb_table" "team_store" {
   name           = "team_store"
   billing_mode   = "PROVISIONED"
@@ -231,6 +315,30 @@ resource "aws_dynamodb_table" "team_store" {
     name = "team_name"
     type = "S"
   }
+
+  lifecycle {
+    prevent_destroy = true
+  }
+}
+
+# team_store_v2 (2/2, no autoscaling)
+resource "aws_dynamodb_table" "team_store_v2" {
+  name           = "team_store_v2"
+  billing_mode   = "PROVISIONED"
+  hash_key       = "competition"
+  range_key      = "team_name"
+  read_capacity  = 2
+  write_capacity = 2
+
+  attribute {
+    name = "competition"
+    type = "S"
+  }
+
+  attribute {
+    name = "team_name"
+    type = "S"
+  }
 }
 
 # authentication (3/3, no autoscaling)
@@ -247,6 +355,100 @@ resource "aws_dynamodb_table" "authentication" {
   }
 }
 
+# competition (5/1, no autoscaling), every write reads the active competition
+resource "aws_dynamodb_table" "competition_store" {
+  name           = "competition_store"
+  billing_mode   = "PROVISIONED"
+  hash_key       = "number"
+  read_capacity  = 5
+  write_capacity = 1
+
+  attribute {
+    name = "number"
+    type = "S"
+  }
+}
+
+# waitlist (1/1, no autoscaling)
+resource "aws_dynamodb_table" "waitlist_store" {
+  name           = "waitlist_store"
+  billing_mode   = "PROVISIONED"
+  hash_key       = "competition"
+  range_key      = "competition_type"
+  read_capacity  = 1
+  write_capacity = 1
+
+  attribute {
+    name = "competition"
+    type = "S"
+  }
+
+  attribute {
+    name = "competition_type"
+    type = "S"
+  }
+}
+
+# audit log (1/1, no autoscaling)
+resource "aws_dynamodb_table" "audit_store" {
+  name           = "audit_store"
+  billing_mode   = "PROVISIONED"
+  hash_key       = "competition"
+  range_key      = "id"
+  read_capacity  = 1
+  write_capacity = 1
+
+  attribute {
+    name = "competition"
+    type = "S"
+  }
+
+  attribute {
+    name = "id"
+    type = "S"
+  }
+}
+
+# officials (1/1, no autoscaling)
+resource "aws_dynamodb_table" "official_store" {
+  name           = "official_store"
+  billing_mode   = "PROVISIONED"
+  hash_key       = "competition"
+  range_key      = "name"
+  read_capacity  = 1
+  write_capacity = 1
+
+  attribute {
+    name = "competition"
+    type = "S"
+  }
+
+  attribute {
+    name = "name"
+    type = "S"
+  }
+}
+
+# notes (1/1, no autoscaling)
+resource "aws_dynamodb_table" "note_store" {
+  name           = "note_store"
+  billing_mode   = "PROVISIONED"
+  hash_key       = "competition"
+  range_key      = "id"
+  read_capacity  = 1
+  write_capacity = 1
+
+  attribute {
+    name = "competition"
+    type = "S"
+  }
+
+  attribute {
+    name = "id"
+    type = "S"
+  }
+}
+
 # ---------- Outputs ----------
 output "ec2_public_ip" {
   value = aws_eip.ec2_eip.public_ip
@@ -255,9 +457,18 @@ output "ec2_public_ip" {
 output "dynamodb_tables" {
   value = [
     aws_dynamodb_table.athlete_store.name,
+    aws_dynamodb_table.athlete_store_v2.name,
     aws_dynamodb_table.group_store.name,
+    aws_dynamodb_table.group_store_v2.name,
     aws_dynamodb_table.time_group_store.name,
+    aws_dynamodb_table.time_group_store_v2.name,
     aws_dynamodb_table.team_store.name,
-    aws_dynamodb_table.authentication.name
+    aws_dynamodb_table.team_store_v2.name,
+    aws_dynamodb_table.authentication.name,
+    aws_dynamodb_table.competition_store.name,
+    aws_dynamodb_table.waitlist_store.name,
+    aws_dynamodb_table.audit_store.name,
+    aws_dynamodb_table.official_store.name,
+    aws_dynamodb_table.note_store.name
   ]
 }
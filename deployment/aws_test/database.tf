@@ -21,7 +21,8 @@ resource "aws_iam_role_policy_attachment" "dynamodb_autoscale_policy_attach" {
 
 # ---------- DynamoDB Tables ----------
 
-# athlete_store (15/15 -> autoscale to 50/50)
+# athlete_store_test: single-competition schema, kept read-only until the cut-over to athlete_store_v2_test is verified
+# (see deployment/MIGRATION.md)
 resource "aws_dynamodb_table" "athlete_store_test" {
   name           = "athlete_store_test"
   billing_mode   = "PROVISIONED"
@@ -37,12 +38,40 @@ resource "aws_dynamodb_table" "athlete_store_test" {
   tags = {
     Name = "athlete_store_test"
   }
+
+  lifecycle {
+    prevent_destroy = true
+  }
+}
+
+# athlete_store_v2_test (15/15 -> autoscale to 50/50)
+resource "aws_dynamodb_table" "athlete_store_v2_test" {
+  name           = "athlete_store_v2_test"
+  billing_mode   = "PROVISIONED"
+  hash_key       = "competition"
+  range_key      = "athlete_id"
+  read_capacity  = 3
+  write_capacity = 3
+
+  attribute {
+    name = "competition"
+    type = "S"
+  }
+
+  attribute {
+    name = "athlete_id"
+    type = "S"
+  }
+
+  tags = {
+    Name = "athlete_store_v2_test"
+  }
 }
 
 resource "aws_appautoscaling_target" "athlete_store_test_read" {
   max_capacity       = 10
   min_capacity       = 1
-  resource_id        = "table/${aws_dynamodb_table.athlete_store_test.name}"
+  resource_id        = "table/${aws_dynamodb_table.athlete_store_v2_test.name}"
   scalable_dimension = "dynamodb:table:ReadCapacityUnits"
   service_namespace  = "dynamodb"
   role_arn           = aws_iam_role.dynamodb_autoscale_role.arn
@@ -68,7 +97,7 @@ resource "aws_appautoscaling_policy" "athlete_store_test_read_policy" {
 resource "aws_appautoscaling_target" "athlete_store_test_write" {
   max_capacity       = 10
   min_capacity       = 1
-  resource_id        = "table/${aws_dynamodb_table.athlete_store_test.name}"
+  resource_id        = "table/${aws_dynamodb_table.athlete_store_v2_test.name}"
   scalable_dimension = "dynamodb:table:WriteCapacityUnits"
   service_namespace  = "dynamodb"
   role_arn           = aws_iam_role.dynamodb_autoscale_role.arn
@@ -91,7 +120,8 @@ resource "aws_appautoscaling_policy" "athlete_store_test_write_policy" {
   }
 }
 
-# group_store_test (4/4 -> autoscale to 10/10)
+# group_store_test: single-competition schema, kept read-only until the cut-over to group_store_v2_test is verified
+# (see deployment/MIGRATION.md)
 resource "aws_dynamodb_table" "group_store_test" {
   name           = "group_store_test"
   billing_mode   = "PROVISIONED"
@@ -103,12 +133,36 @@ resource "aws_dynamodb_table" "group_store_test" {
     name = "name"
     type = "S"
   }
+
+  lifecycle {
+    prevent_destroy = true
+  }
+}
+
+# group_store_v2_test (4/4 -> autoscale to 10/10)
+resource "aws_dynamodb_table" "group_store_v2_test" {
+  name           = "group_store_v2_test"
+  billing_mode   = "PROVISIONED"
+  hash_key       = "competition"
+  range_key      = "name"
+  read_capacity  = 1
+  write_capacity = 1
+
+  attribute {
+    name = "competition"
+    type = "S"
+  }
+
+  attribute {
+    name = "name"
+    type = "S"
+  }
 }
 
 resource "aws_appautoscaling_target" "group_store_test_read" {
   max_capacity       = 5
   min_capacity       = 1
-  resource_id        = "table/${aws_dynamodb_table.group_store_test.name}"
+  resource_id        = "table/${aws_dynamodb_table.group_store_v2_test.name}"
   scalable_dimension = "dynamodb:table:ReadCapacityUnits"
   service_namespace  = "dynamodb"
   role_arn           = aws_iam_role.dynamodb_autoscale_role.arn
@@ -132,7 +186,7 @@ resource "aws_appautoscaling_policy" "group_store_test_read_policy" {
 resource "aws_appautoscaling_target" "group_store_test_write" {
   max_capacity       = 5
   min_capacity       = 1
-  resource_id        = "table/${aws_dynamodb_table.group_store_test.name}"
+  resource_id        = "table/${aws_dynamodb_table.group_store_v2_test.name}"
   scalable_dimension = "dynamodb:table:WriteCapacityUnits"
   service_namespace  = "dynamodb"
   role_arn           = aws_iam_role.dynamodb_autoscale_role.arn
@@ -153,7 +207,8 @@ resource "aws_appautoscaling_policy" "group_store_test_write_policy" {
   }
 }
 
-# time_group_store_test (3/3 -> autoscale to 10/10)
+# time_group_store_test: single-competition schema, kept read-only until the cut-over to time_group_store_v2_test is verified
+# (see deployment/MIGRATION.md)
 resource "aws_dynamodb_table" "time_group_store_test" {
   name           = "time_group_store_test"
   billing_mode   = "PROVISIONED"
@@ -165,12 +220,36 @@ resource "aws_dynamodb_table" "time_group_store_test" {
     name = "name"
     type = "S"
   }
+
+  lifecycle {
+    prevent_destroy = true
+  }
+}
+
+# time_group_store_v2_test (3/3 -> autoscale to 10/10)
+resource "aws_dynamodb_table" "time_group_store_v2_test" {
+  name           = "time_group_store_v2_test"
+  billing_mode   = "PROVISIONED"
+  hash_key       = "competition"
+  range_key      = "name"
+  read_capacity  = 2
+  write_capacity = 2
+
+  attribute {
+    name = "competition"
+    type = "S"
+  }
+
+  attribute {
+    name = "name"
+    type = "S"
+  }
 }
 
 resource "aws_appautoscaling_target" "time_group_store_test_read" {
   max_capacity       = 5
   min_capacity       = 1
-  resource_id        = "table/${aws_dynamodb_table.time_group_store_test.name}"
+  resource_id        = "table/${aws_dynamodb_table.time_group_store_v2_test.name}"
   scalable_dimension = "dynamodb:table:ReadCapacityUnits"
   service_namespace  = "dynamodb"
   role_arn           = aws_iam_role.dynamodb_autoscale_role.arn
@@ -194,7 +273,7 @@ resource "aws_appautoscaling_policy" "time_group_store_test_read_policy" {
 resource "aws_appautoscaling_target" "time_group_store_test_write" {
   max_capacity       = 5
   min_capacity       = 1
-  resource_id        = "table/${aws_dynamodb_table.time_group_store_test.name}"
+  resource_id        = "table/${aws_dynamodb_table.time_group_store_v2_test.name}"
   scalable_dimension = "dynamodb:table:WriteCapacityUnits"
   service_namespace  = "dynamodb"
   role_arn           = aws_iam_role.dynamodb_autoscale_role.arn
@@ -215,7 +294,8 @@ resource "aws_appautoscaling_policy" "time_group_store_test_write_policy" {
   }
 }
 
-# team_store_test (2/2, no autoscaling)
+# team_store_test: single-competition schema, kept read-only until the cut-over to team_store_v2_test is verified
+# (see deployment/MIGRATION.md)
 resource "aws_dynamodb_table" "team_store_test" {
   name           = "team_store_test"
   billing_mode   = "PROVISIONED"
@@ -227,6 +307,30 @@ resource "aws_dynamodb_table" "team_store_test" {
     name = "team_name"
     type = "S"
   }
+
+  lifecycle {
+    prevent_destroy = true
+  }
+}
+
+# team_store_v2_test (2/2, no autoscaling)
+resource "aws_dynamodb_table" "team_store_v2_test" {
+  name           = "team_store_v2_test"
+  billing_mode   = "PROVISIONED"
+  hash_key       = "competition"
+  range_key      = "team_name"
+  read_capacity  = 1
+  write_capacity = 1
+
+  attribute {
+    name = "competition"
+    type = "S"
+  }
+
+  attribute {
+    name = "team_name"
+    type = "S"
+  }
 }
 
 # authentication_test (3/3, no autoscaling)
@@ -243,6 +347,100 @@ resource "aws_dynamodb_table" "authentication_test" {
   }
 }
 
+# competition_test (1/1, no autoscaling)
+resource "aws_dynamodb_table" "competition_store_test" {
+  name           = "competition_store_test"
+  billing_mode   = "PROVISIONED"
+  hash_key       = "number"
+  read_capacity  = 1
+  write_capacity = 1
+
+  attribute {
+    name = "number"
+    type = "S"
+  }
+}
+
+# waitlist (1/1, no autoscaling)
+resource "aws_dynamodb_table" "waitlist_store_test" {
+  name           = "waitlist_store_test"
+  billing_mode   = "PROVISIONED"
+  hash_key       = "competition"
+  range_key      = "competition_type"
+  read_capacity  = 1
+  write_capacity = 1
+
+  attribute {
+    name = "competition"
+    type = "S"
+  }
+
+  attribute {
+    name = "competition_type"
+    type = "S"
+  }
+}
+
+# audit log (1/1, no autoscaling)
+resource "aws_dynamodb_table" "audit_store_test" {
+  name           = "audit_store_test"
+  billing_mode   = "PROVISIONED"
+  hash_key       = "competition"
+  range_key      = "id"
+  read_capacity  = 1
+  write_capacity = 1
+
+  attribute {
+    name = "competition"
+    type = "S"
+  }
+
+  attribute {
+    name = "id"
+    type = "S"
+  }
+}
+
+# officials (1/1, no autoscaling)
+resource "aws_dynamodb_table" "official_store_test" {
+  name           = "official_store_test"
+  billing_mode   = "PROVISIONED"
+  hash_key       = "competition"
+  range_key      = "name"
+  read_capacity  = 1
+  write_capacity = 1
+
+  attribute {
+    name = "competition"
+    type = "S"
+  }
+
+  attribute {
+    name = "name"
+    type = "S"
+  }
+}
+
+# notes (1/1, no autoscaling)
+resource "aws_dynamodb_table" "note_store_test" {
+  name           = "note_store_test"
+  billing_mode   = "PROVISIONED"
+  hash_key       = "competition"
+  range_key      = "id"
+  read_capacity  = 1
+  write_capacity = 1
+
+  attribute {
+    name = "competition"
+    type = "S"
+  }
+
+  attribute {
+    name = "id"
+    type = "S"
+  }
+}
+
 # ---------- Outputs ----------
 output "ec2_public_ip" {
   value = aws_eip.ec2_eip.public_ip
@@ -251,9 +449,18 @@ output "ec2_public_ip" {
 output "dynamodb_tables" {
   value = [
     aws_dynamodb_table.athlete_store_test.name,
+    aws_dynamodb_table.athlete_store_v2_test.name,
     aws_dynamodb_table.group_store_test.name,
+    aws_dynamodb_table.group_store_v2_test.name,
     aws_dynamodb_table.time_group_store_test.name,
+    aws_dynamodb_table.time_group_store_v2_test.name,
     aws_dynamodb_table.team_store_test.name,
-    aws_dynamodb_table.authentication_test.name
+    aws_dynamodb_table.team_store_v2_test.name,
+    aws_dynamodb_table.authentication_test.name,
+    aws_dynamodb_table.competition_store_test.name,
+    aws_dynamodb_table.waitlist_store_test.name,
+    aws_dynamodb_table.audit_store_test.name,
+    aws_dynamodb_table.official_store_test.name,
+    aws_dynamodb_table.note_store_test.name
   ]
 }
@@ -43,7 +43,7 @@ export function DisciplineEditButton(
     finish_discipline(group_name, state.discipline, (discipline) => {
       setState({ ...state, showPopup: false, discipline: discipline })
       update_discipline(discipline)
-    })
+    }, true)
   }
 
   const reset = function() {
@@ -232,6 +232,8 @@ function DefaultStartingOrder({ StartingOrder, saveStartingOrder }:
 function TrackStartingOrder({ StartingOrder, saveStartingOrder }:
   { StartingOrder: { name: string, athletes: (AthleteID | null)[] }[], saveStartingOrder: (order: StartingOrder) => void }) {
   const [currentRuns, setcurrentRuns] = useState(StartingOrder)
+  // Number of lanes of the venue, given by the runs of the backend
+  const lanes = StartingOrder.length > 0 ? Math.max(...StartingOrder.map(run => run.athletes.length)) : 6
   const [lastDraggedOver, setLastDraggedOver] = useState({ run_index: NaN, row_index: NaN })
   const [draggedRow, setDraggedRow] = useState({ run_index: NaN, row_index: NaN, cell_empty: false })
 
@@ -308,7 +310,7 @@ function TrackStartingOrder({ StartingOrder, saveStartingOrder }:
 
   const addRun = function () {
     let newRuns = [...currentRuns]
-    newRuns.push({ name: "Lauf " + (newRuns.length + 1).toString(), athletes: [null, null, null, null, null, null] })
+    newRuns.push({ name: "Lauf " + (newRuns.length + 1).toString(), athletes: Array(lanes).fill(null) })
     setcurrentRuns(newRuns);
   }
 
@@ -320,10 +322,10 @@ function TrackStartingOrder({ StartingOrder, saveStartingOrder }:
     let used_runs: { name: string, athletes: (AthleteID | null)[] }[] = []
 
     currentRuns.forEach(run => {
-      if (run.athletes.length > 6) {
+      if (run.athletes.length > lanes) {
         runs_correct = false;
         setSubmitted("wrong")
-        alert("Maximal 6 Athlet:innen pro Lauf erlaubt")
+        alert("Maximal " + lanes + " Athlet:innen pro Lauf erlaubt")
       } else if (run.athletes.length > 0) {
         used_runs.push(run)
       }
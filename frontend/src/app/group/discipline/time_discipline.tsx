@@ -192,6 +192,8 @@ function StartingOrderSummary({ starting_order, saveStartingOrder, finishDiscipl
     const [editActive, setEditActive] = useState(false)
     const [openRuns, setOpenRuns] = useState<Set<string>>(new Set([]));
     const [currentRuns, setcurrentRuns] = useState(starting_order)
+    // Number of lanes of the venue, given by the runs of the backend
+    const lanes = starting_order.length > 0 ? Math.max(...starting_order.map(run => run.athletes.length)) : 6
     const [lastDraggedOver, setLastDraggedOver] = useState({ run_index: NaN, row_index: NaN })
     const [submitted, setSubmitted] = useState("")
 
@@ -248,7 +250,7 @@ function StartingOrderSummary({ starting_order, saveStartingOrder, finishDiscipl
     }
     const addRun = function () {
         let newRuns = [...currentRuns]
-        newRuns.push({ name: "Lauf " + (newRuns.length + 1).toString(), athletes: [null, null, null, null, null, null] })
+        newRuns.push({ name: "Lauf " + (newRuns.length + 1).toString(), athletes: Array(lanes).fill(null) })
         setcurrentRuns(newRuns);
     }
 
@@ -260,10 +262,10 @@ function StartingOrderSummary({ starting_order, saveStartingOrder, finishDiscipl
         let used_runs: { name: string, athletes: (AthleteID | null)[] }[] = []
 
         currentRuns.forEach(run => {
-            if (run.athletes.length > 6) {
+            if (run.athletes.length > lanes) {
                 runs_correct = false;
                 setSubmitted("wrong")
-                alert("Maximal 6 Athlet:innen pro Lauf erlaubt")
+                alert("Maximal " + lanes + " Athlet:innen pro Lauf erlaubt")
             } else if (run.athletes.length > 0) {
                 used_runs.push(run)
             }
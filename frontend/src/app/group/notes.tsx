@@ -2,7 +2,7 @@ import Title_Footer_Layout from "./subpage_layout";
 import { discipline_mapping } from "../lib/config";
 import { useEffect, useState } from "react";
 import { useAsyncError } from "../lib/asyncError";
-import { Discipline } from "../lib/interfaces";
+import { Discipline, NoteEntry } from "../lib/interfaces";
 import { set } from "zod";
 
 export default function NotesPage({discipline, group_name}:{discipline: string, group_name: string}) {
@@ -16,6 +16,7 @@ export default function NotesPage({discipline, group_name}:{discipline: string,
 
 export function Notes({page, group_name}:{page: string, group_name: string}) {
     const [selectedDiscipline, setSelectedDiscipline] = useState("Allgemein");
+    const [entries, setEntries] = useState<NoteEntry[]>([]);
     const [content, setContent] = useState("");
     const [disciplines, setGroupDisciplines] = useState<string[]>([]);
     const [noteSaved, setNoteSaved] = useState(true);
@@ -46,45 +47,52 @@ export function Notes({page, group_name}:{page: string, group_name: string}) {
             })
     }, [group_name])
 
-    // Fetch the content based on the selected discipline
+    // Fetch the notes of the selected discipline
     useEffect(() => {  
-        let api_url = `/api/notes?discipline=${selectedDiscipline}&group_name=${group_name}`;
+        let api_url = `/api/note_entries?discipline=${selectedDiscipline}&group_name=${group_name}`;
         fetch(api_url)
             .then(res => {
                 if (res.ok) {
-                    return res.text();
-                } else if (res.status === 404) {
-                    // If the note does not exist, return an empty string
-                    return "";
+                    return res.json();
                 } else {
                     throw new Error(`Network response was not ok: ${res.status} - ${res.statusText}`);
                 }
             })
-            .then(text => {
-                setContent(text);
+            .then(res => {
+                setEntries(res as NoteEntry[]);
             })
             .catch((e) => {
                 console.error("Error fetching notes:", e);
-                setContent("Fehler beim Laden der Notizen.")
+                setEntries([]);
+                alert("Fehler beim Laden der Notizen.");
             });
     }, [selectedDiscipline]);
 
+    // Notes are never overwritten, every save adds a new note
     const saveNote = () => {
-        let api_url = `/api/notes?discipline=${selectedDiscipline}&group_name=${group_name}`;
+        if (content.trim() === "") {
+            return;
+        }
+        let api_url = `/api/note_entry?discipline=${selectedDiscipline}&group_name=${group_name}`;
         fetch(api_url, {
             method: 'POST',
             headers: {
-                'Content-Type': 'text/plain',
+                'Content-Type': 'application/json',
             },
-            body: content,
+            body: JSON.stringify({text: content}),
         })
         .then(res => {
             if (res.ok) {
-                setNoteSaved(true);
+                return res.json();
             } else {
                 throw new Error(`Network response was not ok: ${res.status} - ${res.statusText}`);
             }
         })
+        .then(res => {
+            setEntries([...entries, res as NoteEntry]);
+            setContent("");
+            setNoteSaved(true);
+        })
         .catch((e) => {
             console.error("Error saving note:", e);
             alert("Fehler beim Speichern der Notiz.");
@@ -111,10 +119,19 @@ export function Notes({page, group_name}:{page: string, group_name: string}) {
                 </select>
             </div>
             <div className="flex flex-col items-right w-full text-lg p-4 mt-4 rounded-md shadow-md shadow-slate-700 bg-gray-100">
+                {entries.map((entry) => (
+                    <div key={entry.id} className="w-full p-2 mb-2 rounded border border-gray-300 bg-white">
+                        {
+                            !entry.legacy &&
+                            <div className="text-sm text-gray-500">{new Date(entry.timestamp).toLocaleString("de-AT")}</div>
+                        }
+                        <div className="whitespace-pre-wrap">{entry.text}</div>
+                    </div>
+                ))}
                 <textarea 
                 key="note"
                 className="w-full min-h-[35vh] p-2 rounded border border-gray-300 bg-white" 
-                placeholder="Hier Notizen eingeben..."
+                placeholder="Neue Notiz eingeben..."
                 value={content}
                 onChange={(e) => noteUpdated(e.target.value)}>
                 </textarea>
@@ -1,6 +1,7 @@
 import { Discipline } from "./interfaces";
 
-export function finish_discipline(group_name: string, discipline: Discipline, callback_fn: (discipline: Discipline) => void) {
+/// force: finish even if results are missing (admin override)
+export function finish_discipline(group_name: string, discipline: Discipline, callback_fn: (discipline: Discipline) => void, force: boolean = false) {
     let api_url = `/api/discipline_state?name=${group_name}`
 
     fetch(api_url, {
@@ -10,7 +11,8 @@ export function finish_discipline(group_name: string, discipline: Discipline, ca
         },
         body: JSON.stringify({
             "name": discipline.name,
-            "state": "Finished"
+            "state": "Finished",
+            "force": force
         })
     })
         .then(res => {
@@ -39,7 +41,8 @@ export function reset_discipline(group_name: string, discipline: Discipline, cal
         },
         body: JSON.stringify({
             "name": discipline.name,
-            "state": "BeforeStart"
+            "state": "BeforeStart",
+            "force": true
         })
     })
         .then(res => {
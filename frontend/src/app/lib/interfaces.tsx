@@ -18,6 +18,17 @@ export interface StartingOrder {
     Default?: AthleteID[]
 }
 
+export interface NoteEntry {
+    id: string,
+    group_name: string,
+    discipline: string,
+    timestamp: string,
+    role?: string,
+    text: string,
+    category: "Injury" | "Protest" | "Equipment" | "Weather" | "General",
+    legacy?: boolean
+}
+
 
 export class AthleteID {
     name: string;